@@ -29,7 +29,7 @@ typedef enum {
    */
   ARGONAUTICA_ERROR_ADDITIONAL_DATA_TOO_LONG = 1,
   /*
-   * Rust backend not yet supported. Please use the C backend
+   * Backend not supported. The C backend requires the backend-c feature
    */
   ARGONAUTICA_ERROR_BACKEND_UNSUPPORTED = 2,
   /*
@@ -124,6 +124,10 @@ typedef enum {
    * Utf-8 encode error. Bytes provided could not be encoded into utf-8
    */
   ARGONAUTICA_ERROR_UTF8_ENCODE = 25,
+  /*
+   * Verifier policy error. The parameters of the hash provided exceed the limits of the verifier's policy
+   */
+  ARGONAUTICA_ERROR_VERIFIER_POLICY = 26,
 } argonautica_error_t;
 
 /*
@@ -19,7 +19,7 @@ pub enum argonautica_error_t {
     /// Additional data too long. Length in bytes must be less than 2^32
     ARGONAUTICA_ERROR_ADDITIONAL_DATA_TOO_LONG = 1,
 
-    /// Rust backend not yet supported. Please use the C backend
+    /// Backend not supported. The C backend requires the backend-c feature
     ARGONAUTICA_ERROR_BACKEND_UNSUPPORTED = 2,
 
     /// Base64 decode error. Bytes provided were invalid base64
@@ -90,6 +90,9 @@ pub enum argonautica_error_t {
 
     /// Utf-8 encode error. Bytes provided could not be encoded into utf-8
     ARGONAUTICA_ERROR_UTF8_ENCODE = 25,
+
+    /// Verifier policy error. The parameters of the hash provided exceed the limits of the verifier's policy
+    ARGONAUTICA_ERROR_VERIFIER_POLICY = 26,
 }
 
 impl argonautica_error_t {
@@ -99,7 +102,7 @@ impl argonautica_error_t {
         let s: &'static [u8] = match self {
             ARGONAUTICA_OK => b"OK. No error occurred\0",
             ARGONAUTICA_ERROR_ADDITIONAL_DATA_TOO_LONG => b"Additional data too long. Length in bytes must be less than 2^32\0",
-            ARGONAUTICA_ERROR_BACKEND_UNSUPPORTED => b"Rust backend not yet supported. Please use the C backend\0",
+            ARGONAUTICA_ERROR_BACKEND_UNSUPPORTED => b"Backend not supported. The C backend requires the backend-c feature\0",
             ARGONAUTICA_ERROR_BASE64_DECODE => b"Base64 decode error. Bytes provided were invalid base64\0",
             ARGONAUTICA_ERROR_BUG => b"This is a bug in the argonautica crate and should not occur. Please file an issue\0",
             ARGONAUTICA_ERROR_HASH_DECODE => b"Hash decode error. Hash provided was invalid\0",
@@ -123,6 +126,7 @@ impl argonautica_error_t {
             ARGONAUTICA_ERROR_THREADS_TOO_FEW => b"Threads too few. Threads must be greater than 0\0",
             ARGONAUTICA_ERROR_THREADS_TOO_MANY => b"Threads too many. Threads must be less than 2^24\0",
             ARGONAUTICA_ERROR_UTF8_ENCODE => b"Utf-8 encode error. Bytes provided could not be encoded into utf-8\0",
+            ARGONAUTICA_ERROR_VERIFIER_POLICY => b"Verifier policy error. The parameters of the hash provided exceed the limits of the verifier's policy\0",
         };
         s.as_ptr() as *const c_char
     }
@@ -134,17 +138,32 @@ impl From<Error> for argonautica_error_t {
         use argonautica_error_t::*;
         match err.kind() {
             AdditionalDataTooLongError => ARGONAUTICA_ERROR_ADDITIONAL_DATA_TOO_LONG,
+            // Unreachable from C: the C API has no audits
+            AuditInputError => ARGONAUTICA_ERROR_BUG,
             BackendEncodeError => ARGONAUTICA_ERROR_BUG,
             BackendUnsupportedError => ARGONAUTICA_ERROR_BACKEND_UNSUPPORTED,
             Base64DecodeError => ARGONAUTICA_ERROR_BASE64_DECODE,
             Bug => ARGONAUTICA_ERROR_BUG,
+            // Unreachable from C: the C API has no envelopes
+            EnvelopeDecodeError => ARGONAUTICA_ERROR_BUG,
+            EnvelopePassphraseError => ARGONAUTICA_ERROR_BUG,
+            EnvelopeTamperedError => ARGONAUTICA_ERROR_BUG,
             HashDecodeError => ARGONAUTICA_ERROR_HASH_DECODE,
             HashLenTooShortError => ARGONAUTICA_ERROR_HASH_LEN_TOO_SHORT,
             HashMissingError => ARGONAUTICA_ERROR_HASH_MISSING,
             IterationsTooFewError => ARGONAUTICA_ERROR_ITERATIONS_TOO_FEW,
+            // Unreachable from C: the C API has no key derivation
+            KdfSubkeysInvalidError => ARGONAUTICA_ERROR_BUG,
+            // Unreachable from C: the C API has no keyring
+            KeyIdInvalidError => ARGONAUTICA_ERROR_BUG,
+            KeyIdUnknownError => ARGONAUTICA_ERROR_BUG,
             LanesTooFewError => ARGONAUTICA_ERROR_LANES_TOO_FEW,
             LanesTooManyError => ARGONAUTICA_ERROR_LANES_TOO_MANY,
             MemoryAllocationError => ARGONAUTICA_ERROR_MEMORY_ALLOCATION,
+            // Unreachable from C: the C API doesn't configure a memory budget
+            MemoryBudgetExceededError => ARGONAUTICA_ERROR_BUG,
+            // Unreachable from C: the C API has no memory allocators or locked inputs
+            MemoryLockError => ARGONAUTICA_ERROR_BUG,
             MemorySizeInvalidError => ARGONAUTICA_ERROR_MEMORY_SIZE_INVALID,
             MemorySizeTooSmallError => ARGONAUTICA_ERROR_MEMORY_SIZE_TOO_SMALL,
             OsRngError => ARGONAUTICA_ERROR_OS_RNG,
@@ -162,6 +181,7 @@ impl From<Error> for argonautica_error_t {
             ThreadsTooManyError => ARGONAUTICA_ERROR_THREADS_TOO_MANY,
             Utf8EncodeError => ARGONAUTICA_ERROR_UTF8_ENCODE,
             VariantEncodeError => ARGONAUTICA_ERROR_BUG,
+            VerifierPolicyError => ARGONAUTICA_ERROR_VERIFIER_POLICY,
             VersionEncodeError => ARGONAUTICA_ERROR_BUG,
             __Nonexhaustive => ARGONAUTICA_ERROR_BUG,
         }
@@ -25,7 +25,6 @@
 //!     * `git submodule init`
 //!     * `git submodule update`
 //! * Build the library using [Cargo](https://github.com/rust-lang/cargo)
-//!     * `cargo build --release --features="simd"`, or
 //!     * `cargo build --release`
 //! * Use the library
 //!     * The library, which will be called `libargonautica_c.dylib` or something similar (depending on your OS), will be in the `./target/release` directory
@@ -16,13 +16,11 @@ hasher.additional_data = None  # Default is None
 # do with a secret key). Again, this is rarely used.
 
 hasher.backend = Backend.C  # Default is Backend.C
-# 👆 argonautica was designed to support multiple backends (meaning multiple
-# implementations of the underlying argon2 algorithm). Currently only the
-# C backend is supported, which uses the canonical argon2 library written
-# in C to actually do the work. In the future a Rust backend will also be
-# supported, but, for the moment, you must use Backend.C, which is the
-# default. Using Backend.Rust will result in an error (again, for the
-# moment).
+# 👆 argonautica supports multiple backends (meaning multiple
+# implementations of the underlying argon2 algorithm). The C backend uses
+# the canonical argon2 library written in C to actually do the work; the
+# Rust backend is a pure Rust implementation. Both produce exactly the
+# same hashes.
 
 hasher.hash_len = 32  # Default is 32
 # 👆 The hash length in bytes is configurable. The default is 32.
@@ -1,32 +1,30 @@
+#[cfg(feature = "backend-c")]
 extern crate bindgen;
+#[cfg(feature = "backend-c")]
 extern crate cc;
-#[macro_use]
-extern crate cfg_if;
 extern crate failure;
+#[cfg(feature = "backend-c")]
 extern crate tempfile;
 
+#[cfg(feature = "backend-c")]
 use std::env;
+#[cfg(feature = "backend-c")]
 use std::fs;
-use std::path::Path;
+#[cfg(feature = "backend-c")]
+use std::path::{Path, PathBuf};
 
-cfg_if! {
-    if #[cfg(feature = "simd")] {
-        const IS_SIMD: bool = true;
-    } else {
-        const IS_SIMD: bool = false;
-    }
+// Without the C backend there is nothing to compile; the Rust backend is plain Rust
+#[cfg(not(feature = "backend-c"))]
+fn main() -> Result<(), failure::Error> {
+    Ok(())
 }
 
+#[cfg(feature = "backend-c")]
 fn main() -> Result<(), failure::Error> {
     let temp = tempfile::tempdir()?;
     let temp_dir = temp.path();
     let temp_dir_str = temp_dir.to_str().unwrap();
 
-    let blamka_header = if IS_SIMD {
-        "phc-winner-argon2/src/blake2/blamka-round-opt.h"
-    } else {
-        "phc-winner-argon2/src/blake2/blamka-round-ref.h"
-    };
     for header_path_str in &[
         "phc-winner-argon2/include/argon2.h",
         "phc-winner-argon2/src/core.h",
@@ -34,7 +32,8 @@ fn main() -> Result<(), failure::Error> {
         "phc-winner-argon2/src/thread.h",
         "phc-winner-argon2/src/blake2/blake2-impl.h",
         "phc-winner-argon2/src/blake2/blake2.h",
-        blamka_header,
+        "phc-winner-argon2/src/blake2/blamka-round-opt.h",
+        "phc-winner-argon2/src/blake2/blamka-round-ref.h",
     ] {
         let header_path = Path::new(*header_path_str);
         let header_filename = header_path.file_name().unwrap();
@@ -43,12 +42,19 @@ fn main() -> Result<(), failure::Error> {
         fs::copy(from, to)?;
     }
 
-    let blamka_code = if IS_SIMD {
-        "phc-winner-argon2/src/opt.c"
-    } else {
-        "phc-winner-argon2/src/ref.c"
-    };
-    let mut builder = cc::Build::new();
+    // Both ref.c and opt.c define `fill_segment`, which is where all of the block compression
+    // happens. We compile ref.c once and, on x86 and x86_64, opt.c once for SSE2 and once for
+    // AVX2, renaming each copy of `fill_segment` with a preprocessor define. A small shim
+    // (dispatch.c) then provides the `fill_segment` that core.c calls, forwarding to whichever
+    // copy was selected at runtime by argonautica's CPU feature detection
+    let opt_level = env::var("OPT_LEVEL")?.parse::<usize>()?;
+    let target_arch = env::var("CARGO_CFG_TARGET_ARCH")?;
+    let is_x86 = target_arch == "x86" || target_arch == "x86_64";
+
+    let dispatch_path = temp_dir.join("dispatch.c");
+    fs::write(&dispatch_path, DISPATCH_C)?;
+
+    let mut builder = base_builder(temp_dir, opt_level);
     builder
         .files(&[
             "phc-winner-argon2/src/argon2.c",
@@ -56,19 +62,35 @@ fn main() -> Result<(), failure::Error> {
             "phc-winner-argon2/src/blake2/blake2b.c",
             "phc-winner-argon2/src/encoding.c",
             "phc-winner-argon2/src/thread.c",
-            blamka_code,
         ])
-        .include(temp_dir)
-        .flag_if_supported("-pthread")
-        .flag_if_supported("-std=c89")
-        .warnings(false)
-        .extra_warnings(false);
-    if IS_SIMD {
-        builder.flag_if_supported("-march=native");
-    }
-    let opt_level = env::var("OPT_LEVEL")?.parse::<usize>()?;
-    if opt_level < 3 {
-        builder.flag_if_supported("-g");
+        .file(&dispatch_path);
+    builder.object(compile_fill_segment(
+        temp_dir,
+        opt_level,
+        "phc-winner-argon2/src/ref.c",
+        "ref",
+        &[],
+        &[],
+    )?);
+    if is_x86 {
+        builder
+            .define("ARGONAUTICA_X86", None)
+            .object(compile_fill_segment(
+                temp_dir,
+                opt_level,
+                "phc-winner-argon2/src/opt.c",
+                "sse2",
+                &["-msse2"],
+                &[],
+            )?)
+            .object(compile_fill_segment(
+                temp_dir,
+                opt_level,
+                "phc-winner-argon2/src/opt.c",
+                "avx2",
+                &["-mavx2"],
+                &["/arch:AVX2"],
+            )?);
     }
     builder.compile("argon2");
 
@@ -78,10 +100,15 @@ fn main() -> Result<(), failure::Error> {
     let bindings = bindgen::Builder::default()
         .header(format!("{}/argon2.h", temp_dir_str))
         .header(format!("{}/encoding.h", temp_dir_str))
+        .header_contents(
+            "dispatch.h",
+            "void argonautica_set_block_compression(int block_compression);",
+        )
         .whitelist_function("argon2_ctx")
         .whitelist_function("argon2_encodedlen")
         .whitelist_function("argon2_error_message")
         .whitelist_function("argon2_verify_ctx")
+        .whitelist_function("argonautica_set_block_compression")
         .whitelist_function("decode_string")
         .whitelist_function("encode_string")
         .whitelist_type("Argon2_ErrorCodes")
@@ -96,3 +123,92 @@ fn main() -> Result<(), failure::Error> {
 
     Ok(())
 }
+
+// Values must match the discriminants of `config::BlockCompression`
+#[cfg(feature = "backend-c")]
+const DISPATCH_C: &str = r#"
+#include "core.h"
+
+void argonautica_fill_segment_ref(const argon2_instance_t *instance, argon2_position_t position);
+#ifdef ARGONAUTICA_X86
+void argonautica_fill_segment_sse2(const argon2_instance_t *instance, argon2_position_t position);
+void argonautica_fill_segment_avx2(const argon2_instance_t *instance, argon2_position_t position);
+#endif
+
+static int argonautica_block_compression = 0;
+
+void argonautica_set_block_compression(int block_compression) {
+    argonautica_block_compression = block_compression;
+}
+
+void fill_segment(const argon2_instance_t *instance, argon2_position_t position) {
+    switch (argonautica_block_compression) {
+#ifdef ARGONAUTICA_X86
+    case 2:
+        argonautica_fill_segment_avx2(instance, position);
+        return;
+    case 1:
+        argonautica_fill_segment_sse2(instance, position);
+        return;
+#endif
+    default:
+        argonautica_fill_segment_ref(instance, position);
+        return;
+    }
+}
+"#;
+
+#[cfg(feature = "backend-c")]
+fn base_builder(temp_dir: &Path, opt_level: usize) -> cc::Build {
+    let mut builder = cc::Build::new();
+    builder
+        .include(temp_dir)
+        .flag_if_supported("-pthread")
+        .flag_if_supported("-std=c89")
+        .warnings(false)
+        .extra_warnings(false);
+    if opt_level < 3 {
+        builder.flag_if_supported("-g");
+    }
+    builder
+}
+
+/// Compiles `path` (either ref.c or opt.c) into a standalone object file whose `fill_segment`
+/// is renamed to `argonautica_fill_segment_{suffix}`, enabling the instruction set given by
+/// `flags` (or `msvc_flags` when compiling with MSVC)
+#[cfg(feature = "backend-c")]
+fn compile_fill_segment(
+    temp_dir: &Path,
+    opt_level: usize,
+    path: &str,
+    suffix: &str,
+    flags: &[&str],
+    msvc_flags: &[&str],
+) -> Result<PathBuf, failure::Error> {
+    let mut builder = base_builder(temp_dir, opt_level);
+    let fill_segment = format!("argonautica_fill_segment_{}", suffix);
+    builder.define("fill_segment", Some(fill_segment.as_str()));
+    let is_msvc = builder.get_compiler().is_like_msvc();
+    for flag in if is_msvc { msvc_flags } else { flags } {
+        builder.flag(flag);
+    }
+    let compiler = builder.get_compiler();
+    let object_path = temp_dir.join(format!("fill_segment_{}.o", suffix));
+    let mut command = compiler.to_command();
+    if is_msvc {
+        command
+            .arg("/c")
+            .arg(format!("/Fo{}", object_path.display()))
+            .arg(path);
+    } else {
+        command.arg("-c").arg("-o").arg(&object_path).arg(path);
+    }
+    let status = command.status()?;
+    if !status.success() {
+        return Err(failure::err_msg(format!(
+            "failed to compile {} ({})",
+            path, suffix
+        )));
+    }
+    Ok(object_path)
+}
@@ -0,0 +1,653 @@
+//! Control over where the Argon2 work area lives (see
+//! [`MemoryAllocator`](trait.MemoryAllocator.html)).
+//!
+//! Every hash allocates a work area (the Argon2 "block matrix") of `memory_size` kibibytes,
+//! which holds values derived from the password. By default it is allocated on the heap, but a
+//! [`Hasher`](../struct.Hasher.html) or [`Verifier`](../struct.Verifier.html) configured with a
+//! [`MemoryAllocator`](trait.MemoryAllocator.html) (see
+//! [`Hasher::configure_memory_allocator`](../struct.Hasher.html#method.configure_memory_allocator))
+//! obtains it from the allocator instead, with either backend. This lets you, for example,
+//! reuse memory across hashes, lock it into RAM or surround it with guard pages.
+//!
+//! Four allocators are provided:
+//! * [`SystemAllocator`](struct.SystemAllocator.html), which allocates on the heap,
+//! * [`ArenaAllocator`](struct.ArenaAllocator.html), which keeps one work area per thread and
+//!   reuses it from one hash to the next,
+//! * [`LockedAllocator`](struct.LockedAllocator.html), which locks the work area into RAM, and
+//! * [`GuardedAllocator`](struct.GuardedAllocator.html), which surrounds the work area with
+//!   guard pages.
+//!
+//! No allocator backed by huge pages is provided: it would need huge pages reserved by the
+//! system administrator, and the work area is usually too small to benefit from them. You can
+//! implement one on top of `mmap` with [`MemoryAllocator`](trait.MemoryAllocator.html)
+use std::alloc::{self, Layout};
+use std::cell::RefCell;
+use std::fmt;
+use std::mem;
+#[cfg(unix)]
+use std::ptr;
+use std::ptr::NonNull;
+
+#[cfg(unix)]
+use libc;
+
+use utils::wipe_raw;
+use {Error, ErrorKind};
+
+/// The alignment (in bytes) of every work area requested from a
+/// [`MemoryAllocator`](trait.MemoryAllocator.html)
+pub const MEMORY_ALIGNMENT: usize = 64;
+
+/// Trait for allocators of the Argon2 work area.
+///
+/// For each hash, [`allocate`](trait.MemoryAllocator.html#tymethod.allocate) is called once
+/// with a `layout` of `memory_size` kibibytes (give or take a few blocks, depending on the
+/// number of lanes) aligned to [`MEMORY_ALIGNMENT`](constant.MEMORY_ALIGNMENT.html) bytes, and
+/// [`deallocate`](trait.MemoryAllocator.html#tymethod.deallocate) is called once with the
+/// same pointer and layout when hashing is done. Allocators are shared across threads, so
+/// they may be called concurrently.
+///
+/// # Safety
+///
+/// Implementations must return memory that is valid for reads and writes of `layout.size()`
+/// bytes, aligned to `layout.align()`, and not used by anything else until it is deallocated
+pub unsafe trait MemoryAllocator: fmt::Debug + Send + Sync {
+    /// Allocates a work area described by `layout`. Returns an [`Error`](../struct.Error.html)
+    /// (typically of kind
+    /// [`MemoryAllocationError`](../enum.ErrorKind.html#variant.MemoryAllocationError)) if the
+    /// memory cannot be allocated; hashing then fails with that error
+    fn allocate(&self, layout: Layout) -> Result<NonNull<u8>, Error>;
+    /// Deallocates a work area.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must have been returned by a call to
+    /// [`allocate`](trait.MemoryAllocator.html#tymethod.allocate) on this allocator with the
+    /// same `layout`, and must not have been deallocated already
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout);
+}
+
+/// A [`MemoryAllocator`](trait.MemoryAllocator.html) that allocates on the heap with Rust's
+/// global allocator, i.e. the same memory you get without configuring an allocator. Useful as
+/// a building block for other allocators
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemAllocator;
+
+unsafe impl MemoryAllocator for SystemAllocator {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<u8>, Error> {
+        if layout.size() == 0 {
+            return Err(Error::new(ErrorKind::MemoryAllocationError).add_context("Size: 0"));
+        }
+        let ptr = unsafe { alloc::alloc(layout) };
+        NonNull::new(ptr).ok_or_else(|| {
+            Error::new(ErrorKind::MemoryAllocationError)
+                .add_context(format!("Size: {}", layout.size()))
+        })
+    }
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        alloc::dealloc(ptr.as_ptr(), layout)
+    }
+}
+
+thread_local! {
+    static ARENA: RefCell<Option<Arena>> = RefCell::default();
+}
+
+/// A [`MemoryAllocator`](trait.MemoryAllocator.html) that allocates the work area of a thread
+/// once and reuses it for every subsequent hash on that thread, instead of allocating and
+/// freeing `memory_size` kibibytes for every hash. Between hashes, the work area is wiped
+/// (overwritten with zeros).
+///
+/// Each thread keeps at most one work area, which is reused as long as hashes on that thread
+/// need exactly the same amount of memory (i.e. use the same `memory_size` and `lanes`);
+/// otherwise it is freed and replaced. It is freed when the thread exits or when
+/// [`release`](struct.ArenaAllocator.html#method.release) is called on that thread. The work
+/// areas belong to the threads, not to the allocator; so all
+/// [`ArenaAllocator`](struct.ArenaAllocator.html)s share them. This works best when hashing
+/// on a fixed set of long-lived threads, e.g. the threads of a
+/// [`ThreadPool`](../executor/struct.ThreadPool.html)
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ArenaAllocator;
+
+/// The work area of a thread, while it is not in use
+struct Arena {
+    layout: Layout,
+    ptr: NonNull<u8>,
+}
+
+impl Drop for Arena {
+    fn drop(&mut self) {
+        unsafe { SystemAllocator.deallocate(self.ptr, self.layout) };
+    }
+}
+
+impl ArenaAllocator {
+    /// Creates a new [`ArenaAllocator`](struct.ArenaAllocator.html)
+    pub fn new() -> ArenaAllocator {
+        ArenaAllocator
+    }
+    /// Frees the work area of the current thread, if any. Use it on a thread that will not hash
+    /// again for a while
+    pub fn release(&self) {
+        ARENA.with(|arena| drop(arena.borrow_mut().take()));
+    }
+}
+
+unsafe impl MemoryAllocator for ArenaAllocator {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<u8>, Error> {
+        let arena = ARENA
+            .try_with(|arena| arena.borrow_mut().take())
+            .unwrap_or(None);
+        match arena {
+            Some(arena) => {
+                if arena.layout == layout {
+                    let ptr = arena.ptr;
+                    // The work area is handed out, so it must not be freed with `arena`
+                    mem::forget(arena);
+                    return Ok(ptr);
+                }
+                // A work area of a different size is freed before allocating a new one
+                drop(arena);
+                SystemAllocator.allocate(layout)
+            }
+            None => SystemAllocator.allocate(layout),
+        }
+    }
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        wipe_raw(ptr.as_ptr(), layout.size());
+        let mut arena = Some(Arena { layout, ptr });
+        // If the thread already has an idle work area (e.g. because the work area was
+        // allocated on another thread) or is exiting, the work area is freed instead
+        let _ = ARENA.try_with(|current| {
+            let mut current = current.borrow_mut();
+            if current.is_none() {
+                *current = arena.take();
+            }
+        });
+    }
+}
+
+/// A [`MemoryAllocator`](trait.MemoryAllocator.html) that locks the work area into RAM (with
+/// `mlock`), so that it is never written to swap, and, on Linux, excludes it from core dumps.
+/// The work area is allocated in whole pages, because locks are not counted: unlocking a page
+/// that is shared with other locked memory would unlock that too.
+///
+/// Allocating fails with an [`Error`](../struct.Error.html) of kind
+/// [`MemoryLockError`](../enum.ErrorKind.html#variant.MemoryLockError) if the memory cannot be
+/// locked, typically because the work area exceeds the process's `RLIMIT_MEMLOCK` limit (which
+/// is often as low as 64 KiB), or because the platform does not support it (only Unix
+/// platforms do)
+#[derive(Clone, Copy, Debug, Default)]
+pub struct LockedAllocator;
+
+impl LockedAllocator {
+    /// Creates a new [`LockedAllocator`](struct.LockedAllocator.html)
+    pub fn new() -> LockedAllocator {
+        LockedAllocator
+    }
+}
+
+unsafe impl MemoryAllocator for LockedAllocator {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<u8>, Error> {
+        let layout = page_layout(layout)?;
+        let ptr = SystemAllocator.allocate(layout)?;
+        if let Err(e) = unsafe { lock(ptr.as_ptr(), layout.size()) } {
+            unsafe { SystemAllocator.deallocate(ptr, layout) };
+            return Err(e);
+        }
+        Ok(ptr)
+    }
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        // Cannot fail, since it succeeded when the work area was allocated
+        if let Ok(layout) = page_layout(layout) {
+            unlock(ptr.as_ptr(), layout.size());
+            SystemAllocator.deallocate(ptr, layout);
+        }
+    }
+}
+
+/// A [`MemoryAllocator`](trait.MemoryAllocator.html) that maps each work area into pages of
+/// its own, between two inaccessible guard pages, so that reading or writing past either end
+/// of the work area crashes the process instead of silently touching other memory. The work
+/// area is placed at the end of its pages, so that overruns hit the guard page right away.
+///
+/// Mapping pages is slower than allocating on the heap, and each work area takes up two more
+/// pages of address space. Only Unix platforms are supported; elsewhere, allocating fails with
+/// an [`Error`](../struct.Error.html) of kind
+/// [`MemoryAllocationError`](../enum.ErrorKind.html#variant.MemoryAllocationError)
+#[derive(Clone, Copy, Debug, Default)]
+pub struct GuardedAllocator;
+
+impl GuardedAllocator {
+    /// Creates a new [`GuardedAllocator`](struct.GuardedAllocator.html)
+    pub fn new() -> GuardedAllocator {
+        GuardedAllocator
+    }
+}
+
+#[cfg(unix)]
+unsafe impl MemoryAllocator for GuardedAllocator {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<u8>, Error> {
+        let page_size = page_size();
+        let pages = page_layout(layout)?;
+        let mapping_len = pages
+            .size()
+            .checked_add(2 * page_size)
+            .ok_or_else(|| allocation_error(layout.size()))?;
+        unsafe {
+            let mapping = libc::mmap(
+                ptr::null_mut(),
+                mapping_len,
+                libc::PROT_NONE,
+                libc::MAP_PRIVATE | libc::MAP_ANONYMOUS,
+                -1,
+                0,
+            );
+            if mapping == libc::MAP_FAILED {
+                let e = ::std::io::Error::last_os_error();
+                return Err(allocation_error(layout.size()).add_context(format!("mmap: {}", e)));
+            }
+            let start = (mapping as *mut u8).add(page_size);
+            if libc::mprotect(
+                start as *mut libc::c_void,
+                pages.size(),
+                libc::PROT_READ | libc::PROT_WRITE,
+            ) != 0
+            {
+                let e = ::std::io::Error::last_os_error();
+                libc::munmap(mapping, mapping_len);
+                return Err(allocation_error(layout.size()).add_context(format!("mprotect: {}", e)));
+            }
+            // The start of the work area, rounded down to its alignment, so that it ends as
+            // close to the trailing guard page as possible
+            let end = start as usize + pages.size();
+            let ptr = (end - layout.size()) & !(layout.align() - 1);
+            Ok(NonNull::new_unchecked(ptr as *mut u8))
+        }
+    }
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        let page_size = page_size();
+        // Cannot fail, since it succeeded when the work area was allocated
+        if let Ok(pages) = page_layout(layout) {
+            // The work area starts in the first of its pages, since it takes up all but less
+            // than a page of them
+            let start = ptr.as_ptr() as usize & !(page_size - 1);
+            let mapping = (start - page_size) as *mut libc::c_void;
+            libc::munmap(mapping, pages.size() + 2 * page_size);
+        }
+    }
+}
+
+#[cfg(not(unix))]
+unsafe impl MemoryAllocator for GuardedAllocator {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<u8>, Error> {
+        Err(allocation_error(layout.size())
+            .add_context("Guard pages are not supported on this platform"))
+    }
+    unsafe fn deallocate(&self, _ptr: NonNull<u8>, _layout: Layout) {
+        unreachable!()
+    }
+}
+
+fn allocation_error(size: usize) -> Error {
+    Error::new(ErrorKind::MemoryAllocationError).add_context(format!("Size: {}", size))
+}
+
+/// Returns the layout of the whole pages that hold a work area described by `layout`, aligned
+/// to the page size
+fn page_layout(layout: Layout) -> Result<Layout, Error> {
+    let page_size = page_size();
+    if layout.size() == 0 || layout.align() > page_size {
+        return Err(allocation_error(layout.size()));
+    }
+    // The page size is a power of two
+    let size = layout
+        .size()
+        .checked_add(page_size - 1)
+        .ok_or_else(|| allocation_error(layout.size()))?
+        & !(page_size - 1);
+    Layout::from_size_align(size, page_size).map_err(|_| allocation_error(layout.size()))
+}
+
+#[cfg(unix)]
+pub(crate) fn page_size() -> usize {
+    match unsafe { libc::sysconf(libc::_SC_PAGESIZE) } {
+        size if size > 0 => size as usize,
+        _ => 4096,
+    }
+}
+
+#[cfg(not(unix))]
+pub(crate) fn page_size() -> usize {
+    4096
+}
+
+/// Locks `len` bytes at `ptr` into RAM and, on Linux, excludes them from core dumps. Returns an
+/// [`Error`](../struct.Error.html) of kind
+/// [`MemoryLockError`](../enum.ErrorKind.html#variant.MemoryLockError) if that fails
+#[cfg(unix)]
+pub(crate) unsafe fn lock(ptr: *mut u8, len: usize) -> Result<(), Error> {
+    if libc::mlock(ptr as *const libc::c_void, len) != 0 {
+        let e = ::std::io::Error::last_os_error();
+        let mut rlimit = libc::rlimit {
+            rlim_cur: 0,
+            rlim_max: 0,
+        };
+        let context = if libc::getrlimit(libc::RLIMIT_MEMLOCK, &mut rlimit) != 0 {
+            format!("mlock: {}", e)
+        } else if rlimit.rlim_cur == libc::RLIM_INFINITY {
+            format!("mlock: {}. RLIMIT_MEMLOCK: unlimited", e)
+        } else {
+            format!("mlock: {}. RLIMIT_MEMLOCK: {} bytes", e, rlimit.rlim_cur)
+        };
+        return Err(Error::new(ErrorKind::MemoryLockError).add_context(context));
+    }
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    {
+        if libc::madvise(ptr as *mut libc::c_void, len, libc::MADV_DONTDUMP) != 0 {
+            let e = ::std::io::Error::last_os_error();
+            unlock(ptr, len);
+            return Err(
+                Error::new(ErrorKind::MemoryLockError).add_context(format!("madvise: {}", e))
+            );
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+pub(crate) unsafe fn lock(_ptr: *mut u8, _len: usize) -> Result<(), Error> {
+    Err(Error::new(ErrorKind::MemoryLockError)
+        .add_context("Locking memory is not supported on this platform"))
+}
+
+/// Undoes [`lock`](fn.lock.html)
+#[cfg(unix)]
+pub(crate) unsafe fn unlock(ptr: *mut u8, len: usize) {
+    // The pages go back to the allocator, so they must be dumpable again
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    libc::madvise(ptr as *mut libc::c_void, len, libc::MADV_DODUMP);
+    libc::munlock(ptr as *const libc::c_void, len);
+}
+
+#[cfg(not(unix))]
+pub(crate) unsafe fn unlock(_ptr: *mut u8, _len: usize) {}
+
+/// Returns the layout of a work area of `size` bytes
+pub(crate) fn work_area_layout(size: usize) -> Result<Layout, Error> {
+    Layout::from_size_align(size, MEMORY_ALIGNMENT).map_err(|_| allocation_error(size))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ptr;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    use super::*;
+    use config::Backend;
+    use {Hasher, Verifier};
+
+    #[derive(Debug, Default)]
+    struct CountingAllocator {
+        allocated: AtomicUsize,
+        deallocated: AtomicUsize,
+    }
+
+    unsafe impl MemoryAllocator for Arc<CountingAllocator> {
+        fn allocate(&self, layout: Layout) -> Result<NonNull<u8>, Error> {
+            assert_eq!(layout.align(), MEMORY_ALIGNMENT);
+            self.allocated.fetch_add(layout.size(), Ordering::SeqCst);
+            SystemAllocator.allocate(layout)
+        }
+        unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+            self.deallocated.fetch_add(layout.size(), Ordering::SeqCst);
+            SystemAllocator.deallocate(ptr, layout)
+        }
+    }
+
+    #[derive(Debug)]
+    struct FailingAllocator;
+
+    unsafe impl MemoryAllocator for FailingAllocator {
+        fn allocate(&self, _layout: Layout) -> Result<NonNull<u8>, Error> {
+            Err(Error::new(ErrorKind::MemoryAllocationError).add_context("Out of arena space"))
+        }
+        unsafe fn deallocate(&self, _ptr: NonNull<u8>, _layout: Layout) {
+            unreachable!()
+        }
+    }
+
+    fn backends() -> Vec<Backend> {
+        let mut backends = vec![Backend::Rust];
+        if cfg!(feature = "backend-c") {
+            backends.push(Backend::C);
+        }
+        backends
+    }
+
+    #[test]
+    fn test_memory_allocator() {
+        for backend in backends() {
+            let mut hasher = Hasher::default();
+            hasher
+                .configure_backend(backend)
+                .configure_iterations(2)
+                .configure_lanes(2)
+                .configure_memory_size(64)
+                .configure_threads(2)
+                .opt_out_of_secret_key(true)
+                .with_salt("somesalt");
+            let expected = hasher.hash_password("P@ssw0rd").unwrap();
+
+            let allocator = Arc::new(CountingAllocator::default());
+            hasher.configure_memory_allocator(allocator.clone());
+            assert!(hasher.config().memory_allocator().is_some());
+            let hash = hasher.hash_password("P@ssw0rd").unwrap();
+            assert_eq!(hash, expected);
+            assert_eq!(allocator.allocated.load(Ordering::SeqCst), 64 * 1024);
+            assert_eq!(allocator.deallocated.load(Ordering::SeqCst), 64 * 1024);
+
+            let mut verifier = Verifier::default();
+            verifier
+                .configure_backend(backend)
+                .configure_memory_allocator(allocator.clone());
+            assert!(verifier.verify_password(&hash, "P@ssw0rd").unwrap());
+            assert_eq!(allocator.allocated.load(Ordering::SeqCst), 2 * 64 * 1024);
+            assert_eq!(allocator.deallocated.load(Ordering::SeqCst), 2 * 64 * 1024);
+        }
+    }
+
+    #[test]
+    fn test_memory_allocator_error() {
+        for backend in backends() {
+            let mut hasher = Hasher::default();
+            hasher
+                .configure_backend(backend)
+                .configure_iterations(2)
+                .configure_lanes(1)
+                .configure_memory_allocator(FailingAllocator)
+                .configure_memory_size(32)
+                .opt_out_of_secret_key(true);
+            let err = hasher.hash_password("P@ssw0rd").unwrap_err();
+            assert_eq!(err.kind(), ErrorKind::MemoryAllocationError);
+            assert!(err.to_string().contains("Out of arena space"));
+        }
+    }
+
+    #[test]
+    fn test_arena_allocator() {
+        for backend in backends() {
+            for &(lanes, memory_size) in &[(1, 32), (2, 64), (4, 128), (2, 64)] {
+                let mut hasher = Hasher::default();
+                hasher
+                    .configure_backend(backend)
+                    .configure_iterations(2)
+                    .configure_lanes(lanes)
+                    .configure_memory_size(memory_size)
+                    .configure_threads(lanes)
+                    .opt_out_of_secret_key(true)
+                    .with_salt("somesalt");
+                let expected = hasher.hash_password("P@ssw0rd").unwrap();
+
+                hasher.configure_memory_allocator(ArenaAllocator::new());
+                for _ in 0..3 {
+                    assert_eq!(hasher.hash_password("P@ssw0rd").unwrap(), expected);
+                }
+                let mut verifier = Verifier::default();
+                verifier
+                    .configure_backend(backend)
+                    .configure_memory_allocator(ArenaAllocator::new());
+                assert!(verifier.verify_password(&expected, "P@ssw0rd").unwrap());
+                assert!(!verifier.verify_password(&expected, "P@ssw0rd2").unwrap());
+            }
+        }
+        ArenaAllocator::new().release();
+    }
+
+    #[test]
+    fn test_arena_allocator_threads() {
+        fn hasher() -> Hasher<'static> {
+            let mut hasher = Hasher::default();
+            hasher
+                .configure_iterations(2)
+                .configure_lanes(2)
+                .configure_memory_size(64)
+                .opt_out_of_secret_key(true)
+                .with_salt("somesalt");
+            hasher
+        }
+        let expected = hasher().hash_password("P@ssw0rd").unwrap();
+        let handles = (0..4)
+            .map(|_| {
+                let expected = expected.clone();
+                ::std::thread::spawn(move || {
+                    let mut hasher = hasher();
+                    hasher.configure_memory_allocator(ArenaAllocator::new());
+                    for _ in 0..3 {
+                        assert_eq!(hasher.hash_password("P@ssw0rd").unwrap(), expected);
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+    }
+
+    #[test]
+    fn test_arena_allocator_reuse() {
+        let allocator = ArenaAllocator::new();
+        let layout = work_area_layout(4096).unwrap();
+        unsafe {
+            let ptr = allocator.allocate(layout).unwrap();
+            assert_eq!(ptr.as_ptr() as usize & (MEMORY_ALIGNMENT - 1), 0);
+            ptr::write_bytes(ptr.as_ptr(), 0xAB, layout.size());
+            allocator.deallocate(ptr, layout);
+
+            // The same work area is handed out again, wiped
+            let reused = allocator.allocate(layout).unwrap();
+            assert_eq!(reused, ptr);
+            let bytes = ::std::slice::from_raw_parts(reused.as_ptr(), layout.size());
+            assert!(bytes.iter().all(|&byte| byte == 0));
+
+            // While it is in use, other work areas are allocated fresh
+            let other = allocator.allocate(layout).unwrap();
+            assert_ne!(other, reused);
+            allocator.deallocate(other, layout);
+            allocator.deallocate(reused, layout);
+
+            // A work area of a different size replaces it
+            let larger = work_area_layout(8192).unwrap();
+            let ptr = allocator.allocate(larger).unwrap();
+            allocator.deallocate(ptr, larger);
+            assert_eq!(allocator.allocate(larger).unwrap(), ptr);
+            allocator.deallocate(ptr, larger);
+        }
+        allocator.release();
+        ARENA.with(|arena| assert!(arena.borrow().is_none()));
+    }
+
+    #[test]
+    fn test_locked_allocator() {
+        for backend in backends() {
+            let mut hasher = Hasher::default();
+            hasher
+                .configure_backend(backend)
+                .configure_iterations(2)
+                .configure_lanes(2)
+                .configure_memory_size(32)
+                .configure_threads(2)
+                .opt_out_of_secret_key(true)
+                .with_salt("somesalt");
+            let expected = hasher.hash_password("P@ssw0rd").unwrap();
+
+            hasher.configure_memory_allocator(LockedAllocator::new());
+            match hasher.hash_password("P@ssw0rd") {
+                Ok(hash) => assert_eq!(hash, expected),
+                // Locking is not permitted in this environment
+                Err(e) => assert_eq!(e.kind(), ErrorKind::MemoryLockError),
+            }
+        }
+    }
+
+    #[test]
+    fn test_guarded_allocator() {
+        for backend in backends() {
+            let mut hasher = Hasher::default();
+            hasher
+                .configure_backend(backend)
+                .configure_iterations(2)
+                .configure_lanes(2)
+                .configure_memory_size(64)
+                .configure_threads(2)
+                .opt_out_of_secret_key(true)
+                .with_salt("somesalt");
+            let expected = hasher.hash_password("P@ssw0rd").unwrap();
+
+            hasher.configure_memory_allocator(GuardedAllocator::new());
+            assert_eq!(hasher.hash_password("P@ssw0rd").unwrap(), expected);
+            let mut verifier = Verifier::default();
+            verifier
+                .configure_backend(backend)
+                .configure_memory_allocator(GuardedAllocator::new());
+            assert!(verifier.verify_password(&expected, "P@ssw0rd").unwrap());
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_guarded_allocator_layout() {
+        let allocator = GuardedAllocator::new();
+        for &size in &[1, 1000, page_size(), page_size() + 1, 3 * page_size() - 64] {
+            let layout = work_area_layout(size).unwrap();
+            unsafe {
+                let ptr = allocator.allocate(layout).unwrap();
+                assert_eq!(ptr.as_ptr() as usize & (MEMORY_ALIGNMENT - 1), 0);
+                // The work area ends within its alignment of the trailing guard page
+                let end = ptr.as_ptr() as usize + size;
+                assert!(end.wrapping_neg() & (page_size() - 1) < MEMORY_ALIGNMENT);
+                ptr::write_bytes(ptr.as_ptr(), 0xAB, size);
+                allocator.deallocate(ptr, layout);
+            }
+        }
+    }
+
+    #[test]
+    fn test_send() {
+        fn assert_send<T: Send>() {}
+        assert_send::<ArenaAllocator>();
+        assert_send::<GuardedAllocator>();
+        assert_send::<LockedAllocator>();
+        assert_send::<SystemAllocator>();
+    }
+
+    #[test]
+    fn test_sync() {
+        fn assert_sync<T: Sync>() {}
+        assert_sync::<ArenaAllocator>();
+        assert_sync::<GuardedAllocator>();
+        assert_sync::<LockedAllocator>();
+        assert_sync::<SystemAllocator>();
+    }
+}
@@ -0,0 +1,655 @@
+//! Audits of stored hashes (see [`Auditor`](struct.Auditor.html)).
+//!
+//! An audit reads hashes, e.g. from a credential dump, one at a time and reports how the
+//! variants, versions, memory sizes, iterations, lanes, salt lengths and hash lengths of the
+//! hashes are distributed. It also flags the hashes that do not parse, that exceed a
+//! [`VerifierPolicy`](../config/struct.VerifierPolicy.html) or, optionally, that are weaker than
+//! a [`HasherConfig`](../config/struct.HasherConfig.html) (see
+//! [`HashRaw::needs_rehash`](../output/struct.HashRaw.html#method.needs_rehash)). Only the
+//! distributions and a bounded number of findings are kept in memory, so audits can stream
+//! through millions of hashes.
+//!
+//! ```
+//! extern crate argonautica;
+//!
+//! use argonautica::audit::{Auditor, Column, InputFormat, Issue};
+//! use argonautica::config::Variant;
+//!
+//! fn main() {
+//!     let dump = r#"user,hash
+//! alice,"$argon2id$v=19$m=4096,t=192,p=4$c29tZXNhbHQ$aGFzaGhhc2hoYXNoaGFzaA"
+//! bob,"$argon2i$v=19$m=4096,t=3,p=1$c29tZXNhbHQ$aGFzaGhhc2hoYXNoaGFzaA"
+//! carol,5f4dcc3b5aa765d61d8327deb882cf99
+//! "#;
+//!     let mut auditor = Auditor::default();
+//!     auditor.configure_format(InputFormat::Csv {
+//!         column: Column::Name("hash".to_string()),
+//!         delimiter: ',',
+//!         has_header: true,
+//!     });
+//!     let report = auditor.audit(dump.as_bytes()).unwrap();
+//!     assert_eq!(report.total(), 3);
+//!     assert_eq!(report.invalid(), 1);
+//!     assert_eq!(report.variants()[&Variant::Argon2id], 1);
+//!
+//!     let finding = &report.findings()[0];
+//!     assert_eq!(finding.line(), 4);
+//!     match finding.issue() {
+//!         Issue::Invalid { position, .. } => assert_eq!(*position, Some(0)),
+//!         _ => panic!(),
+//!     }
+//! }
+//! ```
+use std::collections::BTreeMap;
+use std::io::BufRead;
+
+use backend::parse_rust;
+use config::{HasherConfig, Variant, VerifierPolicy, Version};
+use output::RehashReasons;
+use {Error, ErrorKind};
+
+const DEFAULT_MAX_FINDINGS: usize = 10_000;
+
+/// The column of a CSV input that holds the hashes
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub enum Column {
+    /// The column at this (zero-based) index
+    Index(usize),
+    /// The column with this name in the header (first line) of the input
+    Name(String),
+}
+
+/// The format of the input of an [`Auditor`](struct.Auditor.html)
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub enum InputFormat {
+    /// One hash per line. This is the default
+    Lines,
+    /// Comma-separated (or `delimiter`-separated) values, one record per line, with the hashes in
+    /// `column`. Fields may be quoted with double quotes (`""` inside a quoted field is a literal
+    /// double quote), which comma-separated hashes need as they contain commas, but may not span
+    /// several lines. If `has_header` is `true` (which is required for a
+    /// [`Column::Name`](enum.Column.html#variant.Name)), the first line is a header rather than a
+    /// record
+    Csv {
+        #[allow(missing_docs)]
+        column: Column,
+        #[allow(missing_docs)]
+        delimiter: char,
+        #[allow(missing_docs)]
+        has_header: bool,
+    },
+}
+
+/// Why a hash was flagged by an audit
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub enum Issue {
+    /// The hash could not be parsed. The hash itself is not kept, since what does not parse as
+    /// a hash may well be something else, e.g. a password stored in plaintext
+    Invalid {
+        /// Why the hash could not be parsed, e.g.
+        /// [`HashDecodeError`](../enum.ErrorKind.html#variant.HashDecodeError)
+        kind: ErrorKind,
+        /// The byte position in the field (or line) at which parsing failed, or `None` if the
+        /// record has no field in the configured [`Column`](enum.Column.html)
+        position: Option<usize>,
+    },
+    /// The hash exceeds the [`VerifierPolicy`](../config/struct.VerifierPolicy.html) of the
+    /// audit; the error names the first limit exceeded
+    PolicyViolation(Error),
+    /// The hash is weaker than the [`HasherConfig`](../config/struct.HasherConfig.html) of
+    /// the audit, for these reasons
+    Weak(RehashReasons),
+}
+
+/// A hash flagged by an audit
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct Finding {
+    issue: Issue,
+    line: u64,
+}
+
+impl Finding {
+    #[allow(missing_docs)]
+    pub fn issue(&self) -> &Issue {
+        &self.issue
+    }
+    /// The (one-based) line of the input the hash is on
+    pub fn line(&self) -> u64 {
+        self.line
+    }
+}
+
+/// The result of an audit. The distributions only count the hashes that could be parsed
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct AuditReport {
+    findings: Vec<Finding>,
+    findings_truncated: bool,
+    hash_lens: BTreeMap<usize, u64>,
+    invalid: u64,
+    iterations: BTreeMap<u32, u64>,
+    lanes: BTreeMap<u32, u64>,
+    memory_sizes: BTreeMap<u32, u64>,
+    policy_violations: u64,
+    salt_lens: BTreeMap<usize, u64>,
+    total: u64,
+    variants: BTreeMap<Variant, u64>,
+    versions: BTreeMap<Version, u64>,
+    weak: u64,
+}
+
+impl AuditReport {
+    /// The flagged hashes, in input order, up to the
+    /// [maximum number of findings](struct.Auditor.html#method.configure_max_findings)
+    pub fn findings(&self) -> &[Finding] {
+        &self.findings
+    }
+    /// Returns `true` if more hashes were flagged than are listed in
+    /// [`findings`](struct.AuditReport.html#method.findings)
+    pub fn findings_truncated(&self) -> bool {
+        self.findings_truncated
+    }
+    /// The number of hashes by hash length (in bytes)
+    pub fn hash_lens(&self) -> &BTreeMap<usize, u64> {
+        &self.hash_lens
+    }
+    /// The number of hashes that could not be parsed
+    pub fn invalid(&self) -> u64 {
+        self.invalid
+    }
+    /// The number of hashes by number of iterations
+    pub fn iterations(&self) -> &BTreeMap<u32, u64> {
+        &self.iterations
+    }
+    /// The number of hashes by number of lanes
+    pub fn lanes(&self) -> &BTreeMap<u32, u64> {
+        &self.lanes
+    }
+    /// The number of hashes by memory size (in kibibytes)
+    pub fn memory_sizes(&self) -> &BTreeMap<u32, u64> {
+        &self.memory_sizes
+    }
+    /// The number of hashes that exceed the policy
+    pub fn policy_violations(&self) -> u64 {
+        self.policy_violations
+    }
+    /// The number of hashes by salt length (in bytes)
+    pub fn salt_lens(&self) -> &BTreeMap<usize, u64> {
+        &self.salt_lens
+    }
+    /// The number of hashes audited (empty lines and CSV headers are skipped)
+    pub fn total(&self) -> u64 {
+        self.total
+    }
+    /// The number of hashes by variant
+    pub fn variants(&self) -> &BTreeMap<Variant, u64> {
+        &self.variants
+    }
+    /// The number of hashes by version
+    pub fn versions(&self) -> &BTreeMap<Version, u64> {
+        &self.versions
+    }
+    /// The number of hashes that are weaker than the configuration (but within the policy)
+    pub fn weak(&self) -> u64 {
+        self.weak
+    }
+
+    fn flag(&mut self, line: u64, issue: Issue, max_findings: usize) {
+        if self.findings.len() < max_findings {
+            self.findings.push(Finding { issue, line });
+        } else {
+            self.findings_truncated = true;
+        }
+    }
+}
+
+impl Default for Auditor {
+    /// Same as the [`new`](struct.Auditor.html#method.new) method
+    fn default() -> Auditor {
+        Auditor {
+            config: None,
+            format: InputFormat::Lines,
+            max_findings: DEFAULT_MAX_FINDINGS,
+            policy: VerifierPolicy::default(),
+        }
+    }
+}
+
+/// Audits stored hashes (see the [module documentation](index.html))
+#[derive(Clone, Debug)]
+pub struct Auditor {
+    config: Option<HasherConfig>,
+    format: InputFormat,
+    max_findings: usize,
+    policy: VerifierPolicy,
+}
+
+impl Auditor {
+    /// Creates a new [`Auditor`](struct.Auditor.html) that reads one hash per line, flags the
+    /// hashes that do not parse or that exceed the default
+    /// [`VerifierPolicy`](../config/struct.VerifierPolicy.html), and lists up to 10,000
+    /// findings
+    pub fn new() -> Auditor {
+        Auditor::default()
+    }
+    /// Allows you to configure the format of the input. The default is
+    /// [`InputFormat::Lines`](enum.InputFormat.html#variant.Lines)
+    pub fn configure_format(&mut self, format: InputFormat) -> &mut Auditor {
+        self.format = format;
+        self
+    }
+    /// Allows you to configure [`Auditor`](struct.Auditor.html) to also flag the hashes that
+    /// are weaker than `config`, e.g. the configuration of the
+    /// [`Hasher`](../struct.Hasher.html) you currently use to create new hashes
+    pub fn configure_hasher_config(&mut self, config: &HasherConfig) -> &mut Auditor {
+        self.config = Some(config.clone());
+        self
+    }
+    /// Allows you to configure the maximum number of findings listed in the report. Hashes
+    /// flagged after that are still counted. The default is `10000`
+    pub fn configure_max_findings(&mut self, max_findings: usize) -> &mut Auditor {
+        self.max_findings = max_findings;
+        self
+    }
+    /// Allows you to configure the [`VerifierPolicy`](../config/struct.VerifierPolicy.html)
+    /// that hashes are flagged for exceeding. The default is
+    /// [`VerifierPolicy::default`](../config/struct.VerifierPolicy.html#method.default)
+    pub fn configure_policy(&mut self, policy: VerifierPolicy) -> &mut Auditor {
+        self.policy = policy;
+        self
+    }
+    /// Audits the hashes read from `reader`, one line at a time. Each hash is parsed the way a
+    /// [`Verifier`](../struct.Verifier.html) decodes it, so surrounding whitespace is ignored.
+    /// Lines that are not valid UTF-8 are decoded lossily (and will typically not parse).
+    /// Returns an [`Error`](../struct.Error.html) of kind
+    /// [`AuditInputError`](../enum.ErrorKind.html#variant.AuditInputError) if reading fails or
+    /// if the header does not have the configured [`Column`](enum.Column.html)
+    pub fn audit<R: BufRead>(&self, mut reader: R) -> Result<AuditReport, Error> {
+        let mut report = AuditReport::default();
+        let mut buf = Vec::new();
+        let mut line = 0;
+        let csv = match self.format {
+            InputFormat::Lines => None,
+            InputFormat::Csv {
+                ref column,
+                delimiter,
+                has_header,
+            } => {
+                if has_header {
+                    read_line(&mut reader, &mut buf, &mut line)?;
+                }
+                let index = match *column {
+                    Column::Index(index) => index,
+                    Column::Name(ref name) => {
+                        let header = String::from_utf8_lossy(&buf);
+                        let index = if has_header {
+                            split_csv(&header, delimiter)
+                                .iter()
+                                .position(|field| field.trim() == name)
+                        } else {
+                            None
+                        };
+                        index.ok_or_else(|| {
+                            Error::new(ErrorKind::AuditInputError)
+                                .add_context(format!("Header has no column {:?}", name))
+                        })?
+                    }
+                };
+                Some((index, delimiter))
+            }
+        };
+        while read_line(&mut reader, &mut buf, &mut line)? {
+            let text = String::from_utf8_lossy(&buf);
+            if text.trim().is_empty() {
+                continue;
+            }
+            let (index, delimiter) = match csv {
+                Some(csv) => csv,
+                None => {
+                    self.audit_hash(&mut report, line, &text);
+                    continue;
+                }
+            };
+            match split_csv(&text, delimiter).get(index) {
+                Some(hash) => self.audit_hash(&mut report, line, hash),
+                None => {
+                    report.total += 1;
+                    report.invalid += 1;
+                    let issue = Issue::Invalid {
+                        kind: ErrorKind::HashDecodeError,
+                        position: None,
+                    };
+                    report.flag(line, issue, self.max_findings);
+                }
+            }
+        }
+        Ok(report)
+    }
+    /// Audits `hashes`, e.g. hashes queried from a database. The
+    /// [format](struct.Auditor.html#method.configure_format) is ignored, and the "line" of a
+    /// hash is its (one-based) position
+    pub fn audit_hashes<I, S>(&self, hashes: I) -> AuditReport
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut report = AuditReport::default();
+        for (i, hash) in hashes.into_iter().enumerate() {
+            self.audit_hash(&mut report, i as u64 + 1, hash.as_ref());
+        }
+        report
+    }
+    #[allow(missing_docs)]
+    pub fn format(&self) -> &InputFormat {
+        &self.format
+    }
+    #[allow(missing_docs)]
+    pub fn hasher_config(&self) -> Option<&HasherConfig> {
+        self.config.as_ref()
+    }
+    #[allow(missing_docs)]
+    pub fn max_findings(&self) -> usize {
+        self.max_findings
+    }
+    #[allow(missing_docs)]
+    pub fn policy(&self) -> &VerifierPolicy {
+        &self.policy
+    }
+
+    fn audit_hash(&self, report: &mut AuditReport, line: u64, hash: &str) {
+        report.total += 1;
+        // The hash is parsed exactly as a verifier decodes it, so that the audit accepts the
+        // hashes verification accepts (e.g. with surrounding whitespace) and nothing else
+        let hash_raw = match parse_rust(hash) {
+            Ok(hash_raw) => hash_raw,
+            Err(e) => {
+                report.invalid += 1;
+                let issue = Issue::Invalid {
+                    kind: ErrorKind::HashDecodeError,
+                    position: Some(e.pos()),
+                };
+                report.flag(line, issue, self.max_findings);
+                return;
+            }
+        };
+        *report.variants.entry(hash_raw.variant()).or_insert(0) += 1;
+        *report.versions.entry(hash_raw.version()).or_insert(0) += 1;
+        *report
+            .memory_sizes
+            .entry(hash_raw.memory_size())
+            .or_insert(0) += 1;
+        *report.iterations.entry(hash_raw.iterations()).or_insert(0) += 1;
+        *report.lanes.entry(hash_raw.lanes()).or_insert(0) += 1;
+        *report
+            .salt_lens
+            .entry(hash_raw.raw_salt_bytes().len())
+            .or_insert(0) += 1;
+        *report
+            .hash_lens
+            .entry(hash_raw.raw_hash_bytes().len())
+            .or_insert(0) += 1;
+        if let Err(e) = self.policy.check(&hash_raw) {
+            report.policy_violations += 1;
+            report.flag(line, Issue::PolicyViolation(e), self.max_findings);
+            return;
+        }
+        if let Some(ref config) = self.config {
+            let reasons = hash_raw.needs_rehash(config);
+            if !reasons.is_empty() {
+                report.weak += 1;
+                report.flag(line, Issue::Weak(reasons), self.max_findings);
+            }
+        }
+    }
+}
+
+/// Reads the next line of `reader` into `buf`, without its line ending, and increments `line`.
+/// Returns `false` at the end of the input
+fn read_line<R: BufRead>(reader: &mut R, buf: &mut Vec<u8>, line: &mut u64) -> Result<bool, Error> {
+    buf.clear();
+    let read = reader.read_until(b'\n', buf).map_err(|e| {
+        Error::new(ErrorKind::AuditInputError).add_context(format!("Line {}: {}", *line + 1, e))
+    })?;
+    if read == 0 {
+        return Ok(false);
+    }
+    *line += 1;
+    if buf.last() == Some(&b'\n') {
+        buf.pop();
+        if buf.last() == Some(&b'\r') {
+            buf.pop();
+        }
+    }
+    Ok(true)
+}
+
+/// Splits a CSV record into its fields, unquoting quoted fields
+fn split_csv(record: &str, delimiter: char) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut chars = record.chars().peekable();
+    let mut quoted = false;
+    while let Some(c) = chars.next() {
+        if quoted {
+            if c != '"' {
+                field.push(c);
+            } else if chars.peek() == Some(&'"') {
+                field.push('"');
+                chars.next();
+            } else {
+                quoted = false;
+            }
+        } else if c == '"' {
+            quoted = true;
+        } else if c == delimiter {
+            fields.push(field);
+            field = String::new();
+        } else {
+            field.push(c);
+        }
+    }
+    fields.push(field);
+    fields
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HASH: &str = "$argon2id$v=19$m=4096,t=192,p=4$c29tZXNhbHQ$aGFzaGhhc2hoYXNoaGFzaA";
+    const HASH_2I: &str = "$argon2i$v=16$m=32,t=3,p=1$c29tZXNhbHQ$aGFzaGhhc2g";
+    const HASH_HUGE: &str = "$argon2id$v=19$m=4194304,t=192,p=4$c29tZXNhbHQ$aGFzaGhhc2hoYXNoaGFzaA";
+
+    fn counts<K: Clone + Ord>(pairs: &[(K, u64)]) -> BTreeMap<K, u64> {
+        pairs.iter().cloned().collect()
+    }
+
+    #[test]
+    fn test_audit_lines() {
+        let input = format!(
+            "{}\n\n{}\r\n  {}  \nnot a hash\n{}",
+            HASH, HASH_2I, HASH, HASH_HUGE
+        );
+        let report = Auditor::default().audit(input.as_bytes()).unwrap();
+        assert_eq!(report.total(), 5);
+        // The padded hash is valid, as it is for a verifier
+        assert_eq!(report.invalid(), 1);
+        assert_eq!(report.policy_violations(), 1);
+        assert_eq!(report.weak(), 0);
+        assert_eq!(
+            report.variants(),
+            &counts(&[(Variant::Argon2i, 1), (Variant::Argon2id, 3)])
+        );
+        assert_eq!(
+            report.versions(),
+            &counts(&[(Version::_0x10, 1), (Version::_0x13, 3)])
+        );
+        assert_eq!(
+            report.memory_sizes(),
+            &counts(&[(32, 1), (4096, 2), (4_194_304, 1)])
+        );
+        assert_eq!(report.iterations(), &counts(&[(3, 1), (192, 3)]));
+        assert_eq!(report.lanes(), &counts(&[(1, 1), (4, 3)]));
+        assert_eq!(report.salt_lens(), &counts(&[(8, 4)]));
+        assert_eq!(report.hash_lens(), &counts(&[(8, 1), (16, 3)]));
+
+        let findings = report.findings();
+        assert_eq!(findings.len(), 2);
+        assert_eq!(findings[0].line(), 5);
+        match findings[0].issue() {
+            Issue::Invalid { kind, position } => {
+                assert_eq!(*kind, ErrorKind::HashDecodeError);
+                assert_eq!(*position, Some(0));
+            }
+            issue => panic!("{:?}", issue),
+        }
+        assert_eq!(findings[1].line(), 6);
+        match findings[1].issue() {
+            Issue::PolicyViolation(e) => assert_eq!(e.kind(), ErrorKind::VerifierPolicyError),
+            issue => panic!("{:?}", issue),
+        }
+    }
+
+    #[test]
+    fn test_audit_invalid_input_is_not_kept() {
+        let input = "user,hash\nalice,hunter2plaintext\nbob,  $argon2x$hunter2\ncarol\n";
+        let mut auditor = Auditor::default();
+        auditor.configure_format(InputFormat::Csv {
+            column: Column::Name("hash".to_string()),
+            delimiter: ',',
+            has_header: true,
+        });
+        let report = auditor.audit(input.as_bytes()).unwrap();
+        assert_eq!(report.invalid(), 3);
+        let positions = report
+            .findings()
+            .iter()
+            .map(|finding| match *finding.issue() {
+                Issue::Invalid { position, .. } => position,
+                ref issue => panic!("{:?}", issue),
+            })
+            .collect::<Vec<_>>();
+        // The position counts the leading whitespace of the field
+        assert_eq!(positions, vec![Some(0), Some(3), None]);
+        assert!(!format!("{:?}", report).contains("hunter2"));
+        #[cfg(feature = "serde")]
+        {
+            use serde_json;
+            assert!(!serde_json::to_string(&report).unwrap().contains("hunter2"));
+        }
+    }
+
+    #[test]
+    fn test_audit_csv() {
+        let input = format!(
+            "id;\"hash\";note\n1;{};\"a; \"\"quoted\"\" note\"\n2;\"{}\";\n3\n",
+            HASH, HASH_2I,
+        );
+        let mut auditor = Auditor::default();
+        auditor.configure_format(InputFormat::Csv {
+            column: Column::Name("hash".to_string()),
+            delimiter: ';',
+            has_header: true,
+        });
+        let report = auditor.audit(input.as_bytes()).unwrap();
+        assert_eq!(report.total(), 3);
+        assert_eq!(report.invalid(), 1);
+        assert_eq!(report.findings()[0].line(), 4);
+
+        auditor.configure_format(InputFormat::Csv {
+            column: Column::Index(1),
+            delimiter: ';',
+            has_header: true,
+        });
+        assert_eq!(auditor.audit(input.as_bytes()).unwrap(), report);
+
+        // Without a header, the header is audited as a record
+        auditor.configure_format(InputFormat::Csv {
+            column: Column::Index(1),
+            delimiter: ';',
+            has_header: false,
+        });
+        let report = auditor.audit(input.as_bytes()).unwrap();
+        assert_eq!(report.total(), 4);
+        assert_eq!(report.invalid(), 2);
+
+        for (column, has_header) in &[("missing", true), ("hash", false)] {
+            auditor.configure_format(InputFormat::Csv {
+                column: Column::Name(column.to_string()),
+                delimiter: ';',
+                has_header: *has_header,
+            });
+            let err = auditor.audit(input.as_bytes()).unwrap_err();
+            assert_eq!(err.kind(), ErrorKind::AuditInputError);
+        }
+    }
+
+    #[test]
+    fn test_audit_weak() {
+        let mut config = HasherConfig::default();
+        config.set_iterations(100);
+        config.set_lanes(4);
+        config.set_memory_size(4096);
+        config.set_hash_len(16);
+        config.set_salt_len(8);
+        let mut auditor = Auditor::default();
+        auditor.configure_hasher_config(&config);
+        let report = auditor.audit_hashes([HASH, HASH_2I].iter());
+        assert_eq!(report.weak(), 1);
+        assert_eq!(report.findings().len(), 1);
+        assert_eq!(report.findings()[0].line(), 2);
+        match report.findings()[0].issue() {
+            Issue::Weak(reasons) => assert_eq!(
+                *reasons,
+                RehashReasons::VARIANT
+                    | RehashReasons::VERSION
+                    | RehashReasons::MEMORY_SIZE
+                    | RehashReasons::ITERATIONS
+                    | RehashReasons::LANES
+                    | RehashReasons::HASH_LEN
+            ),
+            issue => panic!("{:?}", issue),
+        }
+    }
+
+    #[test]
+    fn test_audit_max_findings() {
+        let mut auditor = Auditor::default();
+        auditor.configure_max_findings(2);
+        let report = auditor.audit_hashes(vec!["a"; 5]);
+        assert_eq!(report.invalid(), 5);
+        assert_eq!(report.findings().len(), 2);
+        assert!(report.findings_truncated());
+    }
+
+    #[test]
+    fn test_split_csv() {
+        assert_eq!(split_csv("", ','), vec![""]);
+        assert_eq!(split_csv("a,,b", ','), vec!["a", "", "b"]);
+        assert_eq!(split_csv("\"a,b\",\"c\"\"d\"", ','), vec!["a,b", "c\"d"]);
+        assert_eq!(split_csv("a\tb", '\t'), vec!["a", "b"]);
+    }
+
+    #[test]
+    fn test_send() {
+        fn assert_send<T: Send>() {}
+        assert_send::<Auditor>();
+        assert_send::<AuditReport>();
+    }
+
+    #[test]
+    fn test_sync() {
+        fn assert_sync<T: Sync>() {}
+        assert_sync::<Auditor>();
+        assert_sync::<AuditReport>();
+    }
+}
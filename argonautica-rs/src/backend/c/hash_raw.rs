@@ -1,5 +1,10 @@
+use std::cell::RefCell;
 use std::ffi::CStr;
+use std::ptr::{self, NonNull};
+use std::sync::{Arc, Once};
 
+use allocator::{work_area_layout, MemoryAllocator};
+use config::BlockCompression;
 use output::HashRaw;
 use {ffi, Error, ErrorKind, Hasher};
 
@@ -19,13 +24,18 @@ impl<'a> Hasher<'a> {
             ),
             None => return Err(Error::new(ErrorKind::PasswordMissingError)),
         };
-        let (secret, secretlen) = match self.secret_key {
-            Some(ref mut secret_key) => (
-                secret_key.as_bytes().as_ptr() as *mut u8,
-                secret_key.len() as u32,
-            ),
+        let (secret, secretlen) = match self.secret_key_bytes() {
+            Some(secret_key) => (secret_key.as_ptr() as *mut u8, secret_key.len() as u32),
             None => (::std::ptr::null_mut(), 0),
         };
+        // The C library's allocation callbacks take no user data, so the allocator is passed
+        // to them through a thread local; the work area is allocated and freed on this thread
+        let (allocate_cbk, free_cbk): (ffi::allocate_fptr, ffi::deallocate_fptr) =
+            match self.config.memory_allocator() {
+                Some(_) => (Some(allocate_cbk), Some(free_cbk)),
+                None => (None, None),
+            };
+        let _memory_allocator = CurrentMemoryAllocator::set(self.config.memory_allocator());
         let mut buffer = vec![0u8; self.config.hash_len() as usize];
         let mut context = ffi::Argon2_Context {
             out: buffer.as_mut_ptr(),
@@ -43,16 +53,25 @@ impl<'a> Hasher<'a> {
             lanes: self.config.lanes(),
             threads: self.config.threads(),
             version: self.config.version() as u32,
-            allocate_cbk: None,
-            free_cbk: None,
+            allocate_cbk,
+            free_cbk,
             flags: 0,
         };
+        let block_compression = BlockCompression::detect();
+        SET_BLOCK_COMPRESSION.call_once(|| unsafe {
+            ffi::argonautica_set_block_compression(block_compression as ::libc::c_int)
+        });
         let context_ptr = &mut context as *mut ffi::Argon2_Context;
         let variant = self.config.variant() as ffi::argon2_type;
         let err = unsafe { ffi::argon2_ctx(context_ptr, variant) };
+        if let Some(e) = CurrentMemoryAllocator::take_error() {
+            return Err(e);
+        }
         check_error(err)?;
         Ok(HashRaw {
+            data: None,
             iterations: self.config.iterations(),
+            key_id: None,
             lanes: self.config.lanes(),
             memory_size: self.config.memory_size(),
             raw_hash_bytes: buffer,
@@ -63,6 +82,96 @@ impl<'a> Hasher<'a> {
     }
 }
 
+thread_local! {
+    static MEMORY_ALLOCATOR: RefCell<Option<CurrentMemoryAllocator>> = RefCell::new(None);
+}
+
+/// The [`MemoryAllocator`](../../allocator/trait.MemoryAllocator.html) of the hash running on
+/// this thread, if any, and the error it returned, if any. Unset on drop
+struct CurrentMemoryAllocator {
+    allocator: Arc<dyn MemoryAllocator>,
+    error: Option<Error>,
+}
+
+impl CurrentMemoryAllocator {
+    fn set(allocator: Option<Arc<dyn MemoryAllocator>>) -> CurrentMemoryAllocatorGuard {
+        MEMORY_ALLOCATOR.with(|current| {
+            *current.borrow_mut() = allocator.map(|allocator| CurrentMemoryAllocator {
+                allocator,
+                error: None,
+            });
+        });
+        CurrentMemoryAllocatorGuard
+    }
+    fn get() -> Option<Arc<dyn MemoryAllocator>> {
+        MEMORY_ALLOCATOR.with(|current| {
+            current
+                .borrow()
+                .as_ref()
+                .map(|current| current.allocator.clone())
+        })
+    }
+    fn set_error(e: Error) {
+        MEMORY_ALLOCATOR.with(|current| {
+            if let Some(ref mut current) = *current.borrow_mut() {
+                current.error = Some(e);
+            }
+        });
+    }
+    fn take_error() -> Option<Error> {
+        MEMORY_ALLOCATOR.with(|current| {
+            current
+                .borrow_mut()
+                .as_mut()
+                .and_then(|current| current.error.take())
+        })
+    }
+}
+
+struct CurrentMemoryAllocatorGuard;
+
+impl Drop for CurrentMemoryAllocatorGuard {
+    fn drop(&mut self) {
+        MEMORY_ALLOCATOR.with(|current| *current.borrow_mut() = None);
+    }
+}
+
+unsafe extern "C" fn allocate_cbk(
+    memory: *mut *mut u8,
+    bytes_to_allocate: ffi::size_t,
+) -> ::libc::c_int {
+    *memory = ptr::null_mut();
+    let allocator = match CurrentMemoryAllocator::get() {
+        Some(allocator) => allocator,
+        None => return ffi::Argon2_ErrorCodes_ARGON2_MEMORY_ALLOCATION_ERROR as ::libc::c_int,
+    };
+    match work_area_layout(bytes_to_allocate as usize).and_then(|layout| allocator.allocate(layout))
+    {
+        Ok(ptr) => {
+            *memory = ptr.as_ptr();
+            ffi::Argon2_ErrorCodes_ARGON2_OK as ::libc::c_int
+        }
+        Err(e) => {
+            CurrentMemoryAllocator::set_error(e);
+            ffi::Argon2_ErrorCodes_ARGON2_MEMORY_ALLOCATION_ERROR as ::libc::c_int
+        }
+    }
+}
+
+unsafe extern "C" fn free_cbk(memory: *mut u8, bytes_to_allocate: ffi::size_t) {
+    let (allocator, ptr) = match (CurrentMemoryAllocator::get(), NonNull::new(memory)) {
+        (Some(allocator), Some(ptr)) => (allocator, ptr),
+        _ => return,
+    };
+    // Safe to unwrap: the same layout was created when the memory was allocated
+    let layout = work_area_layout(bytes_to_allocate as usize).unwrap();
+    allocator.deallocate(ptr, layout);
+}
+
+// The C library's choice of block compression implementation is process-wide state, so it is
+// set exactly once, before the first hash, to the implementation detected for this CPU
+static SET_BLOCK_COMPRESSION: Once = Once::new();
+
 fn check_error(err: ffi::Argon2_ErrorCodes) -> Result<(), Error> {
     match err {
         ffi::Argon2_ErrorCodes_ARGON2_OK => Ok(()),
@@ -1,6 +1,7 @@
+#[cfg(feature = "backend-c")]
 mod c;
 mod rust;
 
-#[cfg(test)]
+#[cfg(all(test, feature = "backend-c"))]
 pub(crate) use self::c::encode_c;
-pub(crate) use self::rust::decode_rust;
+pub(crate) use self::rust::{decode_params_rust, decode_rust, parse_rust};
@@ -0,0 +1,265 @@
+use std::ops::{BitXorAssign, Index, IndexMut};
+
+use config::BlockCompression;
+use utils::wipe_raw;
+
+pub(crate) const BLOCK_SIZE: usize = 1024;
+pub(crate) const QWORDS_IN_BLOCK: usize = BLOCK_SIZE / 8;
+
+impl Clone for Block {
+    fn clone(&self) -> Block {
+        *self
+    }
+}
+
+impl Default for Block {
+    fn default() -> Block {
+        Block([0u64; QWORDS_IN_BLOCK])
+    }
+}
+
+impl BitXorAssign<&Block> for Block {
+    fn bitxor_assign(&mut self, rhs: &Block) {
+        for (a, b) in self.0.iter_mut().zip(rhs.0.iter()) {
+            *a ^= *b;
+        }
+    }
+}
+
+impl Index<usize> for Block {
+    type Output = u64;
+
+    fn index(&self, index: usize) -> &u64 {
+        &self.0[index]
+    }
+}
+
+impl IndexMut<usize> for Block {
+    fn index_mut(&mut self, index: usize) -> &mut u64 {
+        &mut self.0[index]
+    }
+}
+
+/// A single 1 KiB Argon2 memory block, stored as 128 little-endian 64-bit words
+#[derive(Copy)]
+pub(crate) struct Block(pub(crate) [u64; QWORDS_IN_BLOCK]);
+
+impl Block {
+    pub(crate) fn from_bytes(bytes: &[u8]) -> Block {
+        debug_assert_eq!(bytes.len(), BLOCK_SIZE);
+        let mut block = Block::default();
+        for (word, chunk) in block.0.iter_mut().zip(bytes.chunks(8)) {
+            let mut buffer = [0u8; 8];
+            buffer.copy_from_slice(chunk);
+            *word = u64::from_le_bytes(buffer);
+        }
+        block
+    }
+    pub(crate) fn as_le_bytes(&self) -> [u8; BLOCK_SIZE] {
+        let mut bytes = [0u8; BLOCK_SIZE];
+        for (chunk, word) in bytes.chunks_mut(8).zip(self.0.iter()) {
+            chunk.copy_from_slice(&word.to_le_bytes());
+        }
+        bytes
+    }
+    /// Overwrites the block with zeros (see [`wipe`](../../../utils/fn.wipe.html))
+    pub(crate) fn wipe(&mut self) {
+        unsafe { wipe_raw(self.0.as_mut_ptr() as *mut u8, BLOCK_SIZE) };
+    }
+}
+
+/// The Argon2 compression function G. Computes `G(prev, reference)` and either writes the
+/// result into `next` (`with_xor == false`) or xors it into the existing contents of `next`
+/// (`with_xor == true`, as required by passes after the first in version 0x13)
+pub(crate) type FillBlock = fn(prev: &Block, reference: &Block, next: &mut Block, with_xor: bool);
+
+/// Returns the implementation of [`FillBlock`](type.FillBlock.html) corresponding to
+/// `block_compression`, which must be supported by the current CPU
+pub(crate) fn fill_block_fn(block_compression: BlockCompression) -> FillBlock {
+    debug_assert!(block_compression.is_supported());
+    match block_compression {
+        BlockCompression::Reference => fill_block_ref,
+        #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+        BlockCompression::Sse2 => fill_block_sse2,
+        #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+        BlockCompression::Avx2 => fill_block_avx2,
+        #[cfg(not(any(target_arch = "x86", target_arch = "x86_64")))]
+        BlockCompression::Sse2 | BlockCompression::Avx2 => fill_block_ref,
+    }
+}
+
+fn fill_block_ref(prev: &Block, reference: &Block, next: &mut Block, with_xor: bool) {
+    fill_block(prev, reference, next, with_xor)
+}
+
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+fn fill_block_sse2(prev: &Block, reference: &Block, next: &mut Block, with_xor: bool) {
+    #[target_feature(enable = "sse2")]
+    unsafe fn inner(prev: &Block, reference: &Block, next: &mut Block, with_xor: bool) {
+        fill_block(prev, reference, next, with_xor)
+    }
+    // Safe: only handed out by `fill_block_fn` after SSE2 support has been detected
+    unsafe { inner(prev, reference, next, with_xor) }
+}
+
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+fn fill_block_avx2(prev: &Block, reference: &Block, next: &mut Block, with_xor: bool) {
+    #[target_feature(enable = "avx2")]
+    unsafe fn inner(prev: &Block, reference: &Block, next: &mut Block, with_xor: bool) {
+        fill_block(prev, reference, next, with_xor)
+    }
+    // Safe: only handed out by `fill_block_fn` after AVX2 support has been detected
+    unsafe { inner(prev, reference, next, with_xor) }
+}
+
+// Inlined into each of the functions above so that the compiler can vectorize it using
+// whatever instruction set the caller has enabled
+#[inline(always)]
+fn fill_block(prev: &Block, reference: &Block, next: &mut Block, with_xor: bool) {
+    let mut block_r = *reference;
+    block_r ^= prev;
+    let mut block_tmp = block_r;
+    if with_xor {
+        block_tmp ^= &*next;
+    }
+    for i in 0..8 {
+        let v = &mut block_r.0;
+        round(
+            v,
+            [
+                16 * i,
+                16 * i + 1,
+                16 * i + 2,
+                16 * i + 3,
+                16 * i + 4,
+                16 * i + 5,
+                16 * i + 6,
+                16 * i + 7,
+                16 * i + 8,
+                16 * i + 9,
+                16 * i + 10,
+                16 * i + 11,
+                16 * i + 12,
+                16 * i + 13,
+                16 * i + 14,
+                16 * i + 15,
+            ],
+        );
+    }
+    for i in 0..8 {
+        let v = &mut block_r.0;
+        round(
+            v,
+            [
+                2 * i,
+                2 * i + 1,
+                2 * i + 16,
+                2 * i + 17,
+                2 * i + 32,
+                2 * i + 33,
+                2 * i + 48,
+                2 * i + 49,
+                2 * i + 64,
+                2 * i + 65,
+                2 * i + 80,
+                2 * i + 81,
+                2 * i + 96,
+                2 * i + 97,
+                2 * i + 112,
+                2 * i + 113,
+            ],
+        );
+    }
+    block_tmp ^= &block_r;
+    *next = block_tmp;
+}
+
+#[inline(always)]
+fn f_bla_mka(x: u64, y: u64) -> u64 {
+    let m = 0xFFFF_FFFFu64;
+    let xy = (x & m).wrapping_mul(y & m);
+    x.wrapping_add(y).wrapping_add(xy.wrapping_mul(2))
+}
+
+#[inline(always)]
+fn g(v: &mut [u64; QWORDS_IN_BLOCK], a: usize, b: usize, c: usize, d: usize) {
+    v[a] = f_bla_mka(v[a], v[b]);
+    v[d] = (v[d] ^ v[a]).rotate_right(32);
+    v[c] = f_bla_mka(v[c], v[d]);
+    v[b] = (v[b] ^ v[c]).rotate_right(24);
+    v[a] = f_bla_mka(v[a], v[b]);
+    v[d] = (v[d] ^ v[a]).rotate_right(16);
+    v[c] = f_bla_mka(v[c], v[d]);
+    v[b] = (v[b] ^ v[c]).rotate_right(63);
+}
+
+#[inline(always)]
+fn round(v: &mut [u64; QWORDS_IN_BLOCK], i: [usize; 16]) {
+    g(v, i[0], i[4], i[8], i[12]);
+    g(v, i[1], i[5], i[9], i[13]);
+    g(v, i[2], i[6], i[10], i[14]);
+    g(v, i[3], i[7], i[11], i[15]);
+    g(v, i[0], i[5], i[10], i[15]);
+    g(v, i[1], i[6], i[11], i[12]);
+    g(v, i[2], i[7], i[8], i[13]);
+    g(v, i[3], i[4], i[9], i[14]);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_blocks() -> (Block, Block) {
+        let mut prev = Block::default();
+        let mut reference = Block::default();
+        for i in 0..QWORDS_IN_BLOCK {
+            prev[i] = i as u64;
+            reference[i] = (i as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15);
+        }
+        (prev, reference)
+    }
+
+    #[test]
+    fn test_block_compressions_agree() {
+        let (prev, reference) = test_blocks();
+        for with_xor in &[false, true] {
+            let mut expected = reference;
+            fill_block_ref(&prev, &reference, &mut expected, *with_xor);
+            for block_compression in &[BlockCompression::Sse2, BlockCompression::Avx2] {
+                if !block_compression.is_supported() {
+                    continue;
+                }
+                let mut next = reference;
+                fill_block_fn(*block_compression)(&prev, &reference, &mut next, *with_xor);
+                assert_eq!(&next.0[..], &expected.0[..]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_block_bytes_roundtrip() {
+        let mut bytes = [0u8; BLOCK_SIZE];
+        for (i, byte) in bytes.iter_mut().enumerate() {
+            *byte = i as u8;
+        }
+        let block = Block::from_bytes(&bytes[..]);
+        assert_eq!(block[0], 0x0706_0504_0302_0100);
+        assert_eq!(&block.as_le_bytes()[..], &bytes[..]);
+    }
+
+    #[test]
+    fn test_fill_block_with_xor() {
+        let (prev, reference) = test_blocks();
+        let mut without_xor = Block::default();
+        fill_block(&prev, &reference, &mut without_xor, false);
+
+        let mut existing = Block::default();
+        existing[7] = 0xDEAD_BEEF;
+        let mut with_xor = existing;
+        fill_block(&prev, &reference, &mut with_xor, true);
+
+        let mut expected = without_xor;
+        expected ^= &existing;
+        assert_eq!(&with_xor.0[..], &expected.0[..]);
+    }
+}
@@ -0,0 +1,26 @@
+use blake2_rfc::blake2b::Blake2b;
+
+use backend::rust::core::Context;
+
+pub(crate) const PREHASH_DIGEST_LENGTH: usize = 64;
+pub(crate) const PREHASH_SEED_LENGTH: usize = 72;
+
+/// Computes the 64-byte pre-hashing digest H0 from section 3.2 of the Argon2 specification.
+/// The returned array is 72 bytes long so that the caller can append the little-endian block
+/// index and lane index when generating the first two blocks of each lane
+pub(crate) fn h0(context: &Context) -> [u8; PREHASH_SEED_LENGTH] {
+    let mut state = Blake2b::new(PREHASH_DIGEST_LENGTH);
+    state.update(&context.lanes.to_le_bytes());
+    state.update(&context.hash_len.to_le_bytes());
+    state.update(&context.memory_size.to_le_bytes());
+    state.update(&context.iterations.to_le_bytes());
+    state.update(&(context.version as u32).to_le_bytes());
+    state.update(&(context.variant as u32).to_le_bytes());
+    for input in &[context.pwd, context.salt, context.secret, context.ad] {
+        state.update(&(input.len() as u32).to_le_bytes());
+        state.update(input);
+    }
+    let mut seed = [0u8; PREHASH_SEED_LENGTH];
+    seed[..PREHASH_DIGEST_LENGTH].copy_from_slice(state.finalize().as_bytes());
+    seed
+}
@@ -0,0 +1,77 @@
+use blake2_rfc::blake2b::Blake2b;
+
+const BLAKE2B_OUTBYTES: usize = 64;
+
+/// The variable-length hash function H' from section 3.2 of the Argon2 specification. Fills
+/// `out` with `out.len()` bytes derived from `input`
+pub(crate) fn h_prime(out: &mut [u8], input: &[u8]) {
+    let out_len = out.len();
+    let out_len_bytes = (out_len as u32).to_le_bytes();
+    if out_len <= BLAKE2B_OUTBYTES {
+        let mut state = Blake2b::new(out_len);
+        state.update(&out_len_bytes);
+        state.update(input);
+        out.copy_from_slice(state.finalize().as_bytes());
+        return;
+    }
+
+    let half = BLAKE2B_OUTBYTES / 2;
+    let mut v = {
+        let mut state = Blake2b::new(BLAKE2B_OUTBYTES);
+        state.update(&out_len_bytes);
+        state.update(input);
+        let mut v = [0u8; BLAKE2B_OUTBYTES];
+        v.copy_from_slice(state.finalize().as_bytes());
+        v
+    };
+    out[..half].copy_from_slice(&v[..half]);
+    let mut position = half;
+    let mut to_produce = out_len - half;
+    while to_produce > BLAKE2B_OUTBYTES {
+        let mut state = Blake2b::new(BLAKE2B_OUTBYTES);
+        state.update(&v);
+        v.copy_from_slice(state.finalize().as_bytes());
+        out[position..position + half].copy_from_slice(&v[..half]);
+        position += half;
+        to_produce -= half;
+    }
+    let mut state = Blake2b::new(to_produce);
+    state.update(&v);
+    out[position..].copy_from_slice(state.finalize().as_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_h_prime_short_output_is_prefixed_blake2b() {
+        let mut out = [0u8; 32];
+        h_prime(&mut out, b"input");
+        let mut state = Blake2b::new(32);
+        state.update(&32u32.to_le_bytes());
+        state.update(b"input");
+        assert_eq!(&out[..], state.finalize().as_bytes());
+    }
+
+    #[test]
+    fn test_h_prime_long_output_chains_blocks() {
+        let mut out = [0u8; 100];
+        h_prime(&mut out, b"input");
+
+        let mut state = Blake2b::new(64);
+        state.update(&100u32.to_le_bytes());
+        state.update(b"input");
+        let v1 = state.finalize();
+        assert_eq!(&out[..32], &v1.as_bytes()[..32]);
+
+        let mut state = Blake2b::new(64);
+        state.update(v1.as_bytes());
+        let v2 = state.finalize();
+        assert_eq!(&out[32..64], &v2.as_bytes()[..32]);
+
+        let mut state = Blake2b::new(36);
+        state.update(v2.as_bytes());
+        assert_eq!(&out[64..], state.finalize().as_bytes());
+    }
+}
@@ -1,7 +1,494 @@
-#![allow(dead_code)]
+//! A pure-Rust implementation of the Argon2 algorithm, following the structure of the
+//! [reference C implementation](https://github.com/P-H-C/phc-winner-argon2)
+mod block;
+mod h0;
+mod h_prime;
 
-use {Error, Hasher};
+use std::alloc::Layout;
+use std::ops::{Deref, DerefMut};
+use std::ptr::{self, NonNull};
+use std::slice;
+use std::thread;
 
-fn h0(_hasher: &mut Hasher) -> Result<[u8; 72], Error> {
-    unimplemented!();
+use self::block::{fill_block_fn, Block, FillBlock, BLOCK_SIZE, QWORDS_IN_BLOCK};
+use self::h0::{h0, PREHASH_DIGEST_LENGTH};
+use self::h_prime::h_prime;
+use allocator::{work_area_layout, MemoryAllocator, MEMORY_ALIGNMENT};
+use config::{BlockCompression, Variant, Version};
+use utils::{wipe, wipe_raw};
+use {Error, ErrorKind};
+
+const SYNC_POINTS: u32 = 4;
+const ADDRESSES_IN_BLOCK: u32 = QWORDS_IN_BLOCK as u32;
+
+/// Everything the Argon2 algorithm needs to produce a hash. This is the Rust analogue of
+/// the C library's `Argon2_Context`
+pub(crate) struct Context<'a> {
+    pub(crate) ad: &'a [u8],
+    pub(crate) block_compression: BlockCompression,
+    pub(crate) hash_len: u32,
+    pub(crate) iterations: u32,
+    pub(crate) lanes: u32,
+    pub(crate) memory_allocator: Option<&'a dyn MemoryAllocator>,
+    pub(crate) memory_size: u32,
+    pub(crate) pwd: &'a [u8],
+    pub(crate) salt: &'a [u8],
+    pub(crate) secret: &'a [u8],
+    pub(crate) threads: u32,
+    pub(crate) variant: Variant,
+    pub(crate) version: Version,
+}
+
+/// Runs Argon2 over the provided [`Context`](struct.Context.html), returning the raw hash bytes
+pub(crate) fn argon2(context: &Context) -> Result<Vec<u8>, Error> {
+    let instance = Instance::new(context);
+    let mut memory = allocate(instance.memory_blocks as usize, context.memory_allocator)?;
+    instance.fill_first_blocks(&mut memory, context);
+    instance.fill_memory_blocks(&mut memory)?;
+    let mut out = vec![0u8; context.hash_len as usize];
+    instance.finalize(&memory, &mut out);
+    Ok(out)
+}
+
+fn allocate(blocks: usize, allocator: Option<&dyn MemoryAllocator>) -> Result<Memory<'_>, Error> {
+    let allocator = match allocator {
+        Some(allocator) => allocator,
+        None => {
+            let mut memory = Vec::new();
+            memory.try_reserve_exact(blocks).map_err(|_| {
+                Error::new(ErrorKind::MemoryAllocationError)
+                    .add_context(format!("Blocks: {}", blocks))
+            })?;
+            memory.resize(blocks, Block::default());
+            return Ok(Memory::Heap(memory));
+        }
+    };
+    let layout = work_area_layout(blocks * BLOCK_SIZE)?;
+    let ptr = allocator.allocate(layout)?;
+    if ptr.as_ptr() as usize & (MEMORY_ALIGNMENT - 1) != 0 {
+        unsafe { allocator.deallocate(ptr, layout) };
+        return Err(Error::new(ErrorKind::MemoryAllocationError)
+            .add_context("The memory allocator returned misaligned memory"));
+    }
+    let ptr = ptr.cast::<Block>();
+    // Safe: the allocator provides `blocks` blocks of valid, suitably aligned memory
+    unsafe { ptr::write_bytes(ptr.as_ptr(), 0, blocks) };
+    Ok(Memory::Allocated {
+        allocator,
+        blocks,
+        layout,
+        ptr,
+    })
+}
+
+/// The memory blocks of a hash, allocated either on the heap or by a custom
+/// [`MemoryAllocator`](../../../allocator/trait.MemoryAllocator.html)
+enum Memory<'a> {
+    Allocated {
+        allocator: &'a dyn MemoryAllocator,
+        blocks: usize,
+        layout: Layout,
+        ptr: NonNull<Block>,
+    },
+    Heap(Vec<Block>),
+}
+
+impl<'a> Deref for Memory<'a> {
+    type Target = [Block];
+
+    fn deref(&self) -> &[Block] {
+        match *self {
+            Memory::Allocated { blocks, ptr, .. } => unsafe {
+                slice::from_raw_parts(ptr.as_ptr(), blocks)
+            },
+            Memory::Heap(ref memory) => memory,
+        }
+    }
+}
+
+impl<'a> DerefMut for Memory<'a> {
+    fn deref_mut(&mut self) -> &mut [Block] {
+        match *self {
+            Memory::Allocated { blocks, ptr, .. } => unsafe {
+                slice::from_raw_parts_mut(ptr.as_ptr(), blocks)
+            },
+            Memory::Heap(ref mut memory) => memory,
+        }
+    }
+}
+
+impl<'a> Drop for Memory<'a> {
+    fn drop(&mut self) {
+        // The blocks are derived from the password (and secret key), so they are wiped
+        // before the memory is freed
+        unsafe { wipe_raw(self.as_mut_ptr() as *mut u8, self.len() * BLOCK_SIZE) };
+        if let Memory::Allocated {
+            allocator,
+            layout,
+            ptr,
+            ..
+        } = *self
+        {
+            unsafe { allocator.deallocate(ptr.cast(), layout) };
+        }
+    }
+}
+
+#[derive(Copy, Clone)]
+struct Position {
+    pass: u32,
+    lane: u32,
+    slice: u32,
+    index: u32,
+}
+
+#[derive(Copy, Clone)]
+struct Instance {
+    fill_block: FillBlock,
+    lane_length: u32,
+    lanes: u32,
+    memory_blocks: u32,
+    passes: u32,
+    segment_length: u32,
+    threads: u32,
+    variant: Variant,
+    version: Version,
+}
+
+impl Instance {
+    fn new(context: &Context) -> Instance {
+        let mut memory_blocks = context.memory_size;
+        if memory_blocks < 2 * SYNC_POINTS * context.lanes {
+            memory_blocks = 2 * SYNC_POINTS * context.lanes;
+        }
+        let segment_length = memory_blocks / (context.lanes * SYNC_POINTS);
+        let memory_blocks = segment_length * (context.lanes * SYNC_POINTS);
+        let threads = if context.threads > context.lanes {
+            context.lanes
+        } else {
+            context.threads
+        };
+        Instance {
+            fill_block: fill_block_fn(context.block_compression),
+            lane_length: segment_length * SYNC_POINTS,
+            lanes: context.lanes,
+            memory_blocks,
+            passes: context.iterations,
+            segment_length,
+            threads,
+            variant: context.variant,
+            version: context.version,
+        }
+    }
+
+    fn fill_first_blocks(&self, memory: &mut [Block], context: &Context) {
+        let mut seed = h0(context);
+        let mut bytes = [0u8; BLOCK_SIZE];
+        for lane in 0..self.lanes {
+            let offset = (lane * self.lane_length) as usize;
+            seed[PREHASH_DIGEST_LENGTH + 4..].copy_from_slice(&lane.to_le_bytes());
+            for index in 0..2u32 {
+                seed[PREHASH_DIGEST_LENGTH..PREHASH_DIGEST_LENGTH + 4]
+                    .copy_from_slice(&index.to_le_bytes());
+                h_prime(&mut bytes, &seed);
+                memory[offset + index as usize] = Block::from_bytes(&bytes);
+            }
+        }
+        wipe(&mut seed);
+        wipe(&mut bytes);
+    }
+
+    fn fill_memory_blocks(&self, memory: &mut [Block]) -> Result<(), Error> {
+        let memory_ptr = MemoryPtr(memory.as_mut_ptr());
+        for pass in 0..self.passes {
+            for slice in 0..SYNC_POINTS {
+                if self.threads <= 1 {
+                    for lane in 0..self.lanes {
+                        let position = Position {
+                            pass,
+                            lane,
+                            slice,
+                            index: 0,
+                        };
+                        // Safe: segments are filled one at a time on this thread
+                        unsafe { self.fill_segment(memory_ptr, position) };
+                    }
+                    continue;
+                }
+                let mut lane = 0;
+                while lane < self.lanes {
+                    let end = ::std::cmp::min(lane + self.threads, self.lanes);
+                    let handles = (lane..end)
+                        .map(|lane| {
+                            let instance = *self;
+                            let position = Position {
+                                pass,
+                                lane,
+                                slice,
+                                index: 0,
+                            };
+                            // Safe: each thread writes only to its own segment and reads only
+                            // from segments that were completed before this slice began. All
+                            // threads are joined before `memory` is touched again
+                            thread::spawn(move || unsafe {
+                                instance.fill_segment(memory_ptr, position)
+                            })
+                        })
+                        .collect::<Vec<_>>();
+                    for handle in handles {
+                        handle
+                            .join()
+                            .map_err(|_| Error::new(ErrorKind::ThreadError))?;
+                    }
+                    lane = end;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Fills a single segment (one lane of one slice of one pass)
+    ///
+    /// Unsafe because `memory` must point to `memory_blocks` initialized blocks and no other
+    /// thread may concurrently access the blocks written by this segment
+    unsafe fn fill_segment(&self, memory: MemoryPtr, mut position: Position) {
+        let memory = memory.0;
+        let data_independent_addressing = match self.variant {
+            Variant::Argon2d => false,
+            Variant::Argon2i => true,
+            Variant::Argon2id => position.pass == 0 && position.slice < SYNC_POINTS / 2,
+        };
+        let zero_block = Block::default();
+        let mut input_block = Block::default();
+        let mut address_block = Block::default();
+        if data_independent_addressing {
+            input_block[0] = u64::from(position.pass);
+            input_block[1] = u64::from(position.lane);
+            input_block[2] = u64::from(position.slice);
+            input_block[3] = u64::from(self.memory_blocks);
+            input_block[4] = u64::from(self.passes);
+            input_block[5] = self.variant as u64;
+        }
+
+        let mut starting_index = 0;
+        if position.pass == 0 && position.slice == 0 {
+            starting_index = 2;
+            if data_independent_addressing {
+                self.next_addresses(&mut address_block, &mut input_block, &zero_block);
+            }
+        }
+
+        let segment_offset =
+            position.lane * self.lane_length + position.slice * self.segment_length;
+        for i in starting_index..self.segment_length {
+            let curr_offset = segment_offset + i;
+            // The block preceding the first block of a lane is the last block of that lane
+            let prev_offset = if position.slice == 0 && i == 0 {
+                curr_offset + self.lane_length - 1
+            } else {
+                curr_offset - 1
+            };
+
+            let pseudo_rand = if data_independent_addressing {
+                if i % ADDRESSES_IN_BLOCK == 0 {
+                    self.next_addresses(&mut address_block, &mut input_block, &zero_block);
+                }
+                address_block[(i % ADDRESSES_IN_BLOCK) as usize]
+            } else {
+                (*memory.offset(prev_offset as isize)).0[0]
+            };
+
+            let ref_lane = if position.pass == 0 && position.slice == 0 {
+                position.lane
+            } else {
+                ((pseudo_rand >> 32) % u64::from(self.lanes)) as u32
+            };
+
+            position.index = i;
+            let ref_index = self.index_alpha(
+                &position,
+                (pseudo_rand & 0xFFFF_FFFF) as u32,
+                ref_lane == position.lane,
+            );
+
+            let ref_block = &*memory.offset((self.lane_length * ref_lane + ref_index) as isize);
+            let prev_block = &*memory.offset(prev_offset as isize);
+            let curr_block = &mut *memory.offset(curr_offset as isize);
+            let with_xor = match self.version {
+                Version::_0x10 => false,
+                Version::_0x13 => position.pass != 0,
+            };
+            (self.fill_block)(prev_block, ref_block, curr_block, with_xor);
+        }
+    }
+
+    /// Maps a pseudo-random value onto the index of the reference block within its lane
+    fn index_alpha(&self, position: &Position, pseudo_rand: u32, same_lane: bool) -> u32 {
+        let reference_area_size = if position.pass == 0 {
+            if position.slice == 0 {
+                position.index - 1
+            } else if same_lane {
+                position.slice * self.segment_length + position.index - 1
+            } else if position.index == 0 {
+                position.slice * self.segment_length - 1
+            } else {
+                position.slice * self.segment_length
+            }
+        } else if same_lane {
+            self.lane_length - self.segment_length + position.index - 1
+        } else if position.index == 0 {
+            self.lane_length - self.segment_length - 1
+        } else {
+            self.lane_length - self.segment_length
+        };
+
+        let pseudo_rand = u64::from(pseudo_rand);
+        let relative_position = (pseudo_rand * pseudo_rand) >> 32;
+        let relative_position = u64::from(reference_area_size)
+            - 1
+            - ((u64::from(reference_area_size) * relative_position) >> 32);
+
+        let start_position = if position.pass != 0 && position.slice != SYNC_POINTS - 1 {
+            u64::from((position.slice + 1) * self.segment_length)
+        } else {
+            0
+        };
+
+        ((start_position + relative_position) % u64::from(self.lane_length)) as u32
+    }
+
+    fn finalize(&self, memory: &[Block], out: &mut [u8]) {
+        let mut block_hash = memory[(self.lane_length - 1) as usize];
+        for lane in 1..self.lanes {
+            let last_block_in_lane = lane * self.lane_length + (self.lane_length - 1);
+            block_hash ^= &memory[last_block_in_lane as usize];
+        }
+        let mut bytes = block_hash.as_le_bytes();
+        h_prime(out, &bytes);
+        wipe(&mut bytes);
+        block_hash.wipe();
+    }
+
+    fn next_addresses(&self, address_block: &mut Block, input_block: &mut Block, zero: &Block) {
+        input_block[6] += 1;
+        (self.fill_block)(zero, input_block, address_block, false);
+        let tmp = *address_block;
+        (self.fill_block)(zero, &tmp, address_block, false);
+    }
+}
+
+#[derive(Copy, Clone)]
+struct MemoryPtr(*mut Block);
+
+unsafe impl Send for MemoryPtr {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Test {
+        variant: Variant,
+        version: Version,
+        expected: &'static str,
+    }
+
+    impl Test {
+        fn run(&self) {
+            // Test vectors from the Argon2 specification (RFC 9106)
+            let pwd = [1u8; 32];
+            let salt = [2u8; 16];
+            let secret = [3u8; 8];
+            let ad = [4u8; 12];
+            let block_compressions = [
+                BlockCompression::Reference,
+                BlockCompression::Sse2,
+                BlockCompression::Avx2,
+            ];
+            for block_compression in block_compressions.iter().filter(|b| b.is_supported()) {
+                for threads in &[1, 4] {
+                    let context = Context {
+                        ad: &ad,
+                        block_compression: *block_compression,
+                        hash_len: 32,
+                        iterations: 3,
+                        lanes: 4,
+                        memory_allocator: None,
+                        memory_size: 32,
+                        pwd: &pwd,
+                        salt: &salt,
+                        secret: &secret,
+                        threads: *threads,
+                        variant: self.variant,
+                        version: self.version,
+                    };
+                    let out = argon2(&context).unwrap();
+                    let hex = out
+                        .iter()
+                        .map(|byte| format!("{:02x}", byte))
+                        .collect::<String>();
+                    assert_eq!(&hex, self.expected);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_argon2_0x13_2d() {
+        Test {
+            variant: Variant::Argon2d,
+            version: Version::_0x13,
+            expected: "512b391b6f1162975371d30919734294f868e3be3984f3c1a13a4db9fabe4acb",
+        }
+        .run();
+    }
+
+    #[test]
+    fn test_argon2_0x13_2i() {
+        Test {
+            variant: Variant::Argon2i,
+            version: Version::_0x13,
+            expected: "c814d9d1dc7f37aa13f0d77f2494bda1c8de6b016dd388d29952a4c4672b6ce8",
+        }
+        .run();
+    }
+
+    #[test]
+    fn test_argon2_0x13_2id() {
+        Test {
+            variant: Variant::Argon2id,
+            version: Version::_0x13,
+            expected: "0d640df58d78766c08c037a34a8b53c9d01ef0452d75b65eb52520e96b01e659",
+        }
+        .run();
+    }
+
+    #[test]
+    fn test_argon2_0x10_2d() {
+        Test {
+            variant: Variant::Argon2d,
+            version: Version::_0x10,
+            expected: "96a9d4e5a1734092c85e29f410a45914a5dd1f5cbf08b2670da68a0285abf32b",
+        }
+        .run();
+    }
+
+    #[test]
+    fn test_argon2_0x10_2i() {
+        Test {
+            variant: Variant::Argon2i,
+            version: Version::_0x10,
+            expected: "87aeedd6517ab830cd9765cd8231abb2e647a5dee08f7c05e02fcb763335d0fd",
+        }
+        .run();
+    }
+
+    #[test]
+    fn test_argon2_0x10_2id() {
+        Test {
+            variant: Variant::Argon2id,
+            version: Version::_0x10,
+            expected: "b64615f07789b66b645b67ee9ed3b377ae350b6bfcbb0fc95141ea8f322613c0",
+        }
+        .run();
+    }
 }
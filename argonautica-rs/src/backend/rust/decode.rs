@@ -4,76 +4,245 @@ use config::{Variant, Version};
 use output::HashRaw;
 use {Error, ErrorKind};
 
+/// Maximum length (in bytes) of the `keyid` parameter, per the PHC string format specification
+/// for Argon2
+const MAX_KEY_ID_LEN: usize = 8;
+
+/// Maximum length (in bytes) of the `data` parameter, per the PHC string format specification
+/// for Argon2
+const MAX_DATA_LEN: usize = 32;
+
+/// Parses a hash in the
+/// [PHC string format](https://github.com/P-H-C/phc-string-format/blob/master/phc-sf-spec.md),
+/// i.e. `$<variant>[$v=<version>]$<param>=<value>(,<param>=<value>)*$<salt>$<hash>`, where the
+/// parameters `m`, `t` and `p` are required and `keyid` and `data` are optional, in any order.
+/// A missing version means version 0x10 (hashes created before the version field existed).
+/// Leading and trailing whitespace is ignored, so hashes stored with surrounding whitespace
+/// (e.g. indented in a string literal or a config file) keep decoding. Errors report the byte
+/// position in `hash` at which parsing failed
 pub(crate) fn decode_rust(hash: &str) -> Result<HashRaw, Error> {
-    let (rest, intermediate) = parse_hash(hash).map_err(|_| {
-        Error::new(ErrorKind::HashDecodeError).add_context(format!("Hash: {}", &hash))
-    })?;
-    let raw_hash_bytes = base64::decode_config(rest, base64::STANDARD_NO_PAD).map_err(|_| {
-        Error::new(ErrorKind::HashDecodeError).add_context(format!("Hash: {}", &hash))
-    })?;
-    let hash_raw = HashRaw {
-        iterations: intermediate.iterations,
-        lanes: intermediate.lanes,
-        memory_size: intermediate.memory_size,
-        raw_hash_bytes: raw_hash_bytes,
-        raw_salt_bytes: intermediate.raw_salt_bytes,
-        variant: intermediate.variant,
-        version: intermediate.version,
-    };
-    Ok(hash_raw)
+    parse_rust(hash).map_err(|e| e.into_error(hash))
+}
+
+/// Same as [`decode_rust`](fn.decode_rust.html), except that the error does not include
+/// `hash`, only why and at which position parsing failed
+pub(crate) fn parse_rust(hash: &str) -> Result<HashRaw, DecodeError> {
+    Parser::new(hash).parse(true)
+}
+
+/// Same as [`decode_rust`](fn.decode_rust.html), except that it parses parameters and a salt
+/// without a hash, i.e. `$<variant>[$v=<version>]$<param>=<value>(,<param>=<value>)*$<salt>`
+/// (which the PHC string format also allows). The returned
+/// [`HashRaw`](../../../output/struct.HashRaw.html) has no raw hash bytes
+pub(crate) fn decode_params_rust(params: &str) -> Result<HashRaw, Error> {
+    Parser::new(params)
+        .parse(false)
+        .map_err(|e| e.into_error(params))
+}
+
+/// Why and where parsing a hash failed
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct DecodeError {
+    message: String,
+    pos: usize,
 }
 
-struct IntermediateStruct {
-    variant: Variant,
-    version: Version,
-    memory_size: u32,
-    iterations: u32,
-    lanes: u32,
-    raw_salt_bytes: Vec<u8>,
+impl DecodeError {
+    /// The byte position at which parsing failed
+    pub(crate) fn pos(&self) -> usize {
+        self.pos
+    }
+
+    fn into_error(self, hash: &str) -> Error {
+        Error::new(ErrorKind::HashDecodeError).add_context(format!(
+            "{} at position {}. Hash: {}",
+            self.message, self.pos, hash,
+        ))
+    }
+}
+
+struct Parser<'a> {
+    hash: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    /// Creates a parser that skips the whitespace surrounding `hash`, while still counting
+    /// positions from the start of `hash`
+    fn new(hash: &'a str) -> Parser<'a> {
+        let hash = hash.trim_end();
+        Parser {
+            hash,
+            pos: hash.len() - hash.trim_start().len(),
+        }
+    }
+
+    fn parse(&mut self, with_hash: bool) -> Result<HashRaw, DecodeError> {
+        self.expect_dollar()?;
+        let variant_pos = self.pos;
+        let variant = self.field().parse::<Variant>().map_err(|_| {
+            self.error_at(
+                variant_pos,
+                "expected variant (argon2d, argon2i or argon2id)",
+            )
+        })?;
+
+        self.expect_dollar()?;
+        let version = if self.rest().starts_with("v=") {
+            let version_pos = self.pos + 2;
+            let field = self.field();
+            let version = parse_decimal(&field[2..])
+                .and_then(|value| Version::from_u32(value).ok())
+                .ok_or_else(|| self.error_at(version_pos, "expected version (16 or 19)"))?;
+            self.expect_dollar()?;
+            version
+        } else {
+            Version::_0x10
+        };
+
+        let mut memory_size = None;
+        let mut iterations = None;
+        let mut lanes = None;
+        let mut key_id = None;
+        let mut data = None;
+        let params_pos = self.pos;
+        let params = self.field();
+        let mut param_pos = params_pos;
+        for param in params.split(',') {
+            let (name, value) = match param.find('=') {
+                Some(i) => (&param[..i], &param[i + 1..]),
+                None => return Err(self.error_at(param_pos, "expected <param>=<value>")),
+            };
+            let value_pos = param_pos + name.len() + 1;
+            let is_duplicate = match name {
+                "m" => memory_size
+                    .replace(self.decimal(value, value_pos)?)
+                    .is_some(),
+                "t" => iterations
+                    .replace(self.decimal(value, value_pos)?)
+                    .is_some(),
+                "p" => lanes.replace(self.decimal(value, value_pos)?).is_some(),
+                "keyid" => key_id
+                    .replace(self.base64(value, value_pos, MAX_KEY_ID_LEN)?)
+                    .is_some(),
+                "data" => data
+                    .replace(self.base64(value, value_pos, MAX_DATA_LEN)?)
+                    .is_some(),
+                _ => return Err(self.error_at(param_pos, &format!("unknown parameter {:?}", name))),
+            };
+            if is_duplicate {
+                return Err(self.error_at(param_pos, &format!("duplicate parameter {:?}", name)));
+            }
+            param_pos += param.len() + 1;
+        }
+        let memory_size = memory_size.ok_or_else(|| self.missing(params_pos, "m"))?;
+        let iterations = iterations.ok_or_else(|| self.missing(params_pos, "t"))?;
+        let lanes = lanes.ok_or_else(|| self.missing(params_pos, "p"))?;
+
+        self.expect_dollar()?;
+        let salt_pos = self.pos;
+        let salt = self.field();
+        let raw_salt_bytes = self.base64(salt, salt_pos, usize::MAX)?;
+
+        let raw_hash_bytes = if with_hash {
+            self.expect_dollar()?;
+            let hash_pos = self.pos;
+            let hash = self.field();
+            if !self.rest().is_empty() {
+                return Err(self.error("unexpected trailing characters"));
+            }
+            let raw_hash_bytes = self.base64(hash, hash_pos, usize::MAX)?;
+            if raw_hash_bytes.is_empty() {
+                return Err(self.error_at(hash_pos, "hash is empty"));
+            }
+            raw_hash_bytes
+        } else {
+            if !self.rest().is_empty() {
+                return Err(self.error("unexpected trailing characters"));
+            }
+            Vec::new()
+        };
+
+        Ok(HashRaw {
+            data,
+            iterations,
+            key_id,
+            lanes,
+            memory_size,
+            raw_hash_bytes,
+            raw_salt_bytes,
+            variant,
+            version,
+        })
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.hash[self.pos..]
+    }
+
+    /// Consumes and returns everything up to (but not including) the next `$` or the end
+    fn field(&mut self) -> &'a str {
+        let rest = self.rest();
+        let len = rest.find('$').unwrap_or(rest.len());
+        self.pos += len;
+        &rest[..len]
+    }
+
+    fn expect_dollar(&mut self) -> Result<(), DecodeError> {
+        if !self.rest().starts_with('$') {
+            return Err(self.error("expected '$'"));
+        }
+        self.pos += 1;
+        Ok(())
+    }
+
+    fn decimal(&self, value: &str, pos: usize) -> Result<u32, DecodeError> {
+        parse_decimal(value).ok_or_else(|| self.error_at(pos, "expected a decimal number"))
+    }
+
+    fn base64(&self, value: &str, pos: usize, max_len: usize) -> Result<Vec<u8>, DecodeError> {
+        let bytes = base64::decode_config(value, base64::STANDARD_NO_PAD)
+            .map_err(|_| self.error_at(pos, "invalid base64"))?;
+        if bytes.len() > max_len {
+            return Err(self.error_at(
+                pos,
+                &format!("value too long (maximum is {} bytes)", max_len),
+            ));
+        }
+        Ok(bytes)
+    }
+
+    fn missing(&self, pos: usize, name: &str) -> DecodeError {
+        self.error_at(pos, &format!("missing required parameter {:?}", name))
+    }
+
+    fn error(&self, message: &str) -> DecodeError {
+        self.error_at(self.pos, message)
+    }
+
+    fn error_at(&self, pos: usize, message: &str) -> DecodeError {
+        DecodeError {
+            message: message.to_string(),
+            pos,
+        }
+    }
 }
 
-#[cfg_attr(rustfmt, rustfmt_skip)]
-named!(parse_hash<&str, IntermediateStruct>, do_parse!(
-    take_until!("$") >>
-    take!(1) >>
-    variant: map_res!(take_until!("$"), |x: &str| x.parse::<Variant>()) >>
-    take_until!("$v=") >>
-    take!(3) >>
-    version: map_res!(take_until!("$"), |x: &str| x.parse::<Version>()) >>
-    take_until!("$m=") >>
-    take!(3) >>
-    memory_size: map_res!(take_until!(","), |x: &str| x.parse::<u32>()) >>
-    take_until!(",t=") >>
-    take!(3) >>
-    iterations: map_res!(take_until!(","), |x: &str| x.parse::<u32>()) >>
-    take_until!(",p=") >>
-    take!(3) >>
-    lanes: map_res!(take_until!("$"), |x: &str| x.parse::<u32>()) >>
-    take_until!("$") >>
-    take!(1) >>
-    raw_salt_bytes: map_res!(take_until!("$"), |x: &str| {
-        base64::decode_config(x, base64::STANDARD_NO_PAD)
-    }) >>
-    take_until!("$") >>
-    take!(1) >>
-    (IntermediateStruct {
-        iterations,
-        lanes,
-        memory_size,
-        raw_salt_bytes,
-        variant,
-        version,
-    })
-));
+/// Parses a decimal value as the PHC string format defines it: ASCII digits only, with no sign
+/// and no leading zeros
+fn parse_decimal(value: &str) -> Option<u32> {
+    if value.is_empty()
+        || !value.bytes().all(|b| b.is_ascii_digit())
+        || (value.len() > 1 && value.starts_with('0'))
+    {
+        return None;
+    }
+    value.parse::<u32>().ok()
+}
 
 #[cfg(test)]
 mod tests {
-    use rand::rngs::StdRng;
-    use rand::{RngCore, SeedableRng};
-
     use super::*;
-    use backend::c::decode_c;
-    use hasher::Hasher;
 
     #[test]
     fn test_decode() {
@@ -102,9 +271,156 @@ mod tests {
         assert_eq!(hash_raw.lanes(), 1);
     }
 
+    #[test]
+    fn test_decode_surrounding_whitespace() {
+        // The hash of the verification example in the README, which begins with a newline and
+        // indentation. It decoded before the PHC string parser was rewritten, so it must still
+        let hash = "
+            $argon2id$v=19$m=4096,t=192,p=4$\
+            o2y5PU86Vt+sr93N7YUGgC7AMpTKpTQCk4tNGUPZMY4$\
+            yzP/ukZRPIbZg6PvgnUUobUMbApfF9RH6NagL9L4Xr4\
+        ";
+        let hash_raw = decode_rust(hash).unwrap();
+        assert_eq!(hash_raw.memory_size(), 4096);
+        assert_eq!(hash_raw.iterations(), 192);
+        assert_eq!(hash_raw.lanes(), 4);
+        assert_eq!(hash_raw, decode_rust(hash.trim()).unwrap());
+        assert_eq!(
+            hash_raw,
+            decode_rust(&format!("{} \r\n", hash.trim())).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_decode_legacy_without_version() {
+        let hash = "$argon2i$m=65536,t=2,p=4$c29tZXNhbHQ$RdescudvJCsgt3ub+b+dWRWJTmaaJObG";
+        let hash_raw = decode_rust(hash).unwrap();
+        assert_eq!(hash_raw.variant(), Variant::Argon2i);
+        assert_eq!(hash_raw.version(), Version::_0x10);
+        assert_eq!(hash_raw.memory_size(), 65536);
+        assert_eq!(hash_raw.iterations(), 2);
+        assert_eq!(hash_raw.lanes(), 4);
+        assert_eq!(hash_raw.raw_salt_bytes(), b"somesalt");
+    }
+
+    #[test]
+    fn test_decode_key_id_and_data_in_any_order() {
+        let hash = "$argon2id$v=19$data=YWQ,p=2,keyid=a2V5,t=3,m=64$c29tZXNhbHQ$AAECAwQFBgc";
+        let hash_raw = decode_rust(hash).unwrap();
+        assert_eq!(hash_raw.memory_size(), 64);
+        assert_eq!(hash_raw.iterations(), 3);
+        assert_eq!(hash_raw.lanes(), 2);
+        assert_eq!(hash_raw.key_id(), Some(&b"key"[..]));
+        assert_eq!(hash_raw.data(), Some(&b"ad"[..]));
+        assert_eq!(hash_raw.raw_hash_bytes(), &[0, 1, 2, 3, 4, 5, 6, 7]);
+
+        // Re-encoding puts the parameters in canonical order
+        assert_eq!(
+            hash_raw.encode_rust(),
+            "$argon2id$v=19$m=64,t=3,p=2,keyid=a2V5,data=YWQ$c29tZXNhbHQ$AAECAwQFBgc",
+        );
+        assert_eq!(decode_rust(&hash_raw.encode_rust()).unwrap(), hash_raw);
+    }
+
+    #[test]
+    fn test_decode_errors() {
+        fn error(hash: &str) -> String {
+            let err = decode_rust(hash).unwrap_err();
+            assert_eq!(err.kind(), ErrorKind::HashDecodeError);
+            format!("{}", err)
+        }
+        let cases = [
+            (
+                "argon2id$v=19$m=64,t=3,p=2$c2FsdHNhbHQ$AAECAwQFBgc",
+                "expected '$' at position 0",
+            ),
+            (
+                "  argon2id$v=19$m=64,t=3,p=2$c2FsdHNhbHQ$AAECAwQFBgc",
+                "expected '$' at position 2",
+            ),
+            (
+                "$argon2x$v=19$m=64,t=3,p=2$c2FsdHNhbHQ$AAECAwQFBgc",
+                "expected variant (argon2d, argon2i or argon2id) at position 1",
+            ),
+            (
+                "$argon2id$v=18$m=64,t=3,p=2$c2FsdHNhbHQ$AAECAwQFBgc",
+                "expected version (16 or 19) at position 12",
+            ),
+            (
+                "$argon2id$v=19$m=64,t=03,p=2$c2FsdHNhbHQ$AAECAwQFBgc",
+                "expected a decimal number at position 22",
+            ),
+            (
+                "$argon2id$v=19$m=64,t=3,p=-2$c2FsdHNhbHQ$AAECAwQFBgc",
+                "expected a decimal number at position 26",
+            ),
+            (
+                "$argon2id$v=19$m=64,t=99999999999,p=2$c2FsdHNhbHQ$AAECAwQFBgc",
+                "expected a decimal number at position 22",
+            ),
+            (
+                "$argon2id$v=19$m=64,t=3$c2FsdHNhbHQ$AAECAwQFBgc",
+                "missing required parameter \"p\" at position 15",
+            ),
+            (
+                "$argon2id$v=19$m=64,t=3,p=2,t=4$c2FsdHNhbHQ$AAECAwQFBgc",
+                "duplicate parameter \"t\" at position 28",
+            ),
+            (
+                "$argon2id$v=19$m=64,t=3,p=2,x=4$c2FsdHNhbHQ$AAECAwQFBgc",
+                "unknown parameter \"x\" at position 28",
+            ),
+            (
+                "$argon2id$v=19$m=64,t3,p=2$c2FsdHNhbHQ$AAECAwQFBgc",
+                "expected <param>=<value> at position 20",
+            ),
+            (
+                "$argon2id$v=19$m=64,t=3,p=2,keyid=AAECAwQFBgcI$c2FsdHNhbHQ$AAECAwQFBgc",
+                "value too long (maximum is 8 bytes) at position 34",
+            ),
+            (
+                "$argon2id$v=19$m=64,t=3,p=2$c2F*dHNhbHQ$AAECAwQFBgc",
+                "invalid base64 at position 28",
+            ),
+            (
+                "$argon2id$v=19$m=64,t=3,p=2$c2FsdHNhbHQ",
+                "expected '$' at position 39",
+            ),
+            (
+                "$argon2id$v=19$m=64,t=3,p=2$c2FsdHNhbHQ$",
+                "hash is empty at position 40",
+            ),
+            (
+                "$argon2id$v=19$m=64,t=3,p=2$c2FsdHNhbHQ$AAECAwQFBgc$",
+                "unexpected trailing characters at position 51",
+            ),
+            (
+                "\t$argon2id$v=19$m=64,t=3,p=2$c2FsdHNhbHQ$AAECAwQFBgc$ ",
+                "unexpected trailing characters at position 52",
+            ),
+        ];
+        for &(hash, expected) in cases.iter() {
+            let message = error(hash);
+            assert!(
+                message.contains(expected),
+                "\nhash: {}\nexpected: {}\nactual: {}",
+                hash,
+                expected,
+                message,
+            );
+        }
+    }
+
+    #[cfg(feature = "backend-c")]
     #[test]
     #[ignore] // TODO: Turn back on once implemented decode_c
     fn test_decode_against_c() {
+        use rand::rngs::StdRng;
+        use rand::{RngCore, SeedableRng};
+
+        use backend::c::decode_c;
+        use hasher::Hasher;
+
         let mut rng: StdRng = SeedableRng::from_seed([0u8; 32]);
         let mut password = vec![0u8; 12];
         let mut secret_key = vec![0u8; 32];
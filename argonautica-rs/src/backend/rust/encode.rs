@@ -6,20 +6,30 @@ impl HashRaw {
     pub(crate) fn encode_rust(&self) -> String {
         let hash_encoded = base64::encode_config(self.raw_hash_bytes(), base64::STANDARD_NO_PAD);
         let salt_encoded = base64::encode_config(self.raw_salt_bytes(), base64::STANDARD_NO_PAD);
+        let mut optional_params = String::new();
+        if let Some(key_id) = self.key_id() {
+            optional_params.push_str(",keyid=");
+            optional_params.push_str(&base64::encode_config(key_id, base64::STANDARD_NO_PAD));
+        }
+        if let Some(data) = self.data() {
+            optional_params.push_str(",data=");
+            optional_params.push_str(&base64::encode_config(data, base64::STANDARD_NO_PAD));
+        }
         format!(
-            "${}$v={}$m={},t={},p={}${}${}",
+            "${}$v={}$m={},t={},p={}{}${}${}",
             self.variant().as_str(),
             self.version().as_str(),
             self.memory_size(),
             self.iterations(),
             self.lanes(),
+            optional_params,
             salt_encoded,
             hash_encoded,
         )
     }
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "backend-c"))]
 mod tests {
     use rand::rngs::StdRng;
     use rand::{RngCore, SeedableRng};
@@ -1,10 +1,94 @@
-#![allow(dead_code)]
+use std::sync::Arc;
 
-use hasher::Hasher;
+use backend::rust::core::{argon2, Context};
+use config::BlockCompression;
 use output::HashRaw;
-use Error;
+use {Error, ErrorKind, Hasher};
 
-pub(crate) fn hash_raw_rust(hasher: &mut Hasher) -> Result<HashRaw, Error> {
-    let _ = hasher;
-    unimplemented!()
+impl<'a> Hasher<'a> {
+    pub(crate) fn hash_raw_rust(&mut self) -> Result<HashRaw, Error> {
+        let ad = match self.additional_data {
+            Some(ref additional_data) => additional_data.as_bytes(),
+            None => &[],
+        };
+        let pwd = match self.password {
+            Some(ref password) => password.as_bytes(),
+            None => return Err(Error::new(ErrorKind::PasswordMissingError)),
+        };
+        let secret = self.secret_key_bytes().unwrap_or(&[]);
+        let memory_allocator = self.config.memory_allocator();
+        let context = Context {
+            ad,
+            block_compression: BlockCompression::detect(),
+            hash_len: self.config.hash_len(),
+            iterations: self.config.iterations(),
+            lanes: self.config.lanes(),
+            memory_allocator: memory_allocator.as_ref().map(Arc::as_ref),
+            memory_size: self.config.memory_size(),
+            pwd,
+            salt: self.salt.as_bytes(),
+            secret,
+            threads: self.config.threads(),
+            variant: self.config.variant(),
+            version: self.config.version(),
+        };
+        let buffer = argon2(&context)?;
+        Ok(HashRaw {
+            data: None,
+            iterations: self.config.iterations(),
+            key_id: None,
+            lanes: self.config.lanes(),
+            memory_size: self.config.memory_size(),
+            raw_hash_bytes: buffer,
+            raw_salt_bytes: self.salt.as_bytes().to_vec(),
+            variant: self.config.variant(),
+            version: self.config.version(),
+        })
+    }
+}
+
+#[cfg(all(test, feature = "backend-c"))]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::{RngCore, SeedableRng};
+
+    use config::{Backend, Variant, Version};
+    use hasher::Hasher;
+
+    #[test]
+    fn test_hash_raw_against_c() {
+        let mut rng: StdRng = SeedableRng::from_seed([0u8; 32]);
+        let mut additional_data = vec![0u8; 16];
+        let mut password = vec![0u8; 12];
+        let mut secret_key = vec![0u8; 32];
+        for variant in &[Variant::Argon2d, Variant::Argon2i, Variant::Argon2id] {
+            for version in &[Version::_0x10, Version::_0x13] {
+                for &(lanes, threads, memory_size) in
+                    &[(1, 1, 8), (1, 1, 64), (4, 2, 64), (3, 3, 256)]
+                {
+                    for hash_len in &[8, 32, 100] {
+                        rng.fill_bytes(&mut additional_data);
+                        rng.fill_bytes(&mut password);
+                        rng.fill_bytes(&mut secret_key);
+                        let mut hasher = Hasher::default();
+                        hasher
+                            .configure_hash_len(*hash_len)
+                            .configure_iterations(2)
+                            .configure_lanes(lanes)
+                            .configure_memory_size(memory_size)
+                            .configure_threads(threads)
+                            .configure_variant(*variant)
+                            .configure_version(*version)
+                            .with_additional_data(&additional_data[..])
+                            .with_password(&password[..])
+                            .with_salt("somesalt")
+                            .with_secret_key(&secret_key[..]);
+                        let hash_raw1 = hasher.configure_backend(Backend::C).hash_raw().unwrap();
+                        let hash_raw2 = hasher.configure_backend(Backend::Rust).hash_raw().unwrap();
+                        assert_eq!(hash_raw1, hash_raw2);
+                    }
+                }
+            }
+        }
+    }
 }
@@ -1,8 +1,6 @@
-#[cfg(feature = "development")]
 mod core;
 mod decode;
 mod encode;
-#[cfg(feature = "development")]
 mod hash_raw;
 
-pub(crate) use self::decode::decode_rust;
+pub(crate) use self::decode::{decode_params_rust, decode_rust, parse_rust};
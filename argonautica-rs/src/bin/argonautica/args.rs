@@ -0,0 +1,90 @@
+use std::time::Duration;
+
+use clap::{Arg, ArgMatches};
+
+/// An option taking a `u32`, which is validated while the command line is parsed
+pub fn u32_arg(name: &'static str, help: &'static str) -> Arg<'static, 'static> {
+    Arg::with_name(name)
+        .long(name)
+        .value_name("N")
+        .validator(|s| {
+            s.parse::<u32>()
+                .map(|_| ())
+                .map_err(|_| format!("{} is not a valid 32-bit unsigned integer", s))
+        })
+        .help(help)
+}
+
+/// The value of an option created with [`u32_arg`](fn.u32_arg.html), if it was passed
+pub fn value_of_u32(matches: &ArgMatches, name: &str) -> Option<u32> {
+    matches.value_of(name).map(|s| s.parse().unwrap())
+}
+
+/// Parses a duration such as `400ms`, `0.5s` or `400` (in milliseconds)
+pub fn parse_duration(s: &str) -> Result<Duration, String> {
+    let error = || format!("{} is not a valid duration, e.g. 400ms or 0.5s", s);
+    let s = s.trim();
+    let split = s
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(s.len());
+    let number = s[..split].parse::<f64>().map_err(|_| error())?;
+    let millis_per_unit = match s[split..].trim() {
+        "" | "ms" => 1.0,
+        "s" => 1_000.0,
+        _ => return Err(error()),
+    };
+    if number <= 0.0 || !number.is_finite() {
+        return Err(error());
+    }
+    Ok(Duration::from_micros(
+        (number * millis_per_unit * 1_000.0).round() as u64,
+    ))
+}
+
+/// Parses a memory size such as `256MiB`, `1GiB` or `4096` (in kibibytes) into kibibytes
+pub fn parse_memory_size(s: &str) -> Result<u32, String> {
+    let error = || format!("{} is not a valid memory size, e.g. 256MiB or 1GiB", s);
+    let s = s.trim();
+    let split = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let number = s[..split].parse::<u64>().map_err(|_| error())?;
+    let kibibytes_per_unit = match s[split..].trim().to_ascii_lowercase().as_str() {
+        "" | "k" | "kib" => 1,
+        "m" | "mib" => 1 << 10,
+        "g" | "gib" => 1 << 20,
+        _ => return Err(error()),
+    };
+    match number.checked_mul(kibibytes_per_unit) {
+        Some(kibibytes) if kibibytes > 0 && kibibytes <= u64::from(u32::MAX) => {
+            Ok(kibibytes as u32)
+        }
+        _ => Err(error()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("400ms"), Ok(Duration::from_millis(400)));
+        assert_eq!(parse_duration("400"), Ok(Duration::from_millis(400)));
+        assert_eq!(parse_duration("0.5s"), Ok(Duration::from_millis(500)));
+        assert_eq!(parse_duration("2s"), Ok(Duration::from_secs(2)));
+        for s in &["", "ms", "-1s", "0", "fast", "1h"] {
+            assert!(parse_duration(s).is_err(), "{}", s);
+        }
+    }
+
+    #[test]
+    fn test_parse_memory_size() {
+        assert_eq!(parse_memory_size("4096"), Ok(4096));
+        assert_eq!(parse_memory_size("64KiB"), Ok(64));
+        assert_eq!(parse_memory_size("256MiB"), Ok(262_144));
+        assert_eq!(parse_memory_size("256m"), Ok(262_144));
+        assert_eq!(parse_memory_size("1GiB"), Ok(1_048_576));
+        for s in &["", "0", "MiB", "1.5GiB", "1TiB", "4096GiB", "-1"] {
+            assert!(parse_memory_size(s).is_err(), "{}", s);
+        }
+    }
+}
@@ -0,0 +1,294 @@
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{self, BufReader};
+
+use argonautica::audit::{AuditReport, Auditor, Column, InputFormat, Issue};
+use argonautica::config::HasherConfig;
+use argonautica::output::RehashReasons;
+use clap::{App, Arg, ArgMatches, SubCommand};
+use failure;
+use serde_json::{self, Map, Value};
+use toml;
+
+use args::{u32_arg, value_of_u32};
+use output::{format_arg, Format};
+use verify::{policy, policy_args};
+use {EXIT_INVALID, EXIT_SUCCESS};
+
+const REASONS: [(RehashReasons, &str); 8] = [
+    (RehashReasons::VARIANT, "variant"),
+    (RehashReasons::VERSION, "version"),
+    (RehashReasons::MEMORY_SIZE, "memory_size"),
+    (RehashReasons::ITERATIONS, "iterations"),
+    (RehashReasons::LANES, "lanes"),
+    (RehashReasons::HASH_LEN, "hash_len"),
+    (RehashReasons::SALT_LEN, "salt_len"),
+    (RehashReasons::KEY_ID, "key_id"),
+];
+
+pub fn subcommand() -> App<'static, 'static> {
+    SubCommand::with_name("audit")
+        .about(
+            "Reports the parameters of stored hashes and flags the ones that do not parse, \
+             exceed a policy or are weaker than a profile",
+        )
+        .arg(Arg::with_name("FILE").help(
+            "File with one hash per line (or CSV with --csv-column), or - to read standard \
+             input [default: -]",
+        ))
+        .arg(
+            Arg::with_name("csv-column")
+                .long("csv-column")
+                .value_name("COLUMN")
+                .help(
+                    "Reads FILE as CSV with the hashes in COLUMN, either a (one-based) number \
+                     or the name of a column in the header",
+                ),
+        )
+        .arg(
+            Arg::with_name("delimiter")
+                .long("delimiter")
+                .value_name("CHAR")
+                .default_value(",")
+                .validator(|s| {
+                    if s.chars().count() == 1 {
+                        Ok(())
+                    } else {
+                        Err("must be a single character".to_string())
+                    }
+                })
+                .help("CSV field delimiter"),
+        )
+        .arg(format_arg())
+        .arg(
+            Arg::with_name("header")
+                .long("header")
+                .requires("csv-column")
+                .help("Skips the header of the CSV (implied if COLUMN is a name)"),
+        )
+        .arg(u32_arg(
+            "max-findings",
+            "Lists at most N flagged hashes, while still counting all of them [default: 10000]",
+        ))
+        .arg(
+            Arg::with_name("profile")
+                .long("profile")
+                .value_name("PATH")
+                .help(
+                    "Flags hashes weaker than the HasherConfig profile at PATH (e.g. the output \
+                     of calibrate), in TOML or JSON",
+                ),
+        )
+        .args(&policy_args())
+}
+
+pub fn run(matches: &ArgMatches) -> Result<i32, failure::Error> {
+    let mut auditor = Auditor::default();
+    auditor
+        .configure_format(input_format(matches))
+        .configure_policy(policy(matches));
+    if let Some(max_findings) = value_of_u32(matches, "max-findings") {
+        auditor.configure_max_findings(max_findings as usize);
+    }
+    if let Some(path) = matches.value_of("profile") {
+        auditor.configure_hasher_config(&read_profile(path)?);
+    }
+    let report = match matches.value_of("FILE") {
+        None | Some("-") => auditor.audit(BufReader::new(io::stdin()))?,
+        Some(path) => {
+            let file =
+                File::open(path).map_err(|e| format_err!("Failed to open {}: {}", path, e))?;
+            auditor.audit(BufReader::new(file))?
+        }
+    };
+    Format::from_matches(matches).print(&to_json(&report), &to_plain(&report));
+    let flagged = report.invalid() + report.policy_violations() + report.weak();
+    Ok(if flagged == 0 {
+        EXIT_SUCCESS
+    } else {
+        EXIT_INVALID
+    })
+}
+
+fn column_is_name(column: &Column) -> bool {
+    match *column {
+        Column::Name(_) => true,
+        Column::Index(_) => false,
+    }
+}
+
+fn distribution<K: ToString>(counts: &BTreeMap<K, u64>) -> Value {
+    let map = counts
+        .iter()
+        .map(|(value, count)| (value.to_string(), json!(count)))
+        .collect::<Map<String, Value>>();
+    Value::Object(map)
+}
+
+fn input_format(matches: &ArgMatches) -> InputFormat {
+    let column = match matches.value_of("csv-column") {
+        Some(column) => column,
+        None => return InputFormat::Lines,
+    };
+    let column = match column.parse::<usize>() {
+        Ok(n) if n > 0 => Column::Index(n - 1),
+        _ => Column::Name(column.to_string()),
+    };
+    InputFormat::Csv {
+        has_header: matches.is_present("header") || column_is_name(&column),
+        column,
+        delimiter: matches
+            .value_of("delimiter")
+            .unwrap()
+            .chars()
+            .next()
+            .unwrap(),
+    }
+}
+
+/// Reads a [`HasherConfig`](../../argonautica/config/struct.HasherConfig.html) profile, as JSON if
+/// `path` ends in `.json` and as TOML otherwise
+fn read_profile(path: &str) -> Result<HasherConfig, failure::Error> {
+    let profile = fs::read_to_string(path)
+        .map_err(|e| format_err!("Failed to read profile {}: {}", path, e))?;
+    let config = if path.ends_with(".json") {
+        serde_json::from_str(&profile)
+            .map_err(|e| format_err!("Invalid profile {}: {}", path, e))?
+    } else {
+        toml::from_str(&profile).map_err(|e| format_err!("Invalid profile {}: {}", path, e))?
+    };
+    Ok(config)
+}
+
+fn reason_names(reasons: RehashReasons) -> Vec<&'static str> {
+    REASONS
+        .iter()
+        .filter(|&&(reason, _)| reasons.contains(reason))
+        .map(|&(_, name)| name)
+        .collect()
+}
+
+fn to_json(report: &AuditReport) -> Value {
+    let variants = report
+        .variants()
+        .iter()
+        .map(|(variant, count)| (variant.as_str(), *count))
+        .collect::<BTreeMap<_, _>>();
+    let versions = report
+        .versions()
+        .iter()
+        .map(|(version, count)| (version.as_str(), *count))
+        .collect::<BTreeMap<_, _>>();
+    let findings = report
+        .findings()
+        .iter()
+        .map(|finding| match *finding.issue() {
+            Issue::Invalid { kind, position } => json!({
+                "line": finding.line(),
+                "issue": "invalid",
+                "error": kind.to_string(),
+                "position": position,
+            }),
+            Issue::PolicyViolation(ref e) => json!({
+                "line": finding.line(),
+                "issue": "policy_violation",
+                "error": e.to_string(),
+            }),
+            Issue::Weak(reasons) => json!({
+                "line": finding.line(),
+                "issue": "weak",
+                "reasons": reason_names(reasons),
+            }),
+        })
+        .collect::<Vec<_>>();
+    json!({
+        "total": report.total(),
+        "invalid": report.invalid(),
+        "policy_violations": report.policy_violations(),
+        "weak": report.weak(),
+        "variants": distribution(&variants),
+        "versions": distribution(&versions),
+        "memory_sizes": distribution(report.memory_sizes()),
+        "iterations": distribution(report.iterations()),
+        "lanes": distribution(report.lanes()),
+        "salt_lens": distribution(report.salt_lens()),
+        "hash_lens": distribution(report.hash_lens()),
+        "findings": findings,
+        "findings_truncated": report.findings_truncated(),
+    })
+}
+
+fn to_plain(report: &AuditReport) -> String {
+    fn counts<I, V: ToString>(counts: I) -> String
+    where
+        I: IntoIterator<Item = (V, u64)>,
+    {
+        counts
+            .into_iter()
+            .map(|(value, count)| format!("{}={}", value.to_string(), count))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+    let mut plain = format!(
+        "total: {}\ninvalid: {}\npolicy_violations: {}\nweak: {}\n",
+        report.total(),
+        report.invalid(),
+        report.policy_violations(),
+        report.weak(),
+    );
+    plain.push_str(&format!(
+        "variants: {}\nversions: {}\nmemory_sizes: {}\niterations: {}\nlanes: {}\n\
+         salt_lens: {}\nhash_lens: {}",
+        counts(report.variants().iter().map(|(v, c)| (v.as_str(), *c))),
+        counts(report.versions().iter().map(|(v, c)| (v.as_str(), *c))),
+        counts(report.memory_sizes().iter().map(|(v, c)| (*v, *c))),
+        counts(report.iterations().iter().map(|(v, c)| (*v, *c))),
+        counts(report.lanes().iter().map(|(v, c)| (*v, *c))),
+        counts(report.salt_lens().iter().map(|(v, c)| (*v, *c))),
+        counts(report.hash_lens().iter().map(|(v, c)| (*v, *c))),
+    ));
+    for finding in report.findings() {
+        let issue = match *finding.issue() {
+            Issue::Invalid {
+                kind,
+                position: Some(position),
+            } => format!("invalid: {} at position {}", kind, position),
+            Issue::Invalid {
+                kind,
+                position: None,
+            } => format!("invalid: {} (no such column)", kind),
+            Issue::PolicyViolation(ref e) => format!("policy violation: {}", e),
+            Issue::Weak(reasons) => format!("weak: {}", reason_names(reasons).join(", ")),
+        };
+        plain.push_str(&format!("\nline {}: {}", finding.line(), issue));
+    }
+    if report.findings_truncated() {
+        plain.push_str("\n(more flagged hashes not listed)");
+    }
+    plain
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_invalid_input_is_not_printed() {
+        let input = "alice,hunter2plaintext\nbob,$argon2x$hunter2\n";
+        let mut auditor = Auditor::default();
+        auditor.configure_format(InputFormat::Csv {
+            column: Column::Index(1),
+            delimiter: ',',
+            has_header: false,
+        });
+        let report = auditor.audit(input.as_bytes()).unwrap();
+        assert_eq!(report.invalid(), 2);
+        let plain = to_plain(&report);
+        assert!(plain.contains("line 2: invalid: "));
+        assert!(plain.contains(" at position 1"));
+        assert!(!plain.contains("hunter2"));
+        let json = serde_json::to_string(&to_json(&report)).unwrap();
+        assert!(json.contains(r#""position":1"#));
+        assert!(!json.contains("hunter2"));
+    }
+}
@@ -0,0 +1,154 @@
+use std::fs;
+use std::time::{Duration, Instant};
+
+use argonautica::config::HasherConfig;
+use argonautica::Hasher;
+use clap::{App, Arg, ArgMatches, SubCommand};
+use failure;
+use serde_json;
+use toml;
+
+use args::{parse_duration, parse_memory_size, u32_arg, value_of_u32};
+use hash::{hasher, parameter_args};
+use EXIT_SUCCESS;
+
+const DEFAULT_SAMPLES: u32 = 20;
+
+/// The calibrated parameters are reported as off target if the median hashing time is not
+/// within this fraction of the target (either way)
+const TOLERANCE: f64 = 0.1;
+
+const PASSWORD: &str = "P@ssw0rd";
+
+pub fn subcommand() -> App<'static, 'static> {
+    SubCommand::with_name("calibrate")
+        .about(
+            "Tunes iterations and memory size to this machine and prints the resulting \
+             HasherConfig profile",
+        )
+        .arg(
+            Arg::with_name("format")
+                .long("format")
+                .value_name("FORMAT")
+                .possible_values(&["json", "toml"])
+                .default_value("toml")
+                .help("Profile format"),
+        )
+        .arg(
+            Arg::with_name("max-memory")
+                .long("max-memory")
+                .value_name("SIZE")
+                .default_value("1GiB")
+                .validator(|s| parse_memory_size(&s).map(|_| ()))
+                .help("Largest memory size to use, e.g. 256MiB (in kibibytes without a unit)"),
+        )
+        .arg(
+            Arg::with_name("output")
+                .long("output")
+                .short("o")
+                .value_name("PATH")
+                .help("Writes the profile to PATH instead of standard output"),
+        )
+        .arg(u32_arg(
+            "samples",
+            "Number of hashes timed with the calibrated parameters [default: 20]",
+        ))
+        .arg(
+            Arg::with_name("target")
+                .long("target")
+                .value_name("DURATION")
+                .default_value("400ms")
+                .validator(|s| parse_duration(&s).map(|_| ()))
+                .help("Target hashing time, e.g. 400ms or 0.5s (in milliseconds without a unit)"),
+        )
+        .args(&parameter_args())
+}
+
+pub fn run(matches: &ArgMatches) -> Result<i32, failure::Error> {
+    let target = parse_duration(matches.value_of("target").unwrap()).unwrap();
+    let max_memory_size = parse_memory_size(matches.value_of("max-memory").unwrap()).unwrap();
+    let samples = value_of_u32(matches, "samples").unwrap_or(DEFAULT_SAMPLES);
+    if samples < 2 {
+        bail!("At least 2 samples are needed");
+    }
+
+    let calibration = hasher(matches)?.calibrate(target, max_memory_size)?;
+    let config = calibration.config();
+    let stats = Stats::measure(config, samples)?;
+    let target_millis = millis(target);
+    eprintln!(
+        "lanes: {}, threads: {}, memory_size: {}, iterations: {}",
+        config.lanes(),
+        config.threads(),
+        config.memory_size(),
+        config.iterations(),
+    );
+    eprintln!(
+        "{} samples: median {:.1} ms, mean {:.1} ms, standard deviation {:.1} ms, \
+         min {:.1} ms, max {:.1} ms",
+        samples, stats.median, stats.mean, stats.std_dev, stats.min, stats.max,
+    );
+    if (stats.median - target_millis).abs() > target_millis * TOLERANCE {
+        eprintln!(
+            "warning: could not get within {}% of the {:.0} ms target on this machine",
+            TOLERANCE * 100.0,
+            target_millis,
+        );
+    }
+
+    let profile = match matches.value_of("format") {
+        Some("json") => serde_json::to_string_pretty(config)? + "\n",
+        _ => toml::to_string(config)?,
+    };
+    match matches.value_of("output") {
+        Some(path) => fs::write(path, profile)
+            .map_err(|e| format_err!("Failed to write profile to {}: {}", path, e))?,
+        None => print!("{}", profile),
+    }
+    Ok(EXIT_SUCCESS)
+}
+
+/// Statistics of hashing times with a calibrated configuration, in milliseconds
+struct Stats {
+    max: f64,
+    mean: f64,
+    median: f64,
+    min: f64,
+    std_dev: f64,
+}
+
+impl Stats {
+    fn measure(config: &HasherConfig, samples: u32) -> Result<Stats, failure::Error> {
+        let mut hasher = Hasher::default();
+        hasher
+            .configure_from(config)
+            .configure_password_clearing(false)
+            .opt_out_of_secret_key(true);
+        let mut times = Vec::with_capacity(samples as usize);
+        for _ in 0..samples {
+            let now = Instant::now();
+            hasher.with_password(PASSWORD).hash_raw()?;
+            times.push(millis(now.elapsed()));
+        }
+        times.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        let n = times.len();
+        let mean = times.iter().sum::<f64>() / n as f64;
+        let variance = times.iter().map(|t| (t - mean) * (t - mean)).sum::<f64>() / (n - 1) as f64;
+        let median = if n % 2 == 0 {
+            (times[n / 2 - 1] + times[n / 2]) / 2.0
+        } else {
+            times[n / 2]
+        };
+        Ok(Stats {
+            max: times[n - 1],
+            mean,
+            median,
+            min: times[0],
+            std_dev: variance.sqrt(),
+        })
+    }
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs() as f64 * 1_000.0 + f64::from(duration.subsec_nanos()) / 1_000_000.0
+}
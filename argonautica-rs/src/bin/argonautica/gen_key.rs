@@ -0,0 +1,29 @@
+use argonautica::utils;
+use clap::{App, ArgMatches, SubCommand};
+use failure;
+
+use args::{u32_arg, value_of_u32};
+use output::{format_arg, Format};
+use EXIT_SUCCESS;
+
+const DEFAULT_LEN: u32 = 32;
+
+pub fn subcommand() -> App<'static, 'static> {
+    SubCommand::with_name("gen-key")
+        .about("Generates a random, base64-encoded secret key")
+        .arg(format_arg())
+        .arg(u32_arg(
+            "len",
+            "Length of the secret key in bytes [default: 32]",
+        ))
+}
+
+pub fn run(matches: &ArgMatches) -> Result<i32, failure::Error> {
+    let len = value_of_u32(matches, "len").unwrap_or(DEFAULT_LEN);
+    if len == 0 {
+        bail!("The secret key must be at least one byte long");
+    }
+    let secret_key = utils::generate_random_base64_encoded_string(len)?;
+    Format::from_matches(matches).print(&json!({ "secret_key": secret_key }), &secret_key);
+    Ok(EXIT_SUCCESS)
+}
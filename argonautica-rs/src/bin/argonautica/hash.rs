@@ -0,0 +1,121 @@
+use argonautica::config::{Backend, Variant, Version};
+use argonautica::input::Salt;
+use argonautica::Hasher;
+use clap::{App, Arg, ArgMatches, SubCommand};
+use failure;
+
+use args::{u32_arg, value_of_u32};
+use input::{password_args, read_password, read_secret_key, secret_key_args};
+use output::{format_arg, Format};
+use EXIT_SUCCESS;
+
+pub fn subcommand() -> App<'static, 'static> {
+    SubCommand::with_name("hash")
+        .about("Hashes a password")
+        .arg(format_arg())
+        .args(&password_args())
+        .args(&secret_key_args())
+        .args(&hasher_args())
+        .arg(
+            Arg::with_name("no-secret-key")
+                .long("no-secret-key")
+                .conflicts_with_all(&["secret-key-env", "secret-key-file"])
+                .help("Hashes without a secret key"),
+        )
+}
+
+pub fn run(matches: &ArgMatches) -> Result<i32, failure::Error> {
+    let mut hasher = hasher(matches)?;
+    hasher.opt_out_of_secret_key(matches.is_present("no-secret-key"));
+    if let Some(secret_key) = read_secret_key(matches)? {
+        hasher.with_secret_key(secret_key);
+    }
+    let password = read_password(matches, true)?;
+    let hash = hasher.with_password(password).hash()?;
+    Format::from_matches(matches).print(&json!({ "hash": hash }), &hash);
+    Ok(EXIT_SUCCESS)
+}
+
+/// Options for every [`Hasher`](../../argonautica/struct.Hasher.html) configuration option
+/// that can be given on the command line. Options that are not passed keep the library defaults
+pub fn hasher_args() -> Vec<Arg<'static, 'static>> {
+    let mut args = vec![
+        Arg::with_name("additional-data")
+            .long("additional-data")
+            .value_name("DATA")
+            .help("Additional data to hash with the password"),
+        u32_arg("iterations", "Number of iterations"),
+        u32_arg("memory-size", "Memory size in kibibytes"),
+        u32_arg("salt-len", "Length of the random salt in bytes"),
+    ];
+    args.extend(parameter_args());
+    args
+}
+
+/// The subset of [`hasher_args`](fn.hasher_args.html) that calibration does not tune
+pub fn parameter_args() -> Vec<Arg<'static, 'static>> {
+    vec![
+        Arg::with_name("backend")
+            .long("backend")
+            .value_name("BACKEND")
+            .possible_values(&["c", "rust"])
+            .help("Argon2 implementation [default: c if available, otherwise rust]"),
+        u32_arg("hash-len", "Length of the hash in bytes"),
+        u32_arg("lanes", "Number of lanes"),
+        u32_arg("threads", "Number of threads"),
+        Arg::with_name("variant")
+            .long("variant")
+            .value_name("VARIANT")
+            .possible_values(&["argon2d", "argon2i", "argon2id"])
+            .help("Argon2 variant"),
+        Arg::with_name("version")
+            .long("version")
+            .value_name("VERSION")
+            .possible_values(&["16", "19"])
+            .help("Argon2 version"),
+    ]
+}
+
+/// Creates a [`Hasher`](../../argonautica/struct.Hasher.html) configured with the options
+/// created by [`hasher_args`](fn.hasher_args.html) (or a subset of them)
+pub fn hasher(matches: &ArgMatches) -> Result<Hasher<'static>, failure::Error> {
+    let mut hasher = Hasher::default();
+    if let Some(additional_data) = matches.value_of("additional-data") {
+        hasher.with_additional_data(additional_data.to_string());
+    }
+    if let Some(backend) = matches.value_of("backend") {
+        hasher.configure_backend(parse_backend(backend));
+    }
+    if let Some(hash_len) = value_of_u32(matches, "hash-len") {
+        hasher.configure_hash_len(hash_len);
+    }
+    if let Some(iterations) = value_of_u32(matches, "iterations") {
+        hasher.configure_iterations(iterations);
+    }
+    if let Some(lanes) = value_of_u32(matches, "lanes") {
+        hasher.configure_lanes(lanes);
+    }
+    if let Some(memory_size) = value_of_u32(matches, "memory-size") {
+        hasher.configure_memory_size(memory_size);
+    }
+    if let Some(salt_len) = value_of_u32(matches, "salt-len") {
+        hasher.with_salt(Salt::random(salt_len));
+    }
+    if let Some(threads) = value_of_u32(matches, "threads") {
+        hasher.configure_threads(threads);
+    }
+    if let Some(variant) = matches.value_of("variant") {
+        hasher.configure_variant(variant.parse::<Variant>()?);
+    }
+    if let Some(version) = matches.value_of("version") {
+        hasher.configure_version(version.parse::<Version>()?);
+    }
+    Ok(hasher)
+}
+
+pub fn parse_backend(backend: &str) -> Backend {
+    match backend {
+        "c" => Backend::C,
+        _ => Backend::Rust,
+    }
+}
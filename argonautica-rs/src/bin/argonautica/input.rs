@@ -0,0 +1,95 @@
+use std::env;
+use std::fs;
+use std::io::{self, BufRead};
+
+use argonautica::input::{Password, SecretKey};
+use clap::{Arg, ArgMatches};
+use failure;
+use rpassword;
+
+pub fn hash_arg() -> Arg<'static, 'static> {
+    Arg::with_name("HASH")
+        .required(true)
+        .help("The encoded hash, or - to read it from the first line of standard input")
+}
+
+pub fn password_args() -> Vec<Arg<'static, 'static>> {
+    vec![Arg::with_name("password-stdin")
+        .long("password-stdin")
+        .help("Reads the password from the first line of standard input instead of prompting")]
+}
+
+pub fn secret_key_args() -> Vec<Arg<'static, 'static>> {
+    vec![
+        Arg::with_name("secret-key-env")
+            .long("secret-key-env")
+            .value_name("VAR")
+            .conflicts_with("secret-key-file")
+            .help("Reads the base64-encoded secret key from the environment variable VAR"),
+        Arg::with_name("secret-key-file")
+            .long("secret-key-file")
+            .value_name("PATH")
+            .help("Reads the base64-encoded secret key (e.g. the output of gen-key) from PATH"),
+    ]
+}
+
+/// Reads the hash from the `HASH` argument, or from standard input if it is `-`
+pub fn read_hash(matches: &ArgMatches) -> Result<String, failure::Error> {
+    match matches.value_of("HASH").unwrap() {
+        "-" => {
+            if matches.is_present("password-stdin") {
+                bail!("The hash and the password cannot both be read from standard input");
+            }
+            Ok(read_line()?.trim().to_string())
+        }
+        hash => Ok(hash.to_string()),
+    }
+}
+
+/// Reads the password from standard input if `--password-stdin` was passed; otherwise prompts
+/// for it on the terminal without echoing it (twice if `confirm` is true)
+pub fn read_password(
+    matches: &ArgMatches,
+    confirm: bool,
+) -> Result<Password<'static>, failure::Error> {
+    if matches.is_present("password-stdin") {
+        return Ok(Password::from(read_line()?));
+    }
+    let password = Password::from(rpassword::read_password_from_tty(Some("Password: "))?);
+    if confirm {
+        let confirmation = Password::from(rpassword::read_password_from_tty(Some(
+            "Confirm password: ",
+        ))?);
+        if confirmation != password {
+            bail!("Passwords do not match");
+        }
+    }
+    Ok(password)
+}
+
+/// Reads the secret key from the file or environment variable given on the command line, if any
+pub fn read_secret_key(matches: &ArgMatches) -> Result<Option<SecretKey<'static>>, failure::Error> {
+    let encoded = if let Some(path) = matches.value_of("secret-key-file") {
+        fs::read_to_string(path)
+            .map_err(|e| format_err!("Failed to read secret key file {}: {}", path, e))?
+    } else if let Some(var) = matches.value_of("secret-key-env") {
+        env::var(var).map_err(|e| format_err!("Failed to read secret key from ${}: {}", var, e))?
+    } else {
+        return Ok(None);
+    };
+    Ok(Some(SecretKey::from_base64_encoded(encoded.trim())?))
+}
+
+/// Reads the first line of standard input, without its line ending
+fn read_line() -> Result<String, failure::Error> {
+    let stdin = io::stdin();
+    let mut line = String::new();
+    stdin.lock().read_line(&mut line)?;
+    if line.ends_with('\n') {
+        line.pop();
+        if line.ends_with('\r') {
+            line.pop();
+        }
+    }
+    Ok(line)
+}
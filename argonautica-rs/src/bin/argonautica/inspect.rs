@@ -0,0 +1,57 @@
+use argonautica::output::HashRaw;
+use base64;
+use clap::{App, ArgMatches, SubCommand};
+use failure;
+
+use input::{hash_arg, read_hash};
+use output::{format_arg, Format};
+use EXIT_SUCCESS;
+
+pub fn subcommand() -> App<'static, 'static> {
+    SubCommand::with_name("inspect")
+        .about("Prints the parameters of a hash")
+        .arg(hash_arg())
+        .arg(format_arg())
+}
+
+pub fn run(matches: &ArgMatches) -> Result<i32, failure::Error> {
+    let hash_raw = read_hash(matches)?.parse::<HashRaw>()?;
+    let encode = |bytes: &[u8]| base64::encode_config(bytes, base64::STANDARD_NO_PAD);
+    let key_id = hash_raw.key_id().map(&encode);
+    let data = hash_raw.data().map(&encode);
+    let salt = encode(hash_raw.raw_salt_bytes());
+    let json = json!({
+        "variant": hash_raw.variant().as_str(),
+        "version": hash_raw.version().as_str(),
+        "memory_size": hash_raw.memory_size(),
+        "iterations": hash_raw.iterations(),
+        "lanes": hash_raw.lanes(),
+        "key_id": key_id,
+        "data": data,
+        "salt": salt,
+        "salt_len": hash_raw.raw_salt_bytes().len(),
+        "hash_len": hash_raw.raw_hash_bytes().len(),
+    });
+    let mut plain = format!(
+        "variant: {}\nversion: {}\nmemory_size: {}\niterations: {}\nlanes: {}\n",
+        hash_raw.variant().as_str(),
+        hash_raw.version().as_str(),
+        hash_raw.memory_size(),
+        hash_raw.iterations(),
+        hash_raw.lanes(),
+    );
+    if let Some(key_id) = key_id {
+        plain.push_str(&format!("key_id: {}\n", key_id));
+    }
+    if let Some(data) = data {
+        plain.push_str(&format!("data: {}\n", data));
+    }
+    plain.push_str(&format!(
+        "salt: {}\nsalt_len: {}\nhash_len: {}",
+        salt,
+        hash_raw.raw_salt_bytes().len(),
+        hash_raw.raw_hash_bytes().len(),
+    ));
+    Format::from_matches(matches).print(&json, &plain);
+    Ok(EXIT_SUCCESS)
+}
@@ -0,0 +1,94 @@
+//! The `argonautica` command-line tool. Requires the `cli` feature.
+//!
+//! ```text
+//! argonautica audit --csv-column hash --max-memory-size 1048576 users.csv
+//! argonautica calibrate --target 400ms --max-memory 256MiB > profile.toml
+//! argonautica gen-key > secret_key
+//! argonautica hash --secret-key-file secret_key
+//! argonautica verify --secret-key-file secret_key '$argon2id$v=19$m=4096,t=192,p=4$...'
+//! argonautica inspect --format json '$argon2id$v=19$m=4096,t=192,p=4$...'
+//! ```
+extern crate argonautica;
+extern crate base64;
+#[macro_use]
+extern crate clap;
+#[macro_use]
+extern crate failure;
+extern crate rpassword;
+#[macro_use]
+extern crate serde_json;
+extern crate toml;
+
+mod args;
+mod audit;
+mod calibrate;
+mod gen_key;
+mod hash;
+mod input;
+mod inspect;
+mod output;
+mod verify;
+
+use std::process;
+
+use clap::{App, AppSettings, ErrorKind};
+
+/// The password matched (`verify`) or the command succeeded (everything else)
+pub const EXIT_SUCCESS: i32 = 0;
+/// The password did not match (`verify`)
+pub const EXIT_INVALID: i32 = 1;
+/// Invalid arguments or input, or any other error
+pub const EXIT_ERROR: i32 = 2;
+
+const AFTER_HELP: &str = "\
+EXIT STATUS:
+    0    Success (for verify: the password matches the hash; for audit: no hash was flagged)
+    1    The password does not match the hash (verify), or some hashes were flagged (audit)
+    2    Invalid arguments or input, or any other error";
+
+fn app() -> App<'static, 'static> {
+    App::new("argonautica")
+        .version(crate_version!())
+        .about("Hashes and verifies passwords with Argon2")
+        .after_help(AFTER_HELP)
+        .setting(AppSettings::SubcommandRequiredElseHelp)
+        .setting(AppSettings::VersionlessSubcommands)
+        .subcommand(audit::subcommand())
+        .subcommand(calibrate::subcommand())
+        .subcommand(gen_key::subcommand())
+        .subcommand(hash::subcommand())
+        .subcommand(inspect::subcommand())
+        .subcommand(verify::subcommand())
+}
+
+fn main() {
+    let matches = match app().get_matches_safe() {
+        Ok(matches) => matches,
+        Err(e) => match e.kind {
+            ErrorKind::HelpDisplayed | ErrorKind::VersionDisplayed => {
+                println!("{}", e.message);
+                process::exit(EXIT_SUCCESS);
+            }
+            _ => {
+                eprintln!("{}", e.message);
+                process::exit(EXIT_ERROR);
+            }
+        },
+    };
+    let result = match matches.subcommand() {
+        ("audit", Some(matches)) => audit::run(matches),
+        ("calibrate", Some(matches)) => calibrate::run(matches),
+        ("gen-key", Some(matches)) => gen_key::run(matches),
+        ("hash", Some(matches)) => hash::run(matches),
+        ("inspect", Some(matches)) => inspect::run(matches),
+        ("verify", Some(matches)) => verify::run(matches),
+        _ => unreachable!(),
+    };
+    match result {
+        Ok(code) => process::exit(code),
+        Err(e) => {
+            eprintln!("error: {}", e);
+            process::exit(EXIT_ERROR);
+        }
+    }
+}
@@ -0,0 +1,34 @@
+use clap::{Arg, ArgMatches};
+use serde_json::{self, Value};
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Format {
+    Json,
+    Plain,
+}
+
+impl Format {
+    pub fn from_matches(matches: &ArgMatches) -> Format {
+        match matches.value_of("format") {
+            Some("json") => Format::Json,
+            _ => Format::Plain,
+        }
+    }
+
+    /// Prints `json` as a single line if the format is JSON; otherwise prints `plain`
+    pub fn print(self, json: &Value, plain: &str) {
+        match self {
+            Format::Json => println!("{}", serde_json::to_string(json).unwrap()),
+            Format::Plain => println!("{}", plain),
+        }
+    }
+}
+
+pub fn format_arg() -> Arg<'static, 'static> {
+    Arg::with_name("format")
+        .long("format")
+        .value_name("FORMAT")
+        .possible_values(&["json", "plain"])
+        .default_value("plain")
+        .help("Output format")
+}
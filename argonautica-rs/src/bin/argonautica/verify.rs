@@ -0,0 +1,100 @@
+use argonautica::config::VerifierPolicy;
+use argonautica::Verifier;
+use clap::{App, Arg, ArgMatches, SubCommand};
+use failure;
+
+use args::{u32_arg, value_of_u32};
+use hash::parse_backend;
+use input::{hash_arg, password_args, read_hash, read_password, read_secret_key, secret_key_args};
+use output::{format_arg, Format};
+use {EXIT_INVALID, EXIT_SUCCESS};
+
+pub fn subcommand() -> App<'static, 'static> {
+    SubCommand::with_name("verify")
+        .about("Verifies a password against a hash")
+        .arg(hash_arg())
+        .arg(format_arg())
+        .args(&password_args())
+        .args(&secret_key_args())
+        .arg(
+            Arg::with_name("additional-data")
+                .long("additional-data")
+                .value_name("DATA")
+                .help("Additional data the password was hashed with"),
+        )
+        .arg(
+            Arg::with_name("backend")
+                .long("backend")
+                .value_name("BACKEND")
+                .possible_values(&["c", "rust"])
+                .help("Argon2 implementation [default: c if available, otherwise rust]"),
+        )
+        .arg(u32_arg("threads", "Number of threads"))
+        .args(&policy_args())
+}
+
+pub fn run(matches: &ArgMatches) -> Result<i32, failure::Error> {
+    let hash = read_hash(matches)?;
+    let mut verifier = Verifier::default();
+    verifier.configure_policy(policy(matches)).with_hash(hash);
+    if let Some(additional_data) = matches.value_of("additional-data") {
+        verifier.with_additional_data(additional_data.to_string());
+    }
+    if let Some(backend) = matches.value_of("backend") {
+        verifier.configure_backend(parse_backend(backend));
+    }
+    if let Some(threads) = value_of_u32(matches, "threads") {
+        verifier.configure_threads(threads);
+    }
+    if let Some(secret_key) = read_secret_key(matches)? {
+        verifier.with_secret_key(secret_key);
+    }
+    let password = read_password(matches, false)?;
+    let is_valid = verifier.with_password(password).verify()?;
+    Format::from_matches(matches).print(
+        &json!({ "valid": is_valid }),
+        if is_valid { "valid" } else { "invalid" },
+    );
+    Ok(if is_valid { EXIT_SUCCESS } else { EXIT_INVALID })
+}
+
+pub fn policy_args() -> Vec<Arg<'static, 'static>> {
+    vec![
+        u32_arg("max-hash-len", "Rejects hashes longer than N bytes"),
+        u32_arg(
+            "max-iterations",
+            "Rejects hashes with more than N iterations",
+        ),
+        u32_arg("max-lanes", "Rejects hashes with more than N lanes"),
+        u32_arg(
+            "max-memory-size",
+            "Rejects hashes using more than N kibibytes",
+        ),
+        u32_arg(
+            "max-salt-len",
+            "Rejects hashes with salts longer than N bytes",
+        ),
+    ]
+}
+
+/// The default [`VerifierPolicy`](../../argonautica/config/struct.VerifierPolicy.html) with the
+/// limits given on the command line
+pub fn policy(matches: &ArgMatches) -> VerifierPolicy {
+    let mut policy = VerifierPolicy::default();
+    if let Some(max_hash_len) = value_of_u32(matches, "max-hash-len") {
+        policy.configure_max_hash_len(max_hash_len);
+    }
+    if let Some(max_iterations) = value_of_u32(matches, "max-iterations") {
+        policy.configure_max_iterations(max_iterations);
+    }
+    if let Some(max_lanes) = value_of_u32(matches, "max-lanes") {
+        policy.configure_max_lanes(max_lanes);
+    }
+    if let Some(max_memory_size) = value_of_u32(matches, "max-memory-size") {
+        policy.configure_max_memory_size(max_memory_size);
+    }
+    if let Some(max_salt_len) = value_of_u32(matches, "max-salt-len") {
+        policy.configure_max_salt_len(max_salt_len);
+    }
+    policy
+}
@@ -0,0 +1,378 @@
+//! A shared limit on the memory used by concurrent hashes (see
+//! [`MemoryBudget`](struct.MemoryBudget.html)).
+//!
+//! Every hash allocates `memory_size` kibibytes for as long as it runs; so a burst of logins
+//! verified in parallel can use far more memory than a single hash suggests. A
+//! [`MemoryBudget`](struct.MemoryBudget.html) attached to [`Hasher`](../struct.Hasher.html)s
+//! and [`Verifier`](../struct.Verifier.html)s (see
+//! [`Hasher::configure_memory_budget`](../struct.Hasher.html#method.configure_memory_budget)
+//! and [`Verifier::configure_memory_budget`](../struct.Verifier.html#method.configure_memory_budget))
+//! admits a hash only once its memory fits within the budget, which makes the peak memory
+//! used by hashing predictable
+use std::collections::VecDeque;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use {Error, ErrorKind};
+
+/// What a [`MemoryBudget`](struct.MemoryBudget.html) does with a hash whose memory does not
+/// currently fit within the budget
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum Admission {
+    /// Wait (in first-come, first-served order) until enough memory has been released
+    Queue,
+    /// Wait (in first-come, first-served order) until enough memory has been released, but
+    /// give up and return an [`Error`](../struct.Error.html) after the given duration
+    QueueWithTimeout(Duration),
+    /// Return an [`Error`](../struct.Error.html) immediately
+    FailFast,
+}
+
+impl Default for Admission {
+    /// [`Admission::Queue`](enum.Admission.html#variant.Queue)
+    fn default() -> Admission {
+        Admission::Queue
+    }
+}
+
+/// A limit on the total memory (in kibibytes) that the hashes of all the
+/// [`Hasher`](../struct.Hasher.html)s and [`Verifier`](../struct.Verifier.html)s attached to it
+/// may use at the same time.
+///
+/// Before hashing, each hash reserves its memory size from the budget and it releases the
+/// reservation once it is done. A hash that does not fit is queued or rejected, depending
+/// on the budget's [`Admission`](enum.Admission.html) policy; a hash that is larger than the
+/// whole budget is always rejected. Rejected hashes return an [`Error`](../struct.Error.html)
+/// of kind [`MemoryBudgetExceededError`](../enum.ErrorKind.html#variant.MemoryBudgetExceededError).
+///
+/// [`MemoryBudget`](struct.MemoryBudget.html) is cheap to clone; clones share the same budget.
+/// Note that queued hashes block the thread they run on, i.e. the calling thread for the
+/// blocking methods and a thread of the executor for the non-blocking and async methods
+#[derive(Clone, Debug)]
+pub struct MemoryBudget {
+    inner: Arc<Inner>,
+}
+
+#[derive(Debug)]
+struct Inner {
+    admission: Admission,
+    condvar: Condvar,
+    state: Mutex<State>,
+    total_memory_size: u64,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    admitted: u64,
+    memory_in_use: u64,
+    next_ticket: u64,
+    queue: VecDeque<u64>,
+    rejected: u64,
+    running: usize,
+}
+
+impl MemoryBudget {
+    /// Creates a new [`MemoryBudget`](struct.MemoryBudget.html) of `total_memory_size`
+    /// kibibytes that queues hashes that do not currently fit (see
+    /// [`Admission::Queue`](enum.Admission.html#variant.Queue))
+    pub fn new(total_memory_size: u64) -> MemoryBudget {
+        MemoryBudget::with_admission(total_memory_size, Admission::default())
+    }
+    /// Creates a new [`MemoryBudget`](struct.MemoryBudget.html) of `total_memory_size`
+    /// kibibytes with a custom [`Admission`](enum.Admission.html) policy
+    pub fn with_admission(total_memory_size: u64, admission: Admission) -> MemoryBudget {
+        MemoryBudget {
+            inner: Arc::new(Inner {
+                admission,
+                condvar: Condvar::new(),
+                state: Mutex::new(State::default()),
+                total_memory_size,
+            }),
+        }
+    }
+    /// The [`Admission`](enum.Admission.html) policy of the
+    /// [`MemoryBudget`](struct.MemoryBudget.html)
+    pub fn admission(&self) -> Admission {
+        self.inner.admission
+    }
+    /// A snapshot of the current state of the [`MemoryBudget`](struct.MemoryBudget.html)
+    pub fn metrics(&self) -> MemoryBudgetMetrics {
+        let state = self.lock();
+        MemoryBudgetMetrics {
+            admitted: state.admitted,
+            memory_in_use: state.memory_in_use,
+            rejected: state.rejected,
+            running: state.running,
+            total_memory_size: self.inner.total_memory_size,
+            waiting: state.queue.len(),
+        }
+    }
+    /// The total memory size (in kibibytes) of the [`MemoryBudget`](struct.MemoryBudget.html)
+    pub fn total_memory_size(&self) -> u64 {
+        self.inner.total_memory_size
+    }
+}
+
+impl MemoryBudget {
+    /// Reserves `memory_size` kibibytes, waiting for them according to the admission policy.
+    /// The reservation is released when the returned guard is dropped
+    pub(crate) fn reserve(&self, memory_size: u32) -> Result<Reservation, Error> {
+        let memory_size = u64::from(memory_size);
+        let total_memory_size = self.inner.total_memory_size;
+        let mut state = self.lock();
+        if memory_size > total_memory_size {
+            state.rejected += 1;
+            return Err(
+                Error::new(ErrorKind::MemoryBudgetExceededError).add_context(format!(
+                    "Memory size: {}. Total memory size of the budget: {}",
+                    memory_size, total_memory_size,
+                )),
+            );
+        }
+        let fits = |state: &State| state.memory_in_use + memory_size <= total_memory_size;
+        if state.queue.is_empty() && fits(&state) {
+            return Ok(self.admit(&mut state, memory_size));
+        }
+        let deadline = match self.inner.admission {
+            Admission::Queue => None,
+            Admission::QueueWithTimeout(timeout) => Some(Instant::now() + timeout),
+            Admission::FailFast => {
+                state.rejected += 1;
+                return Err(self.exceeded_error(&state, memory_size));
+            }
+        };
+        let ticket = state.next_ticket;
+        state.next_ticket += 1;
+        state.queue.push_back(ticket);
+        loop {
+            if state.queue.front() == Some(&ticket) && fits(&state) {
+                state.queue.pop_front();
+                // The next caller in line may fit as well
+                self.inner.condvar.notify_all();
+                return Ok(self.admit(&mut state, memory_size));
+            }
+            state = match deadline {
+                None => self
+                    .inner
+                    .condvar
+                    .wait(state)
+                    .unwrap_or_else(|e| e.into_inner()),
+                Some(deadline) => {
+                    let now = Instant::now();
+                    if now >= deadline {
+                        state.queue.retain(|&t| t != ticket);
+                        state.rejected += 1;
+                        self.inner.condvar.notify_all();
+                        return Err(self.exceeded_error(&state, memory_size));
+                    }
+                    self.inner
+                        .condvar
+                        .wait_timeout(state, deadline - now)
+                        .unwrap_or_else(|e| e.into_inner())
+                        .0
+                }
+            };
+        }
+    }
+    fn admit(&self, state: &mut State, memory_size: u64) -> Reservation {
+        state.admitted += 1;
+        state.memory_in_use += memory_size;
+        state.running += 1;
+        Reservation {
+            budget: self.clone(),
+            memory_size,
+        }
+    }
+    fn exceeded_error(&self, state: &State, memory_size: u64) -> Error {
+        Error::new(ErrorKind::MemoryBudgetExceededError).add_context(format!(
+            "Memory size: {}. Memory in use: {}. Total memory size of the budget: {}",
+            memory_size, state.memory_in_use, self.inner.total_memory_size,
+        ))
+    }
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.inner.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Memory reserved from a [`MemoryBudget`](struct.MemoryBudget.html), released on drop
+#[derive(Debug)]
+pub(crate) struct Reservation {
+    budget: MemoryBudget,
+    memory_size: u64,
+}
+
+impl Drop for Reservation {
+    fn drop(&mut self) {
+        let mut state = self.budget.lock();
+        state.memory_in_use -= self.memory_size;
+        state.running -= 1;
+        self.budget.inner.condvar.notify_all();
+    }
+}
+
+/// A snapshot of the state of a [`MemoryBudget`](struct.MemoryBudget.html), e.g. for
+/// exporting to your metrics system. Can be obtained by calling the
+/// [`metrics`](struct.MemoryBudget.html#method.metrics) method on an instance of
+/// [`MemoryBudget`](struct.MemoryBudget.html)
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+pub struct MemoryBudgetMetrics {
+    admitted: u64,
+    memory_in_use: u64,
+    rejected: u64,
+    running: usize,
+    total_memory_size: u64,
+    waiting: usize,
+}
+
+impl MemoryBudgetMetrics {
+    /// The number of hashes admitted since the budget was created
+    pub fn admitted(&self) -> u64 {
+        self.admitted
+    }
+    /// The memory (in kibibytes) reserved by the hashes that are currently running
+    pub fn memory_in_use(&self) -> u64 {
+        self.memory_in_use
+    }
+    /// The number of hashes rejected since the budget was created, i.e. hashes larger than
+    /// the whole budget and hashes that failed fast or timed out while waiting
+    pub fn rejected(&self) -> u64 {
+        self.rejected
+    }
+    /// The number of hashes that are currently running
+    pub fn running(&self) -> usize {
+        self.running
+    }
+    /// The total memory size (in kibibytes) of the budget
+    pub fn total_memory_size(&self) -> u64 {
+        self.total_memory_size
+    }
+    /// The number of callers currently waiting for memory
+    pub fn waiting(&self) -> usize {
+        self.waiting
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc;
+    use std::thread;
+
+    use super::*;
+    use {Hasher, Verifier};
+
+    #[test]
+    fn test_memory_budget() {
+        let budget = MemoryBudget::with_admission(100, Admission::FailFast);
+        let a = budget.reserve(60).unwrap();
+        let metrics = budget.metrics();
+        assert_eq!(metrics.memory_in_use(), 60);
+        assert_eq!(metrics.running(), 1);
+
+        let err = budget.reserve(60).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::MemoryBudgetExceededError);
+        let b = budget.reserve(40).unwrap();
+        drop(a);
+        drop(b);
+
+        let err = budget.reserve(101).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::MemoryBudgetExceededError);
+
+        let metrics = budget.metrics();
+        assert_eq!(metrics.admitted(), 2);
+        assert_eq!(metrics.memory_in_use(), 0);
+        assert_eq!(metrics.rejected(), 2);
+        assert_eq!(metrics.running(), 0);
+        assert_eq!(metrics.total_memory_size(), 100);
+        assert_eq!(metrics.waiting(), 0);
+    }
+
+    #[test]
+    fn test_memory_budget_queue() {
+        let budget = MemoryBudget::new(100);
+        let reservation = budget.reserve(100).unwrap();
+        let (sender, receiver) = mpsc::channel();
+        let handle = {
+            let budget = budget.clone();
+            thread::spawn(move || {
+                let reservation = budget.reserve(50);
+                sender.send(()).unwrap();
+                reservation.map(|_| ())
+            })
+        };
+        while budget.metrics().waiting() == 0 {
+            thread::yield_now();
+        }
+        assert!(receiver.try_recv().is_err());
+        drop(reservation);
+        handle.join().unwrap().unwrap();
+        assert!(receiver.try_recv().is_ok());
+        assert_eq!(budget.metrics().admitted(), 2);
+    }
+
+    #[test]
+    fn test_memory_budget_timeout() {
+        let timeout = Duration::from_millis(10);
+        let budget = MemoryBudget::with_admission(100, Admission::QueueWithTimeout(timeout));
+        let _reservation = budget.reserve(100).unwrap();
+        let err = budget.reserve(1).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::MemoryBudgetExceededError);
+        assert_eq!(budget.metrics().waiting(), 0);
+        assert_eq!(budget.metrics().rejected(), 1);
+    }
+
+    #[test]
+    fn test_hasher_memory_budget() {
+        let mut hasher = Hasher::default();
+        hasher
+            .configure_iterations(2)
+            .configure_lanes(1)
+            .configure_memory_budget(MemoryBudget::new(32))
+            .configure_memory_size(64)
+            .opt_out_of_secret_key(true);
+        let err = hasher.hash_password("P@ssw0rd").unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::MemoryBudgetExceededError);
+
+        hasher.configure_memory_size(32);
+        assert!(hasher.hash_password("P@ssw0rd").is_ok());
+        let budget = hasher.config().memory_budget().unwrap();
+        assert_eq!(budget.metrics().admitted(), 1);
+        assert_eq!(budget.metrics().memory_in_use(), 0);
+    }
+
+    #[test]
+    fn test_verifier_memory_budget() {
+        let mut hasher = Hasher::default();
+        hasher
+            .configure_iterations(2)
+            .configure_lanes(1)
+            .configure_memory_size(64)
+            .opt_out_of_secret_key(true);
+        let hash = hasher.hash_password("P@ssw0rd").unwrap();
+
+        let budget = MemoryBudget::with_admission(32, Admission::FailFast);
+        let mut verifier = Verifier::default();
+        verifier.configure_memory_budget(budget.clone());
+        assert!(verifier.config().memory_budget().is_some());
+        let err = verifier.verify_password(&hash, "P@ssw0rd").unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::MemoryBudgetExceededError);
+        assert_eq!(budget.metrics().rejected(), 1);
+
+        let mut verifier = Verifier::default();
+        verifier.configure_memory_budget(MemoryBudget::new(64));
+        assert!(verifier.verify_password(&hash, "P@ssw0rd").unwrap());
+    }
+
+    #[test]
+    fn test_send() {
+        fn assert_send<T: Send>() {}
+        assert_send::<MemoryBudget>();
+        assert_send::<MemoryBudgetMetrics>();
+    }
+
+    #[test]
+    fn test_sync() {
+        fn assert_sync<T: Sync>() {}
+        assert_sync::<MemoryBudget>();
+        assert_sync::<MemoryBudgetMetrics>();
+    }
+}
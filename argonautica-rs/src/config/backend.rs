@@ -2,7 +2,8 @@ use config::defaults::DEFAULT_BACKEND;
 use {Error, ErrorKind};
 
 impl Default for Backend {
-    /// Returns [`Backend::C`](enum.Backend.html#variant.C)
+    /// Returns [`Backend::C`](enum.Backend.html#variant.C) if the `backend-c` feature is
+    /// enabled (the default); otherwise returns [`Backend::Rust`](enum.Backend.html#variant.Rust)
     fn default() -> Backend {
         DEFAULT_BACKEND
     }
@@ -10,19 +11,20 @@ impl Default for Backend {
 
 /// Enum representing the choice between a
 /// [C implementation](https://github.com/P-H-C/phc-winner-argon2/tree/20171227)
-/// of the Argon2 algorithm or a Rust implementation. *Currently only a C
-/// implemenation is supported. You will get an error if you choose the Rust backend.
-/// The intention is to write a Rust implementation in the future.*
+/// of the Argon2 algorithm or a pure Rust implementation. Both backends produce byte-for-byte
+/// identical hashes. The C backend is only available if argonautica was built with the
+/// `backend-c` feature (which is on by default); building with `default-features = false`
+/// removes the need for a C compiler, but leaves only the Rust backend.
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
 pub enum Backend {
     /// Backend using a
     /// [C implementation](https://github.com/P-H-C/phc-winner-argon2/tree/20171227)
-    /// of the Argon2 algorithm
+    /// of the Argon2 algorithm. *You will get an error if you use this backend without
+    /// the `backend-c` feature.*
     C = 1,
-    /// Placeholder for a currently-unavailable but planned-for-the-future Rust backend.
-    /// *You will get an error if you use this backend now.*
+    /// Backend using a pure Rust implementation of the Argon2 algorithm
     Rust = 2,
 }
 
@@ -0,0 +1,99 @@
+impl Default for BlockCompression {
+    /// Returns the result of [`BlockCompression::detect`](enum.BlockCompression.html#method.detect)
+    fn default() -> BlockCompression {
+        BlockCompression::detect()
+    }
+}
+
+/// Enum representing the implementation of the Argon2 block compression function (the inner
+/// loop of the algorithm, where almost all of the time is spent). argonautica compiles every
+/// implementation that makes sense for the target architecture and picks the fastest one the
+/// CPU it is <i>running</i> on supports, so the same binary can be deployed to machines with
+/// different instruction sets. All implementations produce identical hashes.
+///
+/// The implementation hashing uses is the one returned by
+/// [`BlockCompression::detect`](enum.BlockCompression.html#method.detect), which can be called to
+/// inspect it (e.g. for diagnostics)
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub enum BlockCompression {
+    /// Portable implementation that runs on any CPU
+    Reference = 0,
+    /// Implementation using SSE2 instructions (x86 and x86_64 only)
+    Sse2 = 1,
+    /// Implementation using AVX2 instructions (x86 and x86_64 only)
+    Avx2 = 2,
+}
+
+impl BlockCompression {
+    /// Returns the fastest implementation supported by the CPU this code is currently
+    /// running on
+    pub fn detect() -> BlockCompression {
+        if BlockCompression::Avx2.is_supported() {
+            BlockCompression::Avx2
+        } else if BlockCompression::Sse2.is_supported() {
+            BlockCompression::Sse2
+        } else {
+            BlockCompression::Reference
+        }
+    }
+
+    /// Returns `true` if the CPU this code is currently running on supports this implementation
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    pub fn is_supported(self) -> bool {
+        match self {
+            BlockCompression::Reference => true,
+            BlockCompression::Sse2 => is_x86_feature_detected!("sse2"),
+            BlockCompression::Avx2 => is_x86_feature_detected!("avx2"),
+        }
+    }
+
+    /// Returns `true` if the CPU this code is currently running on supports this implementation
+    #[cfg(not(any(target_arch = "x86", target_arch = "x86_64")))]
+    pub fn is_supported(self) -> bool {
+        match self {
+            BlockCompression::Reference => true,
+            BlockCompression::Sse2 | BlockCompression::Avx2 => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_is_supported() {
+        assert!(BlockCompression::detect().is_supported());
+        assert!(BlockCompression::Reference.is_supported());
+    }
+
+    #[test]
+    fn test_send() {
+        fn assert_send<T: Send>() {}
+        assert_send::<BlockCompression>();
+    }
+
+    #[test]
+    fn test_sync() {
+        fn assert_sync<T: Sync>() {}
+        assert_sync::<BlockCompression>();
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serialize() {
+        use serde;
+        fn assert_serialize<T: serde::Serialize>() {}
+        assert_serialize::<BlockCompression>();
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_deserialize() {
+        use serde;
+        fn assert_deserialize<'de, T: serde::Deserialize<'de>>() {}
+        assert_deserialize::<BlockCompression>();
+    }
+}
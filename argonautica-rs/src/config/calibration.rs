@@ -0,0 +1,249 @@
+use std::time::{Duration, Instant};
+
+use config::HasherConfig;
+use {Error, ErrorKind, Hasher};
+
+/// Calibration aims for a mean hashing time within this fraction of the target (either way)
+const CALIBRATION_TOLERANCE: f64 = 0.1;
+
+/// Number of hashes timed with the final configuration to compute the reported statistics
+const CALIBRATION_SAMPLES: u32 = 5;
+
+/// Upper bound on the number of adjustments made while tuning iterations
+const CALIBRATION_MAX_ROUNDS: u32 = 16;
+
+const CALIBRATION_PASSWORD: &str = "P@ssw0rd";
+
+/// The result of [`Hasher::calibrate`](../struct.Hasher.html#method.calibrate): a
+/// [`HasherConfig`](struct.HasherConfig.html) tuned for the machine calibration ran on,
+/// along with the hashing times that were measured with it
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct Calibration {
+    config: HasherConfig,
+    mean: Duration,
+    samples: u32,
+    std_dev: Duration,
+    target: Duration,
+}
+
+impl Calibration {
+    /// The calibrated configuration. Only `iterations` and `memory_size` differ from the
+    /// configuration of the [`Hasher`](../struct.Hasher.html) that was calibrated
+    pub fn config(&self) -> &HasherConfig {
+        &self.config
+    }
+    /// Returns `true` if the [`mean`](struct.Calibration.html#method.mean) hashing time is
+    /// within 10% of the [`target`](struct.Calibration.html#method.target). This can be
+    /// `false` if the target is unreachable on this machine, e.g. if even the smallest
+    /// configuration is slower than the target, or if the memory limit is so low that no
+    /// reasonable number of iterations reaches it
+    pub fn is_within_target(&self) -> bool {
+        let (lower, upper) = window(self.target);
+        let mean = as_secs_f64(self.mean);
+        lower <= mean && mean <= upper
+    }
+    /// The mean time it took to hash a password with the calibrated configuration
+    pub fn mean(&self) -> Duration {
+        self.mean
+    }
+    /// The number of hashes that were timed to compute the
+    /// [`mean`](struct.Calibration.html#method.mean) and
+    /// [`std_dev`](struct.Calibration.html#method.std_dev)
+    pub fn samples(&self) -> u32 {
+        self.samples
+    }
+    /// The (sample) standard deviation of the measured hashing times
+    pub fn std_dev(&self) -> Duration {
+        self.std_dev
+    }
+    /// The target hashing time that calibration was asked to achieve
+    pub fn target(&self) -> Duration {
+        self.target
+    }
+}
+
+pub(crate) fn calibrate(
+    config: &HasherConfig,
+    target: Duration,
+    max_memory_size: u32,
+) -> Result<Calibration, Error> {
+    let min_memory_size = (8 * config.lanes()).next_power_of_two();
+    if max_memory_size < min_memory_size {
+        return Err(
+            Error::new(ErrorKind::MemorySizeTooSmallError).add_context(format!(
+                "Max memory size: {}. With {} lanes, memory size must be at least {}",
+                max_memory_size,
+                config.lanes(),
+                min_memory_size,
+            )),
+        );
+    }
+    let (lower, upper) = window(target);
+    let mut config = config.clone();
+
+    // Maximize memory size first, using a single iteration...
+    config.set_iterations(1);
+    config.set_memory_size(min_memory_size);
+    let mut elapsed = measure(&config)?;
+    while elapsed < upper && config.memory_size() <= max_memory_size / 2 {
+        let previous_memory_size = config.memory_size();
+        config.set_memory_size(previous_memory_size * 2);
+        let next_elapsed = measure(&config)?;
+        if next_elapsed > upper {
+            config.set_memory_size(previous_memory_size);
+            break;
+        }
+        elapsed = next_elapsed;
+    }
+
+    // ... then add iterations, which scale hashing time roughly linearly, until the hashing
+    // time lands inside the window
+    let per_iteration = elapsed;
+    let target_secs = as_secs_f64(target);
+    if per_iteration > 0.0 {
+        config.set_iterations(estimate_iterations(target_secs / per_iteration));
+    }
+    for _ in 0..CALIBRATION_MAX_ROUNDS {
+        let elapsed = measure(&config)?;
+        if elapsed >= lower && elapsed <= upper {
+            break;
+        }
+        let iterations = config.iterations();
+        let next_iterations =
+            estimate_iterations(f64::from(iterations) * target_secs / elapsed.max(1e-9));
+        let next_iterations = if next_iterations == iterations {
+            if elapsed < lower {
+                iterations + 1
+            } else {
+                iterations.saturating_sub(1).max(1)
+            }
+        } else {
+            next_iterations
+        };
+        if next_iterations == iterations {
+            break;
+        }
+        config.set_iterations(next_iterations);
+    }
+
+    let mut measurements = Vec::with_capacity(CALIBRATION_SAMPLES as usize);
+    for _ in 0..CALIBRATION_SAMPLES {
+        measurements.push(measure(&config)?);
+    }
+    let n = f64::from(CALIBRATION_SAMPLES);
+    let mean = measurements.iter().sum::<f64>() / n;
+    let variance = measurements
+        .iter()
+        .map(|x| (x - mean) * (x - mean))
+        .sum::<f64>()
+        / (n - 1.0);
+    Ok(Calibration {
+        config,
+        mean: from_secs_f64(mean),
+        samples: CALIBRATION_SAMPLES,
+        std_dev: from_secs_f64(variance.sqrt()),
+        target,
+    })
+}
+
+/// Hashes a password once with `config`, returning the number of seconds it took
+fn measure(config: &HasherConfig) -> Result<f64, Error> {
+    let mut hasher = Hasher {
+        config: config.clone(),
+        ..Hasher::default()
+    };
+    hasher
+        .configure_password_clearing(false)
+        .configure_secret_key_clearing(false)
+        .opt_out_of_secret_key(true)
+        .with_password(CALIBRATION_PASSWORD);
+    let now = Instant::now();
+    let _ = hasher.hash_raw()?;
+    Ok(as_secs_f64(now.elapsed()))
+}
+
+fn estimate_iterations(iterations: f64) -> u32 {
+    if iterations >= f64::from(u32::MAX) {
+        u32::MAX
+    } else if iterations <= 1.0 {
+        1
+    } else {
+        iterations.round() as u32
+    }
+}
+
+fn window(target: Duration) -> (f64, f64) {
+    let target = as_secs_f64(target);
+    (
+        target * (1.0 - CALIBRATION_TOLERANCE),
+        target * (1.0 + CALIBRATION_TOLERANCE),
+    )
+}
+
+fn as_secs_f64(duration: Duration) -> f64 {
+    duration.as_secs() as f64 + f64::from(duration.subsec_nanos()) / 1_000_000_000.0
+}
+
+fn from_secs_f64(secs: f64) -> Duration {
+    let whole_secs = secs.trunc();
+    Duration::new(
+        whole_secs as u64,
+        ((secs - whole_secs) * 1_000_000_000.0) as u32,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use config::Backend;
+
+    #[test]
+    fn test_calibrate() {
+        let mut hasher = Hasher::default();
+        hasher
+            .configure_backend(Backend::Rust)
+            .configure_lanes(2)
+            .configure_threads(2);
+        let calibration = hasher.calibrate(Duration::from_millis(20), 1_000).unwrap();
+        let config = calibration.config();
+        assert!(config.memory_size() <= 1_000);
+        assert!(config.memory_size() >= 16);
+        assert!(config.memory_size().is_power_of_two());
+        assert!(config.iterations() >= 1);
+        assert_eq!(config.lanes(), 2);
+        assert_eq!(config.backend(), Backend::Rust);
+        assert_eq!(calibration.samples(), CALIBRATION_SAMPLES);
+        assert_eq!(calibration.target(), Duration::from_millis(20));
+        assert!(calibration.mean() > Duration::from_millis(0));
+        assert!(calibration.std_dev() <= calibration.mean() * CALIBRATION_SAMPLES);
+    }
+
+    #[test]
+    fn test_calibrate_memory_too_small() {
+        let mut hasher = Hasher::default();
+        hasher.configure_lanes(4);
+        let err = hasher.calibrate(Duration::from_millis(20), 16).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::MemorySizeTooSmallError);
+    }
+
+    #[test]
+    fn test_estimate_iterations() {
+        assert_eq!(estimate_iterations(0.2), 1);
+        assert_eq!(estimate_iterations(2.6), 3);
+        assert_eq!(estimate_iterations(1e12), u32::MAX);
+    }
+
+    #[test]
+    fn test_send() {
+        fn assert_send<T: Send>() {}
+        assert_send::<Calibration>();
+    }
+
+    #[test]
+    fn test_sync() {
+        fn assert_sync<T: Sync>() {}
+        assert_sync::<Calibration>();
+    }
+}
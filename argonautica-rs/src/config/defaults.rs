@@ -1,13 +1,26 @@
+use std::sync::Arc;
+
 use futures_cpupool::CpuPool;
-use num_cpus;
 
-use config::{Backend, Variant, Version};
+use config::{environment, Backend, Environment, Variant, Version};
+use executor::{global_thread_pool, BlockingExecutor};
 
-/// Returns a [`CpuPool`](https://docs.rs/futures-cpupool/0.1.8/futures_cpupool/struct.CpuPool.html)
-/// with threads equal to the number of logical cores on your machine
+/// Returns a new [`CpuPool`](https://docs.rs/futures-cpupool/0.1.8/futures_cpupool/struct.CpuPool.html)
+/// with threads equal to the number of cores available to the process (see
+/// [`environment`](fn.environment.html)). Note that the
+/// non-blocking methods of [`Hasher`](../struct.Hasher.html) and
+/// [`Verifier`](../struct.Verifier.html) do not call this function when they have no cpu pool;
+/// they use the process-wide [`global_thread_pool`](../executor/fn.global_thread_pool.html)
+/// instead
 #[inline(always)]
 pub fn default_cpu_pool() -> CpuPool {
-    CpuPool::new(num_cpus::get())
+    CpuPool::new(environment().cpus())
+}
+
+/// Returns the process-wide [`ThreadPool`](../executor/struct.ThreadPool.html) (see
+/// [`global_thread_pool`](../executor/fn.global_thread_pool.html))
+pub fn default_executor() -> Arc<dyn BlockingExecutor> {
+    Arc::new(global_thread_pool())
 }
 
 #[cfg(feature = "serde")]
@@ -15,30 +28,88 @@ pub(crate) fn default_cpu_pool_serde() -> Option<CpuPool> {
     None
 }
 
-/// Returns the number of logical cores on your machine
+#[cfg(feature = "serde")]
+pub(crate) fn default_salt_len_serde() -> u32 {
+    DEFAULT_SALT_LEN
+}
+
+/// Returns the number of cores available to the process, i.e. the number of logical cores on
+/// your machine or, if lower, the CPU quota of the process's cgroup (see
+/// [`environment`](fn.environment.html))
 #[inline(always)]
 pub fn default_lanes() -> u32 {
-    num_cpus::get() as u32
+    environment().cpus() as u32
 }
 
-/// Returns the number of logical cores on your machine
+/// Returns [`DEFAULT_MEMORY_SIZE`](constant.DEFAULT_MEMORY_SIZE.html) or, if the process's
+/// cgroup has a memory limit too small for that many kibibytes per hash, a smaller power of
+/// two (see [`environment`](fn.environment.html)). The memory size is chosen so that a hash
+/// running on every available core uses at most a quarter of the memory limit, but it is
+/// never smaller than `8` times [`default_lanes`](fn.default_lanes.html)
+pub fn default_memory_size() -> u32 {
+    memory_size_for(&environment())
+}
+
+/// Returns the number of cores available to the process, i.e. the number of logical cores on
+/// your machine or, if lower, the CPU quota of the process's cgroup (see
+/// [`environment`](fn.environment.html))
 #[inline(always)]
 pub fn default_threads() -> u32 {
-    num_cpus::get() as u32
+    environment().cpus() as u32
 }
 
-/// [`Backend::C`](enum.Backend.html#variant.C)
+fn memory_size_for(environment: &Environment) -> u32 {
+    let cpus = environment.cpus() as u64;
+    let min_memory_size = (8 * cpus).next_power_of_two();
+    let memory_size = match environment.memory_limit() {
+        Some(memory_limit) => {
+            let per_hash = memory_limit / 1024 / 4 / cpus;
+            if per_hash < u64::from(DEFAULT_MEMORY_SIZE) {
+                // The largest power of two that fits
+                (per_hash / 2 + 1).next_power_of_two()
+            } else {
+                u64::from(DEFAULT_MEMORY_SIZE)
+            }
+        }
+        None => u64::from(DEFAULT_MEMORY_SIZE),
+    };
+    memory_size.max(min_memory_size) as u32
+}
+
+/// [`Backend::C`](enum.Backend.html#variant.C) if the `backend-c` feature is enabled (the
+/// default); otherwise [`Backend::Rust`](enum.Backend.html#variant.Rust)
+#[cfg(feature = "backend-c")]
 pub const DEFAULT_BACKEND: Backend = Backend::C;
 
+/// [`Backend::C`](enum.Backend.html#variant.C) if the `backend-c` feature is enabled (the
+/// default); otherwise [`Backend::Rust`](enum.Backend.html#variant.Rust)
+#[cfg(not(feature = "backend-c"))]
+pub const DEFAULT_BACKEND: Backend = Backend::Rust;
+
 /// `32_u32`
 pub const DEFAULT_HASH_LEN: u32 = 32;
 
 /// `192_u32`
 pub const DEFAULT_ITERATIONS: u32 = 192;
 
-/// `4096_u32`
+/// `4096_u32` (but see [`default_memory_size`](fn.default_memory_size.html))
 pub const DEFAULT_MEMORY_SIZE: u32 = 4_096;
 
+/// `1024_u32`
+pub const DEFAULT_POLICY_MAX_HASH_LEN: u32 = 1_024;
+
+/// `4096_u32`
+pub const DEFAULT_POLICY_MAX_ITERATIONS: u32 = 4_096;
+
+/// `64_u32`
+pub const DEFAULT_POLICY_MAX_LANES: u32 = 64;
+
+/// `1048576_u32` (i.e. 1 GiB)
+pub const DEFAULT_POLICY_MAX_MEMORY_SIZE: u32 = 1_048_576;
+
+/// `1024_u32`
+pub const DEFAULT_POLICY_MAX_SALT_LEN: u32 = 1_024;
+
 /// `false`
 pub const DEFAULT_OPT_OUT_OF_SECRET_KEY: bool = false;
 
@@ -56,3 +127,27 @@ pub const DEFAULT_VARIANT: Variant = Variant::Argon2id;
 
 /// [`Version::_0x13`](enum.Version.html#variant._0x13)
 pub const DEFAULT_VERSION: Version = Version::_0x13;
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+
+    #[test]
+    fn test_memory_size_for() {
+        let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/cgroup");
+        for &(name, logical_cpus, expected) in &[
+            ("missing", 4, DEFAULT_MEMORY_SIZE),
+            ("v1", 8, DEFAULT_MEMORY_SIZE),
+            ("v2", 8, DEFAULT_MEMORY_SIZE),
+            // 8 MiB shared by 2 cores...
+            ("v2-small", 8, 1024),
+            // ... or used by a single core
+            ("v2-small", 1, 2048),
+        ] {
+            let environment = Environment::detect(&fixtures.join(name), logical_cpus);
+            assert_eq!(memory_size_for(&environment), expected, "{}", name);
+        }
+    }
+}
@@ -0,0 +1,294 @@
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use num_cpus;
+
+// cgroup v1 reports "no limit" as a very large, page-aligned number rather than as "max"
+const CGROUP_V1_UNLIMITED: u64 = 1 << 62;
+
+lazy_static! {
+    static ref ENVIRONMENT: Environment = Environment::detect(Path::new("/"), num_cpus::get());
+}
+
+/// Returns the [`Environment`](struct.Environment.html) argonautica is running in, i.e. the
+/// CPU and memory resources available to the process, which are detected once, when this
+/// function is first called. The defaults for `lanes`, `threads` and `memory_size` (see
+/// [`default_lanes`](fn.default_lanes.html), [`default_threads`](fn.default_threads.html)
+/// and [`default_memory_size`](fn.default_memory_size.html)) are derived from it
+pub fn environment() -> Environment {
+    *ENVIRONMENT
+}
+
+/// The version of the Linux control groups (cgroups) hierarchy the process runs in
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum CgroupVersion {
+    /// cgroup v1, i.e. one hierarchy per controller (e.g. `/sys/fs/cgroup/memory`)
+    V1,
+    /// cgroup v2, i.e. the unified hierarchy
+    V2,
+}
+
+/// The CPU and memory resources available to the process (see
+/// [`environment`](fn.environment.html)).
+///
+/// In a container, the number of cores on the machine and its memory say little about the
+/// resources the process can actually use; so on Linux, the CPU quota and memory limit of
+/// the process's control group (cgroup v1 or v2) are read from `/proc/self/cgroup` and
+/// `/sys/fs/cgroup` and taken into account. On other platforms, or when there is no cgroup
+/// limit, only the number of logical cores is used
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Environment {
+    cgroup_version: Option<CgroupVersion>,
+    cpu_quota: Option<f64>,
+    logical_cpus: usize,
+    memory_limit: Option<u64>,
+}
+
+impl Environment {
+    /// The version of the cgroup hierarchy the process runs in, if any
+    pub fn cgroup_version(&self) -> Option<CgroupVersion> {
+        self.cgroup_version
+    }
+    /// The CPU quota of the process's cgroup in number of cores (e.g. `1.5` if the cgroup may
+    /// use 150ms of CPU time every 100ms), if any
+    pub fn cpu_quota(&self) -> Option<f64> {
+        self.cpu_quota
+    }
+    /// The number of cores available to the process, i.e. the number of logical cores on
+    /// your machine or, if lower, the CPU quota rounded up (at least `1`)
+    pub fn cpus(&self) -> usize {
+        let cpus = match self.cpu_quota {
+            Some(cpu_quota) => (cpu_quota.ceil() as usize).min(self.logical_cpus),
+            None => self.logical_cpus,
+        };
+        cpus.max(1)
+    }
+    /// The number of logical cores on your machine
+    pub fn logical_cpus(&self) -> usize {
+        self.logical_cpus
+    }
+    /// The memory limit (in bytes) of the process's cgroup, if any
+    pub fn memory_limit(&self) -> Option<u64> {
+        self.memory_limit
+    }
+}
+
+impl fmt::Display for Environment {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.cgroup_version {
+            Some(CgroupVersion::V1) => writeln!(f, "cgroup version: v1")?,
+            Some(CgroupVersion::V2) => writeln!(f, "cgroup version: v2")?,
+            None => writeln!(f, "cgroup version: none")?,
+        }
+        writeln!(f, "logical cpus: {}", self.logical_cpus)?;
+        match self.cpu_quota {
+            Some(cpu_quota) => writeln!(f, "cpu quota: {}", cpu_quota)?,
+            None => writeln!(f, "cpu quota: none")?,
+        }
+        writeln!(f, "available cpus: {}", self.cpus())?;
+        match self.memory_limit {
+            Some(memory_limit) => write!(f, "memory limit: {} bytes", memory_limit),
+            None => write!(f, "memory limit: none"),
+        }
+    }
+}
+
+impl Environment {
+    /// Detects the cgroup limits of the process from the `proc` and `sys` directories under
+    /// `root` (`/` outside of tests)
+    pub(crate) fn detect(root: &Path, logical_cpus: usize) -> Environment {
+        let mut environment = Environment {
+            cgroup_version: None,
+            cpu_quota: None,
+            logical_cpus,
+            memory_limit: None,
+        };
+        let cgroups = match read(&root.join("proc/self/cgroup")) {
+            Some(cgroups) => cgroups,
+            None => return environment,
+        };
+        let cgroup_root = root.join("sys/fs/cgroup");
+        let mut v1_paths = Vec::new();
+        let mut v2_path = None;
+        for line in cgroups.lines() {
+            // Each line is "hierarchy-id:controller-list:cgroup-path"
+            let mut fields = line.splitn(3, ':');
+            let (id, controllers, path) = match (fields.next(), fields.next(), fields.next()) {
+                (Some(id), Some(controllers), Some(path)) => (id, controllers, path),
+                _ => continue,
+            };
+            if id == "0" && controllers.is_empty() {
+                v2_path = Some(path);
+            } else {
+                v1_paths.push((controllers, path));
+            }
+        }
+        match v2_path {
+            Some(path) if cgroup_root.join("cgroup.controllers").is_file() => {
+                environment.cgroup_version = Some(CgroupVersion::V2);
+                let dirs = cgroup_dirs(&cgroup_root, path);
+                environment.cpu_quota = find(&dirs, "cpu.max").and_then(|s| parse_cpu_max(&s));
+                environment.memory_limit =
+                    find(&dirs, "memory.max").and_then(|s| parse_memory_max(&s));
+            }
+            _ if !v1_paths.is_empty() => {
+                environment.cgroup_version = Some(CgroupVersion::V1);
+                for (controllers, path) in v1_paths {
+                    for controller in controllers.split(',') {
+                        match controller {
+                            "cpu" => {
+                                let dirs = cgroup_dirs(&cgroup_root.join(controllers), path)
+                                    .into_iter()
+                                    .chain(cgroup_dirs(&cgroup_root.join("cpu"), path))
+                                    .collect::<Vec<_>>();
+                                let quota = find(&dirs, "cpu.cfs_quota_us");
+                                let period = find(&dirs, "cpu.cfs_period_us");
+                                environment.cpu_quota = match (quota, period) {
+                                    (Some(quota), Some(period)) => parse_cfs(&quota, &period),
+                                    _ => None,
+                                };
+                            }
+                            "memory" => {
+                                let dirs = cgroup_dirs(&cgroup_root.join("memory"), path);
+                                environment.memory_limit = find(&dirs, "memory.limit_in_bytes")
+                                    .and_then(|s| s.trim().parse::<u64>().ok())
+                                    .filter(|&limit| limit < CGROUP_V1_UNLIMITED);
+                            }
+                            _ => (),
+                        }
+                    }
+                }
+            }
+            _ => (),
+        }
+        environment
+    }
+}
+
+/// The directories that may hold the files of the cgroup at `path`: the cgroup's own
+/// directory and, because inside a container the cgroup's directory is often mounted as
+/// the root of the hierarchy, the root of the hierarchy itself
+fn cgroup_dirs(hierarchy: &Path, path: &str) -> Vec<PathBuf> {
+    let path = path.trim_start_matches('/');
+    if path.is_empty() {
+        vec![hierarchy.to_path_buf()]
+    } else {
+        vec![hierarchy.join(path), hierarchy.to_path_buf()]
+    }
+}
+
+fn find(dirs: &[PathBuf], file_name: &str) -> Option<String> {
+    dirs.iter()
+        .filter_map(|dir| read(&dir.join(file_name)))
+        .next()
+}
+
+fn read(path: &Path) -> Option<String> {
+    fs::read_to_string(path).ok()
+}
+
+/// Parses cgroup v2's `cpu.max`, e.g. "max 100000" or "150000 100000"
+fn parse_cpu_max(s: &str) -> Option<f64> {
+    let mut fields = s.split_whitespace();
+    match (fields.next(), fields.next()) {
+        (Some("max"), _) => None,
+        (Some(quota), Some(period)) => parse_cfs(quota, period),
+        _ => None,
+    }
+}
+
+/// Parses cgroup v1's `cpu.cfs_quota_us` and `cpu.cfs_period_us`; a quota of `-1` means no
+/// limit
+fn parse_cfs(quota: &str, period: &str) -> Option<f64> {
+    let quota = quota.trim().parse::<i64>().ok()?;
+    let period = period.trim().parse::<i64>().ok()?;
+    if quota <= 0 || period <= 0 {
+        return None;
+    }
+    Some(quota as f64 / period as f64)
+}
+
+/// Parses cgroup v2's `memory.max`, e.g. "max" or "536870912"
+fn parse_memory_max(s: &str) -> Option<u64> {
+    match s.trim() {
+        "max" => None,
+        s => s.parse::<u64>().ok(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture(name: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures/cgroup")
+            .join(name)
+    }
+
+    #[test]
+    fn test_detect_v1() {
+        let environment = Environment::detect(&fixture("v1"), 8);
+        assert_eq!(environment.cgroup_version(), Some(CgroupVersion::V1));
+        assert_eq!(environment.cpu_quota(), Some(1.5));
+        assert_eq!(environment.cpus(), 2);
+        assert_eq!(environment.logical_cpus(), 8);
+        assert_eq!(environment.memory_limit(), Some(268_435_456));
+    }
+
+    #[test]
+    fn test_detect_v1_unlimited() {
+        let environment = Environment::detect(&fixture("v1-unlimited"), 8);
+        assert_eq!(environment.cgroup_version(), Some(CgroupVersion::V1));
+        assert_eq!(environment.cpu_quota(), None);
+        assert_eq!(environment.cpus(), 8);
+        assert_eq!(environment.memory_limit(), None);
+    }
+
+    #[test]
+    fn test_detect_v2() {
+        let environment = Environment::detect(&fixture("v2"), 8);
+        assert_eq!(environment.cgroup_version(), Some(CgroupVersion::V2));
+        assert_eq!(environment.cpu_quota(), Some(0.5));
+        assert_eq!(environment.cpus(), 1);
+        assert_eq!(environment.memory_limit(), Some(67_108_864));
+    }
+
+    #[test]
+    fn test_detect_v2_unlimited() {
+        let environment = Environment::detect(&fixture("v2-unlimited"), 2);
+        assert_eq!(environment.cgroup_version(), Some(CgroupVersion::V2));
+        assert_eq!(environment.cpu_quota(), None);
+        assert_eq!(environment.cpus(), 2);
+        assert_eq!(environment.memory_limit(), None);
+    }
+
+    #[test]
+    fn test_detect_none() {
+        let environment = Environment::detect(&fixture("missing"), 4);
+        assert_eq!(environment.cgroup_version(), None);
+        assert_eq!(environment.cpus(), 4);
+        assert_eq!(environment.memory_limit(), None);
+    }
+
+    #[test]
+    fn test_environment() {
+        let environment = environment();
+        assert!(environment.cpus() >= 1);
+        assert!(environment.cpus() <= environment.logical_cpus());
+        assert!(environment.to_string().contains("available cpus"));
+    }
+
+    #[test]
+    fn test_send() {
+        fn assert_send<T: Send>() {}
+        assert_send::<Environment>();
+    }
+
+    #[test]
+    fn test_sync() {
+        fn assert_sync<T: Sync>() {}
+        assert_sync::<Environment>();
+    }
+}
@@ -1,7 +1,12 @@
+use std::sync::Arc;
+
 use futures_cpupool::CpuPool;
 
+use allocator::MemoryAllocator;
+use budget::MemoryBudget;
 use config::defaults::*;
 use config::{Backend, Flags, Variant, Version};
+use executor::BlockingExecutor;
 use {Error, ErrorKind};
 
 const PANIC_WARNING: &str = "Your program will error if you use this configuration";
@@ -23,12 +28,22 @@ pub struct HasherConfig {
         )
     )]
     cpu_pool: Option<CpuPool>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing, skip_deserializing))]
+    executor: Option<Arc<dyn BlockingExecutor>>,
     hash_len: u32,
     iterations: u32,
+    #[cfg_attr(feature = "serde", serde(default))]
+    key_id: Option<Vec<u8>>,
     lanes: u32,
+    #[cfg_attr(feature = "serde", serde(skip_serializing, skip_deserializing))]
+    memory_allocator: Option<Arc<dyn MemoryAllocator>>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing, skip_deserializing))]
+    memory_budget: Option<MemoryBudget>,
     memory_size: u32,
     opt_out_of_secret_key: bool,
     password_clearing: bool,
+    #[cfg_attr(feature = "serde", serde(default = "default_salt_len_serde"))]
+    salt_len: u32,
     secret_key_clearing: bool,
     threads: u32,
     variant: Variant,
@@ -47,6 +62,11 @@ impl HasherConfig {
             None => None,
         }
     }
+    /// The [`BlockingExecutor`](../executor/trait.BlockingExecutor.html) the async methods
+    /// run on, if one has been configured
+    pub fn executor(&self) -> Option<Arc<dyn BlockingExecutor>> {
+        self.executor.clone()
+    }
     #[allow(missing_docs)]
     pub fn hash_len(&self) -> u32 {
         self.hash_len
@@ -55,10 +75,25 @@ impl HasherConfig {
     pub fn iterations(&self) -> u32 {
         self.iterations
     }
+    /// The id of the active key of the [`Keyring`](../input/struct.Keyring.html) the
+    /// [`Hasher`](../struct.Hasher.html) is configured with, if any
+    pub fn key_id(&self) -> Option<&[u8]> {
+        self.key_id.as_ref().map(|key_id| &key_id[..])
+    }
     #[allow(missing_docs)]
     pub fn lanes(&self) -> u32 {
         self.lanes
     }
+    /// The [`MemoryAllocator`](../allocator/trait.MemoryAllocator.html) the work area of each
+    /// hash is allocated with, if one has been configured
+    pub fn memory_allocator(&self) -> Option<Arc<dyn MemoryAllocator>> {
+        self.memory_allocator.clone()
+    }
+    /// The [`MemoryBudget`](../budget/struct.MemoryBudget.html) hashes reserve their memory
+    /// from, if one has been configured
+    pub fn memory_budget(&self) -> Option<MemoryBudget> {
+        self.memory_budget.clone()
+    }
     #[allow(missing_docs)]
     pub fn memory_size(&self) -> u32 {
         self.memory_size
@@ -71,6 +106,11 @@ impl HasherConfig {
     pub fn password_clearing(&self) -> bool {
         self.password_clearing
     }
+    /// The length (in bytes) of the [`Salt`](../input/struct.Salt.html) the
+    /// [`Hasher`](../struct.Hasher.html) is configured with
+    pub fn salt_len(&self) -> u32 {
+        self.salt_len
+    }
     #[allow(missing_docs)]
     pub fn secret_key_clearing(&self) -> bool {
         self.secret_key_clearing
@@ -94,12 +134,17 @@ impl HasherConfig {
         HasherConfig {
             backend: Backend::default(),
             cpu_pool: None,
+            executor: None,
             hash_len: DEFAULT_HASH_LEN,
             iterations: DEFAULT_ITERATIONS,
+            key_id: None,
             lanes: default_lanes(),
-            memory_size: DEFAULT_MEMORY_SIZE,
+            memory_allocator: None,
+            memory_budget: None,
+            memory_size: default_memory_size(),
             opt_out_of_secret_key: DEFAULT_OPT_OUT_OF_SECRET_KEY,
             password_clearing: DEFAULT_PASSWORD_CLEARING,
+            salt_len: DEFAULT_SALT_LEN,
             secret_key_clearing: DEFAULT_SECRET_KEY_CLEARING,
             threads: default_threads(),
             variant: Variant::default(),
@@ -126,6 +171,9 @@ impl HasherConfig {
     pub(crate) fn set_cpu_pool(&mut self, cpu_pool: CpuPool) {
         self.cpu_pool = Some(cpu_pool);
     }
+    pub(crate) fn set_executor(&mut self, executor: Arc<dyn BlockingExecutor>) {
+        self.executor = Some(executor);
+    }
     pub(crate) fn set_hash_len(&mut self, hash_len: u32) {
         validate_hash_len(hash_len).unwrap_or_else(|e| {
             warn!("{}. {}.", e, PANIC_WARNING);
@@ -138,12 +186,21 @@ impl HasherConfig {
         });
         self.iterations = iterations;
     }
+    pub(crate) fn set_key_id(&mut self, key_id: Option<Vec<u8>>) {
+        self.key_id = key_id;
+    }
     pub(crate) fn set_lanes(&mut self, lanes: u32) {
         validate_lanes(lanes).unwrap_or_else(|e| {
             warn!("{}. {}.", e, PANIC_WARNING);
         });
         self.lanes = lanes;
     }
+    pub(crate) fn set_memory_allocator(&mut self, memory_allocator: Arc<dyn MemoryAllocator>) {
+        self.memory_allocator = Some(memory_allocator);
+    }
+    pub(crate) fn set_memory_budget(&mut self, memory_budget: MemoryBudget) {
+        self.memory_budget = Some(memory_budget);
+    }
     pub(crate) fn set_memory_size(&mut self, memory_size: u32) {
         validate_memory_size(self.lanes, memory_size).unwrap_or_else(|e| {
             warn!("{}. {}.", e, PANIC_WARNING);
@@ -156,6 +213,9 @@ impl HasherConfig {
     pub(crate) fn set_password_clearing(&mut self, boolean: bool) {
         self.password_clearing = boolean;
     }
+    pub(crate) fn set_salt_len(&mut self, salt_len: u32) {
+        self.salt_len = salt_len;
+    }
     pub(crate) fn set_secret_key_clearing(&mut self, boolean: bool) {
         self.secret_key_clearing = boolean;
     }
@@ -191,8 +251,14 @@ impl HasherConfig {
 
 fn validate_backend(backend: Backend) -> Result<(), Error> {
     match backend {
+        #[cfg(feature = "backend-c")]
         Backend::C => (),
-        Backend::Rust => return Err(Error::new(ErrorKind::BackendUnsupportedError)),
+        #[cfg(not(feature = "backend-c"))]
+        Backend::C => {
+            return Err(Error::new(ErrorKind::BackendUnsupportedError)
+                .add_context("argonautica was built without the backend-c feature"))
+        }
+        Backend::Rust => (),
     }
     Ok(())
 }
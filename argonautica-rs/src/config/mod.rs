@@ -1,17 +1,26 @@
 //! Enums and defaults for Argon2 configuration options (e.g. `hash_len`,
 //! [`Variant`](config/enum.Variant.html), [`Version`](config/enum.Version.html), etc.)
 mod backend;
+mod block_compression;
+mod calibration;
 pub(crate) mod defaults;
+mod environment;
 mod flags;
 mod hasher_config;
 mod variant;
 mod verifier_config;
+mod verifier_policy;
 mod version;
 
 pub use self::backend::Backend;
+pub use self::block_compression::BlockCompression;
+pub(crate) use self::calibration::calibrate;
+pub use self::calibration::Calibration;
 pub use self::defaults::*;
+pub use self::environment::{environment, CgroupVersion, Environment};
 pub(crate) use self::flags::Flags;
 pub use self::hasher_config::HasherConfig;
 pub use self::variant::Variant;
 pub use self::verifier_config::VerifierConfig;
+pub use self::verifier_policy::VerifierPolicy;
 pub use self::version::Version;
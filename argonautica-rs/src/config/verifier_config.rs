@@ -1,8 +1,13 @@
+use std::sync::Arc;
+
 use futures_cpupool::CpuPool;
 
+use allocator::MemoryAllocator;
+use budget::MemoryBudget;
 #[cfg(feature = "serde")]
 use config::defaults::default_cpu_pool_serde;
-use config::Backend;
+use config::{Backend, HasherConfig, VerifierPolicy};
+use executor::BlockingExecutor;
 
 /// Read-only configuration for [`Verifier`](../struct.Verifier.html). Can be obtained by calling
 /// the [`config`](../struct.Verifier.html#method.config) method on an instance of
@@ -21,7 +26,15 @@ pub struct VerifierConfig {
         )
     )]
     pub(crate) cpu_pool: Option<CpuPool>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing, skip_deserializing))]
+    pub(crate) executor: Option<Arc<dyn BlockingExecutor>>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing, skip_deserializing))]
+    pub(crate) memory_allocator: Option<Arc<dyn MemoryAllocator>>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing, skip_deserializing))]
+    pub(crate) memory_budget: Option<MemoryBudget>,
     pub(crate) password_clearing: bool,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) policy: VerifierPolicy,
     pub(crate) secret_key_clearing: bool,
     pub(crate) threads: u32,
 }
@@ -38,10 +51,30 @@ impl VerifierConfig {
             None => None,
         }
     }
+    /// The [`BlockingExecutor`](../executor/trait.BlockingExecutor.html) the async methods
+    /// run on, if one has been configured
+    pub fn executor(&self) -> Option<Arc<dyn BlockingExecutor>> {
+        self.executor.clone()
+    }
+    /// The [`MemoryAllocator`](../allocator/trait.MemoryAllocator.html) the work area of each
+    /// verification is allocated with, if one has been configured
+    pub fn memory_allocator(&self) -> Option<Arc<dyn MemoryAllocator>> {
+        self.memory_allocator.clone()
+    }
+    /// The [`MemoryBudget`](../budget/struct.MemoryBudget.html) verifying reserves memory
+    /// from, if one has been configured
+    pub fn memory_budget(&self) -> Option<MemoryBudget> {
+        self.memory_budget.clone()
+    }
     #[allow(missing_docs)]
     pub fn password_clearing(&self) -> bool {
         self.password_clearing
     }
+    /// The [`VerifierPolicy`](struct.VerifierPolicy.html) hashes are checked against before
+    /// verifying
+    pub fn policy(&self) -> VerifierPolicy {
+        self.policy
+    }
     #[allow(missing_docs)]
     pub fn secret_key_clearing(&self) -> bool {
         self.secret_key_clearing
@@ -53,19 +86,17 @@ impl VerifierConfig {
 }
 
 impl VerifierConfig {
-    pub(crate) fn new(
-        backend: Backend,
-        cpu_pool: Option<CpuPool>,
-        password_clearing: bool,
-        secret_key_clearing: bool,
-        threads: u32,
-    ) -> VerifierConfig {
+    pub(crate) fn new(config: &HasherConfig, policy: VerifierPolicy) -> VerifierConfig {
         VerifierConfig {
-            backend,
-            cpu_pool,
-            password_clearing,
-            secret_key_clearing,
-            threads,
+            backend: config.backend(),
+            cpu_pool: config.cpu_pool(),
+            executor: config.executor(),
+            memory_allocator: config.memory_allocator(),
+            memory_budget: config.memory_budget(),
+            password_clearing: config.password_clearing(),
+            policy,
+            secret_key_clearing: config.secret_key_clearing(),
+            threads: config.threads(),
         }
     }
 }
@@ -0,0 +1,209 @@
+use config::defaults::{
+    DEFAULT_POLICY_MAX_HASH_LEN, DEFAULT_POLICY_MAX_ITERATIONS, DEFAULT_POLICY_MAX_LANES,
+    DEFAULT_POLICY_MAX_MEMORY_SIZE, DEFAULT_POLICY_MAX_SALT_LEN,
+};
+use output::HashRaw;
+use {Error, ErrorKind};
+
+impl Default for VerifierPolicy {
+    /// Same as the [`new`](struct.VerifierPolicy.html#method.new) method
+    fn default() -> VerifierPolicy {
+        VerifierPolicy {
+            max_hash_len: DEFAULT_POLICY_MAX_HASH_LEN,
+            max_iterations: DEFAULT_POLICY_MAX_ITERATIONS,
+            max_lanes: DEFAULT_POLICY_MAX_LANES,
+            max_memory_size: DEFAULT_POLICY_MAX_MEMORY_SIZE,
+            max_salt_len: DEFAULT_POLICY_MAX_SALT_LEN,
+        }
+    }
+}
+
+/// Upper limits on the parameters of the hashes a [`Verifier`](../struct.Verifier.html) is
+/// willing to verify against.
+///
+/// The parameters of a hash (memory size, iterations, lanes, etc.) are read from the hash
+/// itself; so, without limits, a tampered with or malicious hash could make verifying allocate
+/// gigabytes of memory or run for minutes. [`Verifier`](../struct.Verifier.html) checks every
+/// hash against its policy (see
+/// [`configure_policy`](../struct.Verifier.html#method.configure_policy)) before doing any
+/// work and returns an [`Error`](../struct.Error.html) of kind
+/// [`VerifierPolicyError`](../enum.ErrorKind.html#variant.VerifierPolicyError) for hashes
+/// that exceed it.
+///
+/// The default limits are generous enough for any hash created with sensible parameters; if
+/// you create hashes with more memory or iterations than that, raise the limits accordingly
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct VerifierPolicy {
+    max_hash_len: u32,
+    max_iterations: u32,
+    max_lanes: u32,
+    max_memory_size: u32,
+    max_salt_len: u32,
+}
+
+impl VerifierPolicy {
+    /// Creates a new [`VerifierPolicy`](struct.VerifierPolicy.html) with the following limits:
+    /// * `max_hash_len`: `1024` bytes
+    /// * `max_iterations`: `4096`
+    /// * `max_lanes`: `64`
+    /// * `max_memory_size`: `1048576` kibibytes (i.e. 1 GiB)
+    /// * `max_salt_len`: `1024` bytes
+    pub fn new() -> VerifierPolicy {
+        VerifierPolicy::default()
+    }
+    /// Creates a new [`VerifierPolicy`](struct.VerifierPolicy.html) that accepts hashes with
+    /// any parameters. Only use this if the hashes you verify against come from a trusted
+    /// source
+    pub fn unlimited() -> VerifierPolicy {
+        VerifierPolicy {
+            max_hash_len: u32::MAX,
+            max_iterations: u32::MAX,
+            max_lanes: u32::MAX,
+            max_memory_size: u32::MAX,
+            max_salt_len: u32::MAX,
+        }
+    }
+    /// Allows you to configure the maximum hash length (in number of bytes)
+    pub fn configure_max_hash_len(&mut self, max_hash_len: u32) -> &mut VerifierPolicy {
+        self.max_hash_len = max_hash_len;
+        self
+    }
+    /// Allows you to configure the maximum number of iterations
+    pub fn configure_max_iterations(&mut self, max_iterations: u32) -> &mut VerifierPolicy {
+        self.max_iterations = max_iterations;
+        self
+    }
+    /// Allows you to configure the maximum number of lanes
+    pub fn configure_max_lanes(&mut self, max_lanes: u32) -> &mut VerifierPolicy {
+        self.max_lanes = max_lanes;
+        self
+    }
+    /// Allows you to configure the maximum memory size (in kibibytes)
+    pub fn configure_max_memory_size(&mut self, max_memory_size: u32) -> &mut VerifierPolicy {
+        self.max_memory_size = max_memory_size;
+        self
+    }
+    /// Allows you to configure the maximum salt length (in number of bytes)
+    pub fn configure_max_salt_len(&mut self, max_salt_len: u32) -> &mut VerifierPolicy {
+        self.max_salt_len = max_salt_len;
+        self
+    }
+    /// Returns an [`Error`](../struct.Error.html) of kind
+    /// [`VerifierPolicyError`](../enum.ErrorKind.html#variant.VerifierPolicyError) if any of
+    /// the parameters of `hash_raw` exceeds the policy
+    pub fn check(&self, hash_raw: &HashRaw) -> Result<(), Error> {
+        let hash_len = hash_raw.raw_hash_bytes().len() as u64;
+        check_limit("Hash length", hash_len, self.max_hash_len)?;
+        self.check_params(
+            hash_raw.iterations(),
+            hash_raw.lanes(),
+            hash_raw.memory_size(),
+            hash_raw.raw_salt_bytes().len(),
+        )
+    }
+    /// Same as [`check`](struct.VerifierPolicy.html#method.check), for parameters that do not
+    /// come with a hash (e.g. those of an envelope)
+    pub(crate) fn check_params(
+        &self,
+        iterations: u32,
+        lanes: u32,
+        memory_size: u32,
+        salt_len: usize,
+    ) -> Result<(), Error> {
+        check_limit("Iterations", iterations.into(), self.max_iterations)?;
+        check_limit("Lanes", lanes.into(), self.max_lanes)?;
+        check_limit("Memory size", memory_size.into(), self.max_memory_size)?;
+        check_limit("Salt length", salt_len as u64, self.max_salt_len)?;
+        Ok(())
+    }
+    #[allow(missing_docs)]
+    pub fn max_hash_len(&self) -> u32 {
+        self.max_hash_len
+    }
+    #[allow(missing_docs)]
+    pub fn max_iterations(&self) -> u32 {
+        self.max_iterations
+    }
+    #[allow(missing_docs)]
+    pub fn max_lanes(&self) -> u32 {
+        self.max_lanes
+    }
+    #[allow(missing_docs)]
+    pub fn max_memory_size(&self) -> u32 {
+        self.max_memory_size
+    }
+    #[allow(missing_docs)]
+    pub fn max_salt_len(&self) -> u32 {
+        self.max_salt_len
+    }
+}
+
+fn check_limit(name: &str, value: u64, max: u32) -> Result<(), Error> {
+    if value > u64::from(max) {
+        return Err(Error::new(ErrorKind::VerifierPolicyError)
+            .add_context(format!("{}: {}. Maximum: {}", name, value, max)));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check() {
+        let hash_raw = "$argon2id$v=19$m=4096,t=192,p=4$c29tZXNhbHQ$aGFzaGhhc2hoYXNoaGFzaA"
+            .parse::<HashRaw>()
+            .unwrap();
+        assert!(VerifierPolicy::default().check(&hash_raw).is_ok());
+        assert!(VerifierPolicy::unlimited().check(&hash_raw).is_ok());
+
+        let mut policy = VerifierPolicy::new();
+        policy.configure_max_memory_size(4096);
+        assert!(policy.check(&hash_raw).is_ok());
+        policy.configure_max_memory_size(2048);
+        let err = policy.check(&hash_raw).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::VerifierPolicyError);
+
+        let policies = vec![
+            *VerifierPolicy::new().configure_max_hash_len(15),
+            *VerifierPolicy::new().configure_max_iterations(191),
+            *VerifierPolicy::new().configure_max_lanes(3),
+            *VerifierPolicy::new().configure_max_salt_len(7),
+        ];
+        for policy in policies {
+            let err = policy.check(&hash_raw).unwrap_err();
+            assert_eq!(err.kind(), ErrorKind::VerifierPolicyError);
+        }
+    }
+
+    #[test]
+    fn test_send() {
+        fn assert_send<T: Send>() {}
+        assert_send::<VerifierPolicy>();
+    }
+
+    #[test]
+    fn test_sync() {
+        fn assert_sync<T: Sync>() {}
+        assert_sync::<VerifierPolicy>();
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serialize() {
+        use serde;
+        fn assert_serialize<T: serde::Serialize>() {}
+        assert_serialize::<VerifierPolicy>();
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_deserialize() {
+        use serde;
+        fn assert_deserialize<'de, T: serde::Deserialize<'de>>() {}
+        assert_deserialize::<VerifierPolicy>();
+    }
+}
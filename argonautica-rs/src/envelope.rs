@@ -0,0 +1,484 @@
+//! Passphrase-based authenticated encryption (see [`Envelope`](struct.Envelope.html)).
+//! Requires the `envelope` feature.
+//!
+//! An envelope is a byte payload encrypted and authenticated with a key derived from a
+//! passphrase with Argon2id (see the [`kdf`](../kdf/index.html) module), using the
+//! XChaCha20-Poly1305 AEAD cipher. It is self-describing: it carries everything needed to
+//! open it again besides the passphrase, i.e. the Argon2 parameters, the salt and the nonce.
+//!
+//! ```
+//! extern crate argonautica;
+//!
+//! use argonautica::envelope::Envelope;
+//! use argonautica::ErrorKind;
+//!
+//! fn main() {
+//!     let mut envelope = Envelope::default();
+//!     envelope.configure_iterations(2).configure_memory_size(1024);
+//!     let sealed = envelope.seal("P@ssw0rd", b"attack at dawn").unwrap();
+//!     assert_eq!(&envelope.open("P@ssw0rd", &sealed).unwrap()[..], b"attack at dawn");
+//!
+//!     let err = envelope.open("P@ssw0rd2", &sealed).unwrap_err();
+//!     assert_eq!(err.kind(), ErrorKind::EnvelopePassphraseError);
+//! }
+//! ```
+//!
+//! # Format
+//!
+//! All integers are little-endian. Everything up to and including the nonce is the header,
+//! which is authenticated along with the payload.
+//!
+//! | Field | Size (in bytes) |
+//! | --- | --- |
+//! | Magic bytes (`AENV`) | 4 |
+//! | Format version (`1`) | 1 |
+//! | Cipher (`1`, i.e. XChaCha20-Poly1305) | 1 |
+//! | Argon2 variant (`0`: Argon2d, `1`: Argon2i, `2`: Argon2id) | 1 |
+//! | Argon2 version (`16` or `19`) | 1 |
+//! | Memory size (in kibibytes) | 4 |
+//! | Iterations | 4 |
+//! | Lanes | 4 |
+//! | Salt length | 1 |
+//! | Salt | salt length |
+//! | Passphrase check | 16 |
+//! | Nonce | 24 |
+//! | Encrypted payload followed by the Poly1305 tag | payload length + 16 |
+//!
+//! The key derivation produces a 32-byte encryption key and a 16-byte passphrase check,
+//! labelled `encryption` and `check` (see
+//! [`KdfParams::derive_subkeys`](../kdf/struct.KdfParams.html#method.derive_subkeys)).
+//! The passphrase check tells a wrong passphrase apart from a payload that was tampered with.
+
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+
+use config::{
+    default_lanes, default_memory_size, Variant, VerifierPolicy, Version, DEFAULT_ITERATIONS,
+};
+use input::Password;
+use kdf::{KdfParams, Subkeys};
+use utils::{constant_time_eq, generate_random_bytes};
+use {Error, ErrorKind};
+
+const MAGIC: &[u8; 4] = b"AENV";
+const FORMAT_VERSION: u8 = 1;
+const CIPHER_XCHACHA20_POLY1305: u8 = 1;
+
+const CHECK_LEN: usize = 16;
+const KEY_LEN: usize = 32;
+const NONCE_LEN: usize = 24;
+const SALT_LEN: usize = 32;
+const TAG_LEN: usize = 16;
+
+const CHECK_LABEL: &str = "check";
+const KEY_LABEL: &str = "encryption";
+
+/// Seals a payload with `passphrase` using the default [`Envelope`](struct.Envelope.html)
+pub fn seal<'a, P>(passphrase: P, plaintext: &[u8]) -> Result<Vec<u8>, Error>
+where
+    P: Into<Password<'a>>,
+{
+    Envelope::default().seal(passphrase, plaintext)
+}
+
+/// Opens an envelope with `passphrase` using the default [`Envelope`](struct.Envelope.html)
+pub fn open<'a, P>(passphrase: P, envelope: &[u8]) -> Result<Vec<u8>, Error>
+where
+    P: Into<Password<'a>>,
+{
+    Envelope::default().open(passphrase, envelope)
+}
+
+impl Default for Envelope {
+    /// Same as the [`new`](struct.Envelope.html#method.new) method
+    fn default() -> Envelope {
+        Envelope {
+            iterations: DEFAULT_ITERATIONS,
+            lanes: default_lanes(),
+            memory_size: default_memory_size(),
+            policy: VerifierPolicy::default(),
+        }
+    }
+}
+
+/// Seals and opens envelopes (see the [module documentation](index.html)).
+///
+/// The Argon2 parameters configure sealing; an envelope is always opened with the
+/// parameters it carries, as long as they are within the
+/// [`VerifierPolicy`](../config/struct.VerifierPolicy.html) (see
+/// [`configure_policy`](struct.Envelope.html#method.configure_policy)), so that a malicious
+/// envelope cannot make opening it arbitrarily expensive
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Envelope {
+    iterations: u32,
+    lanes: u32,
+    memory_size: u32,
+    policy: VerifierPolicy,
+}
+
+impl Envelope {
+    /// Creates a new [`Envelope`](struct.Envelope.html) with the same Argon2 defaults as
+    /// [`Hasher`](../struct.Hasher.html) and the default
+    /// [`VerifierPolicy`](../config/struct.VerifierPolicy.html)
+    pub fn new() -> Envelope {
+        Envelope::default()
+    }
+    /// Allows you to configure the number of iterations used for sealing
+    pub fn configure_iterations(&mut self, iterations: u32) -> &mut Envelope {
+        self.iterations = iterations;
+        self
+    }
+    /// Allows you to configure the number of lanes used for sealing
+    pub fn configure_lanes(&mut self, lanes: u32) -> &mut Envelope {
+        self.lanes = lanes;
+        self
+    }
+    /// Allows you to configure the memory size (in kibibytes) used for sealing
+    pub fn configure_memory_size(&mut self, memory_size: u32) -> &mut Envelope {
+        self.memory_size = memory_size;
+        self
+    }
+    /// Allows you to configure the limits on the Argon2 parameters of the envelopes you open
+    pub fn configure_policy(&mut self, policy: VerifierPolicy) -> &mut Envelope {
+        self.policy = policy;
+        self
+    }
+    /// Encrypts and authenticates `plaintext` with a key derived from `passphrase` and a new,
+    /// random salt, and returns the envelope
+    pub fn seal<'a, P>(&self, passphrase: P, plaintext: &[u8]) -> Result<Vec<u8>, Error>
+    where
+        P: Into<Password<'a>>,
+    {
+        let mut params = KdfParams::new(generate_random_bytes(SALT_LEN as u32)?);
+        params
+            .configure_iterations(self.iterations)
+            .configure_lanes(self.lanes)
+            .configure_memory_size(self.memory_size)
+            .configure_variant(Variant::Argon2id)
+            .configure_version(Version::_0x13);
+        let nonce = generate_random_bytes(NONCE_LEN as u32)?;
+        let subkeys = derive_subkeys(&params, passphrase)?;
+
+        let mut envelope = Vec::with_capacity(
+            MAGIC.len() + 17 + SALT_LEN + CHECK_LEN + NONCE_LEN + plaintext.len() + TAG_LEN,
+        );
+        envelope.extend_from_slice(MAGIC);
+        envelope.push(FORMAT_VERSION);
+        envelope.push(CIPHER_XCHACHA20_POLY1305);
+        envelope.push(params.variant() as u8);
+        envelope.push(params.version() as u8);
+        envelope.extend_from_slice(&params.memory_size().to_le_bytes());
+        envelope.extend_from_slice(&params.iterations().to_le_bytes());
+        envelope.extend_from_slice(&params.lanes().to_le_bytes());
+        envelope.push(SALT_LEN as u8);
+        envelope.extend_from_slice(params.salt());
+        envelope.extend_from_slice(subkeys.get(CHECK_LABEL).unwrap());
+        envelope.extend_from_slice(&nonce);
+
+        let ciphertext = cipher(subkeys.get(KEY_LABEL).unwrap())
+            .encrypt(
+                XNonce::from_slice(&nonce),
+                Payload {
+                    msg: plaintext,
+                    aad: &envelope,
+                },
+            )
+            .map_err(|_| Error::new(ErrorKind::Bug).add_context("Failed to encrypt"))?;
+        envelope.extend_from_slice(&ciphertext);
+        Ok(envelope)
+    }
+    /// Authenticates and decrypts `envelope` with a key derived from `passphrase`, and returns
+    /// the payload. Returns an [`Error`](../struct.Error.html) of kind:
+    /// * [`EnvelopeDecodeError`](../enum.ErrorKind.html#variant.EnvelopeDecodeError) if the
+    ///   envelope is malformed
+    /// * [`VerifierPolicyError`](../enum.ErrorKind.html#variant.VerifierPolicyError) if its
+    ///   Argon2 parameters exceed the policy
+    /// * [`EnvelopePassphraseError`](../enum.ErrorKind.html#variant.EnvelopePassphraseError)
+    ///   if the passphrase is wrong (or the Argon2 parameters or salt were tampered with,
+    ///   which cannot be told apart from a wrong passphrase)
+    /// * [`EnvelopeTamperedError`](../enum.ErrorKind.html#variant.EnvelopeTamperedError) if
+    ///   the passphrase is right but the envelope was tampered with
+    pub fn open<'a, P>(&self, passphrase: P, envelope: &[u8]) -> Result<Vec<u8>, Error>
+    where
+        P: Into<Password<'a>>,
+    {
+        let header = Header::decode(envelope)?;
+        self.policy.check_params(
+            header.params.iterations(),
+            header.params.lanes(),
+            header.params.memory_size(),
+            header.params.salt().len(),
+        )?;
+        let subkeys = derive_subkeys(&header.params, passphrase)?;
+        if !constant_time_eq(subkeys.get(CHECK_LABEL).unwrap(), header.check) {
+            return Err(Error::new(ErrorKind::EnvelopePassphraseError));
+        }
+        cipher(subkeys.get(KEY_LABEL).unwrap())
+            .decrypt(
+                XNonce::from_slice(header.nonce),
+                Payload {
+                    msg: &envelope[header.len..],
+                    aad: &envelope[..header.len],
+                },
+            )
+            .map_err(|_| Error::new(ErrorKind::EnvelopeTamperedError))
+    }
+    #[allow(missing_docs)]
+    pub fn iterations(&self) -> u32 {
+        self.iterations
+    }
+    #[allow(missing_docs)]
+    pub fn lanes(&self) -> u32 {
+        self.lanes
+    }
+    #[allow(missing_docs)]
+    pub fn memory_size(&self) -> u32 {
+        self.memory_size
+    }
+    #[allow(missing_docs)]
+    pub fn policy(&self) -> &VerifierPolicy {
+        &self.policy
+    }
+}
+
+/// The decoded header of an envelope
+struct Header<'a> {
+    check: &'a [u8],
+    /// The length of the header (in bytes), i.e. the offset of the encrypted payload
+    len: usize,
+    nonce: &'a [u8],
+    params: KdfParams,
+}
+
+impl<'a> Header<'a> {
+    fn decode(envelope: &'a [u8]) -> Result<Header<'a>, Error> {
+        let mut reader = Reader {
+            bytes: envelope,
+            pos: 0,
+        };
+        if reader.take(MAGIC.len())? != MAGIC {
+            return Err(decode_error("not an envelope"));
+        }
+        let format_version = reader.u8()?;
+        if format_version != FORMAT_VERSION {
+            return Err(decode_error(&format!(
+                "unsupported format version {}",
+                format_version
+            )));
+        }
+        let cipher = reader.u8()?;
+        if cipher != CIPHER_XCHACHA20_POLY1305 {
+            return Err(decode_error(&format!("unsupported cipher {}", cipher)));
+        }
+        let variant = match reader.u8()? {
+            0 => Variant::Argon2d,
+            1 => Variant::Argon2i,
+            2 => Variant::Argon2id,
+            variant => return Err(decode_error(&format!("invalid variant {}", variant))),
+        };
+        let version = reader.u8()?;
+        let version = Version::from_u32(version.into())
+            .map_err(|_| decode_error(&format!("invalid version {}", version)))?;
+        let memory_size = reader.u32()?;
+        let iterations = reader.u32()?;
+        let lanes = reader.u32()?;
+        let salt_len = reader.u8()?;
+        let salt = reader.take(salt_len.into())?;
+        let check = reader.take(CHECK_LEN)?;
+        let nonce = reader.take(NONCE_LEN)?;
+        if envelope.len() - reader.pos < TAG_LEN {
+            return Err(decode_error("truncated"));
+        }
+        let mut params = KdfParams::new(salt);
+        params
+            .configure_iterations(iterations)
+            .configure_lanes(lanes)
+            .configure_memory_size(memory_size)
+            .configure_variant(variant)
+            .configure_version(version);
+        Ok(Header {
+            check,
+            len: reader.pos,
+            nonce,
+            params,
+        })
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], Error> {
+        if self.bytes.len() - self.pos < len {
+            return Err(decode_error("truncated"));
+        }
+        let bytes = &self.bytes[self.pos..self.pos + len];
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> Result<u8, Error> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u32(&mut self) -> Result<u32, Error> {
+        let mut bytes = [0u8; 4];
+        bytes.copy_from_slice(self.take(4)?);
+        Ok(u32::from_le_bytes(bytes))
+    }
+}
+
+fn decode_error(message: &str) -> Error {
+    Error::new(ErrorKind::EnvelopeDecodeError).add_context(format!("Envelope is {}", message))
+}
+
+/// Derives the encryption key and the passphrase check
+fn derive_subkeys<'a, P>(params: &KdfParams, passphrase: P) -> Result<Subkeys, Error>
+where
+    P: Into<Password<'a>>,
+{
+    params.derive_subkeys(
+        passphrase,
+        &[(KEY_LABEL, KEY_LEN as u32), (CHECK_LABEL, CHECK_LEN as u32)],
+    )
+}
+
+fn cipher(key: &[u8]) -> XChaCha20Poly1305 {
+    XChaCha20Poly1305::new(Key::from_slice(key))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn envelope() -> Envelope {
+        let mut envelope = Envelope::default();
+        envelope
+            .configure_iterations(2)
+            .configure_lanes(2)
+            .configure_memory_size(64);
+        envelope
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let envelope = envelope();
+        for plaintext in &[&b""[..], &b"attack at dawn"[..], &[0xFFu8; 1000][..]] {
+            let sealed = envelope.seal("P@ssw0rd", plaintext).unwrap();
+            assert_eq!(
+                sealed.len(),
+                21 + SALT_LEN + CHECK_LEN + NONCE_LEN + plaintext.len() + TAG_LEN
+            );
+            assert_eq!(&envelope.open("P@ssw0rd", &sealed).unwrap()[..], *plaintext);
+            // Random salt and nonce
+            assert_ne!(envelope.seal("P@ssw0rd", plaintext).unwrap(), sealed);
+        }
+    }
+
+    #[test]
+    fn test_opens_with_envelope_params() {
+        let sealed = envelope().seal("P@ssw0rd", b"payload").unwrap();
+        let mut other = envelope();
+        other.configure_iterations(3).configure_memory_size(128);
+        assert_eq!(&other.open("P@ssw0rd", &sealed).unwrap()[..], b"payload");
+    }
+
+    #[test]
+    fn test_wrong_passphrase() {
+        let envelope = envelope();
+        let sealed = envelope.seal("P@ssw0rd", b"payload").unwrap();
+        let err = envelope.open("P@ssw0rd2", &sealed).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::EnvelopePassphraseError);
+    }
+
+    #[test]
+    fn test_tampered() {
+        let envelope = envelope();
+        let sealed = envelope.seal("P@ssw0rd", b"payload").unwrap();
+        let header_len = sealed.len() - b"payload".len() - TAG_LEN;
+        // Nonce, ciphertext and tag
+        for i in (header_len - NONCE_LEN)..sealed.len() {
+            let mut tampered = sealed.clone();
+            tampered[i] ^= 1;
+            let err = envelope.open("P@ssw0rd", &tampered).unwrap_err();
+            assert_eq!(err.kind(), ErrorKind::EnvelopeTamperedError);
+        }
+        // Salt
+        let mut tampered = sealed.clone();
+        tampered[25] ^= 1;
+        let err = envelope.open("P@ssw0rd", &tampered).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::EnvelopePassphraseError);
+        // Truncated payload
+        let err = envelope
+            .open("P@ssw0rd", &sealed[..sealed.len() - 1])
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::EnvelopeTamperedError);
+    }
+
+    #[test]
+    fn test_decode_error() {
+        let envelope = envelope();
+        let sealed = envelope.seal("P@ssw0rd", b"payload").unwrap();
+        let mut bad_magic = sealed.clone();
+        bad_magic[0] = b'X';
+        let mut bad_format_version = sealed.clone();
+        bad_format_version[4] = 2;
+        let mut bad_cipher = sealed.clone();
+        bad_cipher[5] = 2;
+        let mut bad_variant = sealed.clone();
+        bad_variant[6] = 3;
+        let mut bad_version = sealed.clone();
+        bad_version[7] = 0x12;
+        let mut bad_salt_len = sealed.clone();
+        bad_salt_len[20] = 0xFF;
+        for bytes in &[
+            &b""[..],
+            &b"garbage"[..],
+            &sealed[..40],
+            &sealed[..sealed.len() - b"payload".len() - 1],
+            &bad_magic[..],
+            &bad_format_version[..],
+            &bad_cipher[..],
+            &bad_variant[..],
+            &bad_version[..],
+            &bad_salt_len[..],
+        ] {
+            let err = envelope.open("P@ssw0rd", bytes).unwrap_err();
+            assert_eq!(err.kind(), ErrorKind::EnvelopeDecodeError);
+        }
+    }
+
+    #[test]
+    fn test_policy() {
+        let sealed = envelope().seal("P@ssw0rd", b"payload").unwrap();
+        let mut policy = VerifierPolicy::default();
+        policy.configure_max_memory_size(32);
+        let err = envelope()
+            .configure_policy(policy)
+            .open("P@ssw0rd", &sealed)
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::VerifierPolicyError);
+
+        // A forged memory size is rejected before any work is done
+        let mut forged = sealed.clone();
+        forged[8..12].copy_from_slice(&u32::MAX.to_le_bytes());
+        let err = envelope().open("P@ssw0rd", &forged).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::VerifierPolicyError);
+    }
+
+    #[test]
+    fn test_send() {
+        fn assert_send<T: Send>() {}
+        assert_send::<Envelope>();
+    }
+
+    #[test]
+    fn test_sync() {
+        fn assert_sync<T: Sync>() {}
+        assert_sync::<Envelope>();
+    }
+}
@@ -7,12 +7,19 @@ pub enum ErrorKind {
     #[fail(display = "Additional data too long. Length in bytes must be less than 2^32")]
     AdditionalDataTooLongError,
 
+    /// Audit input error. The hashes to audit could not be read, or the CSV column that holds
+    /// them could not be found
+    #[fail(
+        display = "Audit input error. The hashes to audit could not be read, or the CSV column that holds them could not be found"
+    )]
+    AuditInputError,
+
     /// Backend encode error. u32 provided could not be encoded into a Backend
     #[fail(display = "Backend encode error. u32 provided could not be encoded into a Backend")]
     BackendEncodeError,
 
-    /// Rust backend not yet supported. Please use the C backend
-    #[fail(display = "Rust backend not yet supported. Please use the C backend")]
+    /// Backend not supported. The C backend requires the backend-c feature
+    #[fail(display = "Backend not supported. The C backend requires the backend-c feature")]
     BackendUnsupportedError,
 
     /// Base64 decode error. Bytes provided were invalid base64
@@ -25,6 +32,27 @@ pub enum ErrorKind {
     )]
     Bug,
 
+    /// Envelope decode error. The envelope is malformed, truncated or uses an unsupported
+    /// format version
+    #[fail(
+        display = "Envelope decode error. The envelope is malformed, truncated or uses an unsupported format version"
+    )]
+    EnvelopeDecodeError,
+
+    /// Envelope passphrase error. The passphrase does not match the one the envelope was
+    /// sealed with (or the envelope's key derivation parameters were tampered with)
+    #[fail(
+        display = "Envelope passphrase error. The passphrase does not match the one the envelope was sealed with"
+    )]
+    EnvelopePassphraseError,
+
+    /// Envelope tampered error. The passphrase is correct, but the envelope's contents fail
+    /// authentication, i.e. they were modified after the envelope was sealed
+    #[fail(
+        display = "Envelope tampered error. The passphrase is correct, but the envelope's contents fail authentication"
+    )]
+    EnvelopeTamperedError,
+
     /// Hash decode error. Hash provided was invalid
     #[fail(display = "Hash decode error. Hash provided was invalid")]
     HashDecodeError,
@@ -46,6 +74,21 @@ pub enum ErrorKind {
     #[fail(display = "Iterations must be greater than 0")]
     IterationsTooFewError,
 
+    /// KDF subkeys invalid. Subkey labels must be unique and subkey lengths must be greater
+    /// than 0
+    #[fail(
+        display = "KDF subkeys invalid. Subkey labels must be unique and subkey lengths must be greater than 0"
+    )]
+    KdfSubkeysInvalidError,
+
+    /// Key id invalid. Key ids must be between 1 and 8 bytes long
+    #[fail(display = "Key id invalid. Key ids must be between 1 and 8 bytes long")]
+    KeyIdInvalidError,
+
+    /// Key id unknown. The key id is not in the keyring
+    #[fail(display = "Key id unknown. The key id is not in the keyring")]
+    KeyIdUnknownError,
+
     /// Lanes too few. Lanes must be greater than 0
     #[fail(display = "Lanes must be greater than 0")]
     LanesTooFewError,
@@ -58,6 +101,20 @@ pub enum ErrorKind {
     #[fail(display = "C code attempted to allocate memory (using malloc) and failed")]
     MemoryAllocationError,
 
+    /// Memory budget exceeded. The memory size of the hash does not fit within the memory
+    /// budget (see `MemoryBudget`)
+    #[fail(
+        display = "Memory budget exceeded. The memory size of the hash does not fit within the memory budget"
+    )]
+    MemoryBudgetExceededError,
+
+    /// Memory lock error. Failed to lock memory into RAM, typically because the process's
+    /// RLIMIT_MEMLOCK limit would be exceeded or because the platform does not support it
+    #[fail(
+        display = "Memory lock error. Failed to lock memory into RAM, typically because the process's RLIMIT_MEMLOCK limit would be exceeded or because the platform does not support it"
+    )]
+    MemoryLockError,
+
     /// Memory size invalid. Memory size must be a power of two
     #[fail(display = "Memory size invalid. Memory size must be a power of two")]
     MemorySizeInvalidError,
@@ -136,6 +193,13 @@ pub enum ErrorKind {
     #[fail(display = "Variant encode error. &str provided could not be encoded into a Variant")]
     VariantEncodeError,
 
+    /// Verifier policy error. The parameters of the hash provided exceed the limits of the
+    /// verifier's policy (see `VerifierPolicy`)
+    #[fail(
+        display = "Verifier policy error. The parameters of the hash provided exceed the limits of the verifier's policy"
+    )]
+    VerifierPolicyError,
+
     /// Version encode error. &str or u32 provided could not be encoded into a Version
     #[fail(
         display = "Version encode error. &str or u32 provided could not be encoded into a Version"
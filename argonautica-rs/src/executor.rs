@@ -0,0 +1,682 @@
+//! Executors for the async methods of [`Hasher`](../struct.Hasher.html) and
+//! [`Verifier`](../struct.Verifier.html) (e.g.
+//! [`hash_async`](../struct.Hasher.html#method.hash_async) and
+//! [`verify_async`](../struct.Verifier.html#method.verify_async)).
+//!
+//! Hashing is CPU-bound and takes hundreds of milliseconds, so it must not run on the threads
+//! of an async runtime. The async methods hand the work to a
+//! [`BlockingExecutor`](trait.BlockingExecutor.html) instead and immediately return a
+//! [`Task`](struct.Task.html), which is a
+//! [`std::future::Future`](https://doc.rust-lang.org/std/future/trait.Future.html) that
+//! resolves once the work is done. Two executors are provided: a built-in
+//! [`ThreadPool`](struct.ThreadPool.html), which is the default, and (with the `tokio`
+//! feature) [`TokioExecutor`](struct.TokioExecutor.html), which runs the work on tokio's
+//! blocking thread pool. You can also implement
+//! [`BlockingExecutor`](trait.BlockingExecutor.html) for the executor of your choice.
+//!
+//! Unless configured otherwise, the async and non-blocking methods share a single, lazily
+//! created, process-wide [`ThreadPool`](struct.ThreadPool.html) (see
+//! [`global_thread_pool`](fn.global_thread_pool.html)). Its size can be set once at startup
+//! with [`configure_global_thread_pool`](fn.configure_global_thread_pool.html), and it can be
+//! shut down gracefully with [`shutdown_global_thread_pool`](fn.shutdown_global_thread_pool.html).
+//!
+//! The batch methods ([`Hasher::hash_batch`](../struct.Hasher.html#method.hash_batch) and
+//! [`Verifier::verify_batch`](../struct.Verifier.html#method.verify_batch)) run on the
+//! configured executor too, several jobs at a time, and return a [`Batch`](struct.Batch.html)
+//! that yields the results in order
+use std::collections::VecDeque;
+use std::fmt;
+use std::future::Future;
+use std::panic::{self, AssertUnwindSafe};
+use std::pin::Pin;
+use std::sync::mpsc::{self, Receiver};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::task::{Context, Poll, Waker};
+use std::thread;
+
+use futures::sync::oneshot;
+use futures::Future as Future01;
+#[cfg(feature = "tokio")]
+use tokio::runtime::Handle;
+
+use config::environment;
+use {Error, ErrorKind};
+
+/// A unit of work handed to a [`BlockingExecutor`](trait.BlockingExecutor.html)
+pub type Job = Box<dyn FnOnce() + Send + 'static>;
+
+/// Something that can run jobs that block (i.e. hash passwords) without blocking the threads
+/// of an async runtime. Implement this trait to run the async methods of
+/// [`Hasher`](../struct.Hasher.html) and [`Verifier`](../struct.Verifier.html) on an executor
+/// of your choice
+pub trait BlockingExecutor: fmt::Debug + Send + Sync {
+    /// Runs `job` to completion on a thread where blocking is acceptable. `job` does not
+    /// return anything; its result is delivered through the [`Task`](struct.Task.html)
+    /// returned by the async method that created it. If `job` is dropped without being run,
+    /// that [`Task`](struct.Task.html) resolves to an [`Error`](../struct.Error.html)
+    fn spawn_blocking(&self, job: Job);
+}
+
+/// A [`Future`](https://doc.rust-lang.org/std/future/trait.Future.html) that resolves to the
+/// result of a job running on a [`BlockingExecutor`](trait.BlockingExecutor.html). Returned by
+/// the async methods of [`Hasher`](../struct.Hasher.html) and
+/// [`Verifier`](../struct.Verifier.html). Dropping it does not cancel the job
+pub struct Task<T> {
+    shared: Arc<Mutex<Shared<T>>>,
+}
+
+struct Shared<T> {
+    done: bool,
+    result: Option<Result<T, Error>>,
+    waker: Option<Waker>,
+}
+
+impl<T> Future for Task<T> {
+    type Output = Result<T, Error>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        let mut shared = self.shared.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(result) = shared.result.take() {
+            return Poll::Ready(result);
+        }
+        if shared.done {
+            return Poll::Ready(Err(Error::new(ErrorKind::Bug).add_context(
+                "The job was dropped by the executor before it completed (did it panic?)",
+            )));
+        }
+        shared.waker = Some(cx.waker().clone());
+        Poll::Pending
+    }
+}
+
+impl<T> fmt::Debug for Task<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let done = self.shared.lock().map(|shared| shared.done).unwrap_or(true);
+        f.debug_struct("Task").field("done", &done).finish()
+    }
+}
+
+/// Completes a [`Task`](struct.Task.html) when the job it belongs to finishes or is dropped
+struct Completion<T> {
+    shared: Arc<Mutex<Shared<T>>>,
+}
+
+impl<T> Completion<T> {
+    fn complete(self, result: Result<T, Error>) {
+        let mut shared = self.shared.lock().unwrap_or_else(|e| e.into_inner());
+        shared.result = Some(result);
+    }
+}
+
+impl<T> Drop for Completion<T> {
+    fn drop(&mut self) {
+        let mut shared = self.shared.lock().unwrap_or_else(|e| e.into_inner());
+        shared.done = true;
+        if let Some(waker) = shared.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+/// Runs `f` on `executor`, returning a [`Task`](struct.Task.html) that resolves to its result
+pub(crate) fn spawn<T, F>(executor: &dyn BlockingExecutor, f: F) -> Task<T>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T, Error> + Send + 'static,
+{
+    let shared = Arc::new(Mutex::new(Shared {
+        done: false,
+        result: None,
+        waker: None,
+    }));
+    let completion = Completion {
+        shared: shared.clone(),
+    };
+    executor.spawn_blocking(Box::new(move || completion.complete(f())));
+    Task { shared }
+}
+
+/// Runs `f` on `executor`, returning a [futures 0.1](https://docs.rs/futures/0.1.21/futures/)
+/// future that resolves to its result
+pub(crate) fn spawn_non_blocking<T, F>(
+    executor: &dyn BlockingExecutor,
+    f: F,
+) -> impl Future01<Item = T, Error = Error>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T, Error> + Send + 'static,
+{
+    let (sender, receiver) = oneshot::channel();
+    executor.spawn_blocking(Box::new(move || {
+        let _ = sender.send(f());
+    }));
+    receiver.then(|result| match result {
+        Ok(result) => result,
+        Err(oneshot::Canceled) => Err(Error::new(ErrorKind::Bug).add_context(
+            "The job was dropped by the executor before it completed (did it panic?)",
+        )),
+    })
+}
+
+/// A job of a [`Batch`](struct.Batch.html)
+pub(crate) type BatchJob<T> = Box<dyn FnOnce() -> Result<T, Error> + Send + 'static>;
+
+/// An iterator over the results of the batch methods of [`Hasher`](../struct.Hasher.html) and
+/// [`Verifier`](../struct.Verifier.html) (e.g.
+/// [`hash_batch`](../struct.Hasher.html#method.hash_batch)), one per input and in the same order
+/// as the inputs, whatever order the jobs complete in.
+///
+/// Inputs are consumed lazily: up to a fixed number of jobs run on the executor ahead of the
+/// result being waited for, and the next input is only taken once a result has been yielded,
+/// so a batch can stream through many more inputs than fit in memory. Each call to `next`
+/// blocks until the next result is ready. Dropping a [`Batch`](struct.Batch.html) does not
+/// cancel the jobs already running, but no further inputs are taken
+pub struct Batch<'b, T> {
+    executor: Arc<dyn BlockingExecutor>,
+    jobs: Box<dyn Iterator<Item = BatchJob<T>> + 'b>,
+    parallelism: usize,
+    pending: VecDeque<Receiver<Result<T, Error>>>,
+}
+
+impl<'b, T: Send + 'static> Batch<'b, T> {
+    /// The maximum number of jobs the [`Batch`](struct.Batch.html) runs at a time
+    pub fn parallelism(&self) -> usize {
+        self.parallelism
+    }
+}
+
+impl<'b, T: Send + 'static> Iterator for Batch<'b, T> {
+    type Item = Result<T, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.pending.len() < self.parallelism {
+            let job = match self.jobs.next() {
+                Some(job) => job,
+                None => break,
+            };
+            let (sender, receiver) = mpsc::sync_channel(1);
+            self.executor.spawn_blocking(Box::new(move || {
+                let _ = sender.send(job());
+            }));
+            self.pending.push_back(receiver);
+        }
+        let receiver = self.pending.pop_front()?;
+        Some(receiver.recv().unwrap_or_else(|_| {
+            Err(Error::new(ErrorKind::Bug).add_context(
+                "The job was dropped by the executor before it completed (did it panic?)",
+            ))
+        }))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (lower, upper) = self.jobs.size_hint();
+        let pending = self.pending.len();
+        (
+            lower.saturating_add(pending),
+            upper.and_then(|upper| upper.checked_add(pending)),
+        )
+    }
+}
+
+impl<'b, T> fmt::Debug for Batch<'b, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Batch")
+            .field("executor", &self.executor)
+            .field("parallelism", &self.parallelism)
+            .field("pending", &self.pending.len())
+            .finish()
+    }
+}
+
+/// Returns a [`Batch`](struct.Batch.html) that runs `jobs` on `executor`, at most
+/// `parallelism` (at least one) at a time
+pub(crate) fn spawn_batch<'b, T, I>(
+    executor: Arc<dyn BlockingExecutor>,
+    parallelism: usize,
+    jobs: I,
+) -> Batch<'b, T>
+where
+    I: Iterator<Item = BatchJob<T>> + 'b,
+{
+    Batch {
+        executor,
+        jobs: Box::new(jobs),
+        parallelism: parallelism.max(1),
+        pending: VecDeque::new(),
+    }
+}
+
+lazy_static! {
+    static ref GLOBAL_THREAD_POOL: Mutex<GlobalThreadPool> = Mutex::new(GlobalThreadPool {
+        max_threads: environment().cpus(),
+        thread_pool: None,
+    });
+}
+
+struct GlobalThreadPool {
+    max_threads: usize,
+    thread_pool: Option<ThreadPool>,
+}
+
+/// Sets the maximum number of threads of the process-wide
+/// [`ThreadPool`](struct.ThreadPool.html) returned by
+/// [`global_thread_pool`](fn.global_thread_pool.html). The default is the number of cores
+/// available to the process (see [`environment`](../config/fn.environment.html)).
+///
+/// The global pool is created the first time it is needed, so call this function once at
+/// startup, before hashing or verifying. Returns `false` (and changes nothing) if the global
+/// pool is already running; the size then only applies once it has been shut down and
+/// recreated (see [`shutdown_global_thread_pool`](fn.shutdown_global_thread_pool.html))
+pub fn configure_global_thread_pool(max_threads: usize) -> bool {
+    let mut global = GLOBAL_THREAD_POOL.lock().unwrap_or_else(|e| e.into_inner());
+    if global.thread_pool.is_some() {
+        return false;
+    }
+    global.max_threads = max_threads;
+    true
+}
+
+/// Returns the process-wide [`ThreadPool`](struct.ThreadPool.html), creating it if needed.
+/// This is the pool that the async and non-blocking methods of
+/// [`Hasher`](../struct.Hasher.html) and [`Verifier`](../struct.Verifier.html) use when they
+/// have not been configured with an executor or a cpu pool of their own. The returned
+/// [`ThreadPool`](struct.ThreadPool.html) shares its threads with the global pool, so you
+/// can, for example, check its [`queue_len`](struct.ThreadPool.html#method.queue_len) or
+/// configure other executors with it
+pub fn global_thread_pool() -> ThreadPool {
+    let mut global = GLOBAL_THREAD_POOL.lock().unwrap_or_else(|e| e.into_inner());
+    let max_threads = global.max_threads;
+    global
+        .thread_pool
+        .get_or_insert_with(|| ThreadPool::new(max_threads))
+        .clone()
+}
+
+/// Shuts down the process-wide [`ThreadPool`](struct.ThreadPool.html) (see
+/// [`ThreadPool::shutdown`](struct.ThreadPool.html#method.shutdown)), blocking until all the
+/// jobs already queued on it have completed. Does nothing if the global pool is not running.
+/// If hashing or verifying needs the global pool again afterwards, a new one is created.
+/// Must not be called from a job running on the global pool
+pub fn shutdown_global_thread_pool() {
+    let thread_pool = GLOBAL_THREAD_POOL
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .thread_pool
+        .take();
+    if let Some(thread_pool) = thread_pool {
+        thread_pool.shutdown();
+    }
+}
+
+/// A simple pool of threads for running blocking jobs. This is the default
+/// [`BlockingExecutor`](trait.BlockingExecutor.html).
+///
+/// Threads are started as needed, up to the maximum the pool was created with, and are shut
+/// down once the pool (and all of its clones, which share the same threads) has been dropped
+/// and all queued jobs have run
+#[derive(Clone)]
+pub struct ThreadPool {
+    inner: Arc<ThreadPoolInner>,
+}
+
+struct ThreadPoolInner {
+    max_threads: usize,
+    pool: Arc<Pool>,
+}
+
+struct Pool {
+    condvar: Condvar,
+    exited: Condvar,
+    state: Mutex<PoolState>,
+}
+
+struct PoolState {
+    idle: usize,
+    jobs: VecDeque<Job>,
+    shutdown: bool,
+    threads: usize,
+}
+
+impl Default for ThreadPool {
+    /// Same as the [`new`](struct.ThreadPool.html#method.new) method with the number of cores
+    /// available to the process (see [`environment`](../config/fn.environment.html))
+    fn default() -> ThreadPool {
+        ThreadPool::new(environment().cpus())
+    }
+}
+
+impl ThreadPool {
+    /// Creates a new [`ThreadPool`](struct.ThreadPool.html) that runs at most `max_threads`
+    /// jobs at a time (at least one)
+    pub fn new(max_threads: usize) -> ThreadPool {
+        ThreadPool {
+            inner: Arc::new(ThreadPoolInner {
+                max_threads: max_threads.max(1),
+                pool: Arc::new(Pool {
+                    condvar: Condvar::new(),
+                    exited: Condvar::new(),
+                    state: Mutex::new(PoolState {
+                        idle: 0,
+                        jobs: VecDeque::new(),
+                        shutdown: false,
+                        threads: 0,
+                    }),
+                }),
+            }),
+        }
+    }
+    /// The maximum number of jobs the [`ThreadPool`](struct.ThreadPool.html) runs at a time
+    pub fn max_threads(&self) -> usize {
+        self.inner.max_threads
+    }
+    /// The number of jobs waiting for a thread, i.e. not counting the jobs that are running
+    pub fn queue_len(&self) -> usize {
+        self.inner.pool.lock().jobs.len()
+    }
+    /// Stops the [`ThreadPool`](struct.ThreadPool.html) (and all of its clones) from accepting
+    /// new jobs and blocks until all the jobs already queued on it have completed and its
+    /// threads have exited. Jobs handed to it afterwards are dropped, so the
+    /// [`Task`](struct.Task.html)s waiting for them resolve to an
+    /// [`Error`](../struct.Error.html). Must not be called from a job running on the
+    /// [`ThreadPool`](struct.ThreadPool.html) itself
+    pub fn shutdown(&self) {
+        let pool = &self.inner.pool;
+        let mut state = pool.lock();
+        state.shutdown = true;
+        pool.condvar.notify_all();
+        while state.threads > 0 {
+            state = pool.exited.wait(state).unwrap_or_else(|e| e.into_inner());
+        }
+    }
+    /// The number of threads the [`ThreadPool`](struct.ThreadPool.html) has started that
+    /// have not exited yet
+    pub fn threads(&self) -> usize {
+        self.inner.pool.lock().threads
+    }
+}
+
+impl Pool {
+    fn lock(&self) -> MutexGuard<'_, PoolState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl BlockingExecutor for ThreadPool {
+    fn spawn_blocking(&self, job: Job) {
+        let pool = &self.inner.pool;
+        let mut state = pool.lock();
+        if state.shutdown {
+            // Dropping the job resolves its task to an error
+            return;
+        }
+        state.jobs.push_back(job);
+        if state.jobs.len() > state.idle && state.threads < self.inner.max_threads {
+            let worker_pool = pool.clone();
+            let spawned = thread::Builder::new()
+                .name(format!("argonautica-worker-{}", state.threads))
+                .spawn(move || work(&worker_pool));
+            // If no thread can be started, the job waits for one of the existing threads
+            if spawned.is_ok() {
+                state.threads += 1;
+            }
+        }
+        pool.condvar.notify_one();
+    }
+}
+
+impl fmt::Debug for ThreadPool {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ThreadPool")
+            .field("max_threads", &self.inner.max_threads)
+            .finish()
+    }
+}
+
+impl Drop for ThreadPoolInner {
+    fn drop(&mut self) {
+        let mut state = self.pool.lock();
+        state.shutdown = true;
+        self.pool.condvar.notify_all();
+    }
+}
+
+fn work(pool: &Pool) {
+    loop {
+        let job = {
+            let mut state = pool.lock();
+            while state.jobs.is_empty() && !state.shutdown {
+                state.idle += 1;
+                state = pool.condvar.wait(state).unwrap_or_else(|e| e.into_inner());
+                state.idle -= 1;
+            }
+            match state.jobs.pop_front() {
+                Some(job) => job,
+                None => {
+                    state.threads -= 1;
+                    pool.exited.notify_all();
+                    return;
+                }
+            }
+        };
+        // A panicking job must not take the thread down with it; its task resolves to an error
+        let _ = panic::catch_unwind(AssertUnwindSafe(job));
+    }
+}
+
+/// A [`BlockingExecutor`](trait.BlockingExecutor.html) that runs jobs on the blocking thread
+/// pool of a [tokio](https://tokio.rs) runtime, using
+/// [`spawn_blocking`](https://docs.rs/tokio/1/tokio/runtime/struct.Handle.html#method.spawn_blocking).
+/// Requires the `tokio` feature
+#[cfg(feature = "tokio")]
+#[derive(Clone, Debug)]
+pub struct TokioExecutor {
+    handle: Handle,
+}
+
+#[cfg(feature = "tokio")]
+impl TokioExecutor {
+    /// Creates a [`TokioExecutor`](struct.TokioExecutor.html) that runs jobs on the runtime
+    /// `handle` belongs to
+    pub fn new(handle: Handle) -> TokioExecutor {
+        TokioExecutor { handle }
+    }
+    /// Creates a [`TokioExecutor`](struct.TokioExecutor.html) that runs jobs on the runtime
+    /// this method is called from. Panics if it is not called from within a tokio runtime
+    pub fn current() -> TokioExecutor {
+        TokioExecutor::new(Handle::current())
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl BlockingExecutor for TokioExecutor {
+    fn spawn_blocking(&self, job: Job) {
+        // The job delivers its own result, so its join handle is detached
+        drop(self.handle.spawn_blocking(job));
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::sync::mpsc;
+    use std::task::Wake;
+    use std::time::Duration;
+
+    struct ThreadWaker(thread::Thread);
+
+    impl Wake for ThreadWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    /// Polls `future` to completion on the current thread
+    pub(crate) fn block_on<F: Future>(future: F) -> F::Output {
+        let mut future = Box::pin(future);
+        let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+        let mut cx = Context::from_waker(&waker);
+        loop {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                return output;
+            }
+            thread::park();
+        }
+    }
+
+    #[test]
+    fn test_thread_pool() {
+        let thread_pool = ThreadPool::new(2);
+        let tasks = (0..8)
+            .map(|i| spawn(&thread_pool, move || Ok(i * 2)))
+            .collect::<Vec<_>>();
+        let results = tasks.into_iter().map(block_on).collect::<Vec<_>>();
+        for (i, result) in results.into_iter().enumerate() {
+            assert_eq!(result.unwrap(), i * 2);
+        }
+        assert!(thread_pool.threads() >= 1 && thread_pool.threads() <= 2);
+        assert_eq!(thread_pool.queue_len(), 0);
+    }
+
+    #[test]
+    fn test_thread_pool_shutdown_waits_for_queued_jobs() {
+        let thread_pool = ThreadPool::new(1);
+        let (started_sender, started_receiver) = mpsc::channel::<()>();
+        let (sender, receiver) = mpsc::channel::<()>();
+        // The first job blocks the only thread until the second one is queued
+        let first = spawn(&thread_pool, move || {
+            started_sender.send(()).unwrap();
+            receiver.recv().unwrap();
+            Ok(1)
+        });
+        started_receiver.recv().unwrap();
+        let second = spawn(&thread_pool, || Ok(2));
+        assert_eq!(thread_pool.queue_len(), 1);
+        sender.send(()).unwrap();
+        thread_pool.shutdown();
+        assert_eq!(thread_pool.threads(), 0);
+        assert_eq!(block_on(first).unwrap(), 1);
+        assert_eq!(block_on(second).unwrap(), 2);
+
+        // Jobs are rejected after shutdown
+        let err = block_on(spawn(&thread_pool, || Ok(3))).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Bug);
+    }
+
+    #[test]
+    fn test_batch() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let running = Arc::new(AtomicUsize::new(0));
+        let max_running = Arc::new(Mutex::new(0));
+        let jobs = (0..12u64).map(|i| {
+            let running = running.clone();
+            let max_running = max_running.clone();
+            Box::new(move || {
+                let now_running = running.fetch_add(1, Ordering::SeqCst) + 1;
+                {
+                    let mut max_running = max_running.lock().unwrap();
+                    *max_running = now_running.max(*max_running);
+                }
+                // Later jobs finish first
+                thread::sleep(Duration::from_millis(12 - i));
+                running.fetch_sub(1, Ordering::SeqCst);
+                if i % 5 == 4 {
+                    Err(Error::new(ErrorKind::Bug))
+                } else {
+                    Ok(i)
+                }
+            }) as BatchJob<u64>
+        });
+        let batch = spawn_batch(Arc::new(ThreadPool::new(8)), 3, jobs);
+        assert_eq!(batch.parallelism(), 3);
+        assert_eq!(batch.size_hint(), (12, Some(12)));
+        let results = batch.collect::<Vec<_>>();
+        assert_eq!(results.len(), 12);
+        for (i, result) in results.into_iter().enumerate() {
+            match i % 5 {
+                4 => assert_eq!(result.unwrap_err().kind(), ErrorKind::Bug),
+                _ => assert_eq!(result.unwrap(), i as u64),
+            }
+        }
+        assert!(*max_running.lock().unwrap() <= 3);
+
+        // Jobs dropped by the executor yield errors
+        let thread_pool = ThreadPool::new(1);
+        thread_pool.shutdown();
+        let jobs = (0..2).map(|i| Box::new(move || Ok(i)) as BatchJob<i32>);
+        let mut batch = spawn_batch(Arc::new(thread_pool), 0, jobs);
+        assert_eq!(batch.parallelism(), 1);
+        assert_eq!(batch.next().unwrap().unwrap_err().kind(), ErrorKind::Bug);
+        assert_eq!(batch.next().unwrap().unwrap_err().kind(), ErrorKind::Bug);
+        assert!(batch.next().is_none());
+    }
+
+    #[test]
+    fn test_global_thread_pool() {
+        let thread_pool = global_thread_pool();
+        assert!(!configure_global_thread_pool(1));
+        let task = spawn(&thread_pool, || Ok(1));
+        assert_eq!(block_on(task).unwrap(), 1);
+        let future = spawn_non_blocking(&thread_pool, || Ok(2));
+        assert_eq!(future.wait().unwrap(), 2);
+    }
+
+    #[test]
+    fn test_thread_pool_panic() {
+        let thread_pool = ThreadPool::new(1);
+        let task = spawn::<(), _>(&thread_pool, || panic!("boom"));
+        let err = block_on(task).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Bug);
+        // The thread survives the panic
+        let task = spawn(&thread_pool, || Ok(1));
+        assert_eq!(block_on(task).unwrap(), 1);
+    }
+
+    #[test]
+    fn test_thread_pool_shutdown() {
+        let (sender, receiver) = mpsc::channel();
+        let thread_pool = ThreadPool::new(1);
+        let task = spawn(&thread_pool, move || {
+            thread::sleep(Duration::from_millis(10));
+            sender.send(()).unwrap();
+            Ok(())
+        });
+        drop(thread_pool);
+        // Queued jobs still run after the pool is dropped
+        block_on(task).unwrap();
+        receiver.recv().unwrap();
+    }
+
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn test_tokio_executor() {
+        use tokio::runtime::Builder;
+        use Hasher;
+
+        let runtime = Builder::new_current_thread().build().unwrap();
+        let mut hasher = Hasher::default();
+        hasher
+            .configure_executor(TokioExecutor::new(runtime.handle().clone()))
+            .configure_iterations(2)
+            .configure_lanes(1)
+            .configure_memory_size(32)
+            .opt_out_of_secret_key(true)
+            .with_password("P@ssw0rd");
+        let hash = runtime.block_on(hasher.hash_async());
+        assert!(hash.unwrap().starts_with("$argon2id$v=19$m=32,t=2,p=1$"));
+    }
+
+    #[test]
+    fn test_send() {
+        fn assert_send<T: Send>() {}
+        assert_send::<Task<String>>();
+        assert_send::<ThreadPool>();
+    }
+
+    #[test]
+    fn test_sync() {
+        fn assert_sync<T: Sync>() {}
+        assert_sync::<Task<String>>();
+        assert_sync::<ThreadPool>();
+    }
+}
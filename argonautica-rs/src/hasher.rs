@@ -1,11 +1,19 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures::future::Either;
 use futures::Future;
 use futures_cpupool::CpuPool;
 use scopeguard;
 
-use config::defaults::{default_cpu_pool, default_lanes};
-use config::{Backend, HasherConfig, Variant, Version};
-use input::{AdditionalData, Container, Password, Salt, SecretKey};
+use allocator::MemoryAllocator;
+use budget::MemoryBudget;
+use config::defaults::{default_executor, default_lanes};
+use config::{calibrate, Backend, Calibration, HasherConfig, Variant, Version};
+use executor::{self, Batch, BatchJob, BlockingExecutor, Task};
+use input::{AdditionalData, Container, Keyring, Password, Salt, SecretKey};
 use output::HashRaw;
+use utils::wipe;
 use {Error, ErrorKind};
 
 impl<'a> Default for Hasher<'a> {
@@ -14,6 +22,7 @@ impl<'a> Default for Hasher<'a> {
         Hasher {
             additional_data: None,
             config: HasherConfig::default(),
+            keyring: None,
             password: None,
             salt: Salt::default(),
             secret_key: None,
@@ -29,6 +38,8 @@ pub struct Hasher<'a> {
     pub(crate) additional_data: Option<AdditionalData>,
     pub(crate) config: HasherConfig,
     #[cfg_attr(feature = "serde", serde(skip_serializing, skip_deserializing))]
+    pub(crate) keyring: Option<Keyring>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing, skip_deserializing))]
     pub(crate) password: Option<Password<'a>>,
     pub(crate) salt: Salt,
     #[cfg_attr(feature = "serde", serde(skip_serializing, skip_deserializing))]
@@ -44,31 +55,41 @@ impl<'a> Hasher<'a> {
     /// and `memory_size`) until the time it takes to hash a password is approximately 300-500
     /// milliseconds</b>.
     ///
-    /// There is a script in the examples directory that will show you the various configuration
-    /// options for your machine that produce hashing times between 300 and 500 milliseconds
-    /// (Don't forget to run it with the `--release` and `--features="simd"` flags). Alternatively,
-    /// you can clone the repository and run the benchmark suite with
-    /// `cargo bench --features="benches simd" -- inputs`, which will take longer but which runs
+    /// The [`calibrate`](struct.Hasher.html#method.calibrate) method will do this for you by
+    /// measuring your machine and returning a tuned configuration (the `argonautica calibrate`
+    /// command, built with the `cli` feature, calls it and prints the configuration as a
+    /// profile; there is also a script in the examples directory that calls it; don't forget
+    /// to build either with the `--release` flag).
+    /// Alternatively, you can clone the repository and run the benchmark suite with
+    /// `cargo bench --features="benches" -- inputs`, which will take longer but which runs
     /// many iterations for each configuration scenario; so it provides information about
     /// distributions of running time (e.g. mean, 95% confidence intervals, etc.) as opposed
     /// to just point estimates.
     ///
     /// Here are the default configuration options:
-    /// * `backend`: [`Backend::C`](config/enum.Backend.html#variant.C)
-    /// * `cpu_pool`: A [`CpuPool`](https://docs.rs/futures-cpupool/0.1.8/futures_cpupool/struct.CpuPool.html) ...
-    ///     * with threads equal to the number of logical cores on your machine
-    ///     * that is lazily created, i.e. created only if / when you call the methods
-    ///       that need it ([`hash_non_blocking`](struct.Hasher.html#method.hash_non_blocking) or
-    ///       [`hash_raw_non_blocking`](struct.Hasher.html#method.hash_raw_non_blocking))
+    /// * `backend`: [`Backend::C`](config/enum.Backend.html#variant.C) (or
+    ///   [`Backend::Rust`](config/enum.Backend.html#variant.Rust) without the `backend-c` feature)
+    /// * `cpu_pool`: `None`, i.e. the non-blocking and async methods run on the
+    ///   [global thread pool](executor/fn.global_thread_pool.html), which ...
+    ///     * has threads equal to the number of cores available to the process (see
+    ///       [`configure_global_thread_pool`](executor/fn.configure_global_thread_pool.html))
+    ///     * is lazily created, i.e. created only if / when you call the methods that need it
+    ///     * is shared by every [`Hasher`](struct.Hasher.html) and
+    ///       [`Verifier`](struct.Verifier.html) in the process
     /// * `hash_len`: `32` bytes
     /// * `iterations`: `192`
-    /// * `lanes`: The number of logical cores on your machine
-    /// * `memory_size`: `4096` kibibytes
+    /// * `lanes`: The number of cores available to the process (see
+    ///   [`default_lanes`](config/fn.default_lanes.html))
+    /// * `memory_allocator`: `None`, i.e. the work area of each hash is allocated on the heap
+    /// * `memory_budget`: `None`
+    /// * `memory_size`: `4096` kibibytes, or less in a container with a small memory limit (see
+    ///   [`default_memory_size`](config/fn.default_memory_size.html))
     /// * `opt_out_of_secret_key`: `false`
     /// * `password_clearing`: `false`
     /// * `salt`: random [`Salt`](input/struct.Salt.html) of length 32 bytes that renews with every hash
     /// * `secret_key_clearing`: `false`
-    /// * `threads`: The number of logical cores on your machine
+    /// * `threads`: The number of cores available to the process (see
+    ///   [`default_threads`](config/fn.default_threads.html))
     /// * `variant`: [`Variant::Argon2id`](config/enum.Variant.html#variant.Argon2id)
     /// * `version`: [`Version::_0x13`](config/enum.Verion.html#variant._0x13)
     pub fn new() -> Hasher<'static> {
@@ -111,12 +132,39 @@ impl<'a> Hasher<'a> {
             .with_salt(&[0u8; 8][..]);
         hasher
     }
+    /// Measures how long hashing takes on this machine and returns a
+    /// [`Calibration`](config/struct.Calibration.html) containing a
+    /// [`HasherConfig`](config/struct.HasherConfig.html) whose mean hashing time is within 10%
+    /// of `target`, along with the mean and standard deviation of the hashing times measured with
+    /// it.
+    ///
+    /// Calibration starts from this [`Hasher`](struct.Hasher.html)'s configuration (backend,
+    /// lanes, threads, variant, etc. are kept as is) and tunes only `memory_size` and
+    /// `iterations`: it first picks the largest memory size (a power of two no greater than
+    /// `max_memory_size`, in kibibytes) that hashes within the target using a single iteration,
+    /// then adds iterations until the target is reached. Since a larger memory size makes
+    /// attacks with specialized hardware more expensive, this gets the most out of the time you
+    /// are willing to spend per hash. Calibration hashes a dummy password several times, so
+    /// expect it to take roughly ten to twenty times `target`. Errors if `max_memory_size` is
+    /// smaller than the minimum memory size for the configured number of lanes.
+    ///
+    /// This is meant to be called once, e.g. at service startup, after which you can pass the
+    /// calibrated `iterations` and `memory_size` to
+    /// [`configure_iterations`](struct.Hasher.html#method.configure_iterations) and
+    /// [`configure_memory_size`](struct.Hasher.html#method.configure_memory_size) on the
+    /// [`Hasher`](struct.Hasher.html)s you use for hashing.
+    /// Check [`is_within_target`](config/struct.Calibration.html#method.is_within_target) if
+    /// you need to know whether the target was actually reachable on this machine
+    pub fn calibrate(&self, target: Duration, max_memory_size: u32) -> Result<Calibration, Error> {
+        calibrate(&self.config, target, max_memory_size)
+    }
     /// Allows you to configure [`Hasher`](struct.Hasher.html) with a custom backend. The
-    /// default backend is [`Backend::C`](config/enum.Backend.html#variant.C), <i>which is
-    /// currently the only backend supported. A Rust backend is planned, but is not currently
-    /// available. If you configure a [`Hasher`](struct.Hasher.html) with
-    /// [`Backend::Rust`](config/enum.Backend.html#variant.Rust) it will error when you
-    /// call [`hash`](struct.Hasher.html#method.hash),
+    /// default backend is [`Backend::C`](config/enum.Backend.html#variant.C) (or
+    /// [`Backend::Rust`](config/enum.Backend.html#variant.Rust) if argonautica was built
+    /// without the `backend-c` feature). Both backends produce identical hashes. <i>If you
+    /// configure a [`Hasher`](struct.Hasher.html) with
+    /// [`Backend::C`](config/enum.Backend.html#variant.C) without the `backend-c` feature,
+    /// it will error when you call [`hash`](struct.Hasher.html#method.hash),
     /// [`hash_raw`](struct.Hasher.html#method.hash_raw) or their non-blocking equivalents</i>
     pub fn configure_backend(&mut self, backend: Backend) -> &mut Hasher<'a> {
         self.config.set_backend(backend);
@@ -127,14 +175,60 @@ impl<'a> Hasher<'a> {
     /// The default [`Hasher`](struct.Hasher.html) does not have a cpu pool, which is
     /// only needed for the [`hash_non_blocking`](struct.Hasher.html#method.hash_non_blocking)
     /// and [`hash_raw_non_blocking`](struct.Hasher.html#method.hash_raw_non_blocking) methods.
-    /// If you call either of these methods without a cpu pool, a default cpu pool will be created
-    /// for you on the fly; so even if you never configure [`Hasher`](struct.Hasher.html) with
-    /// this method you can still use the non-blocking hashing methods.
-    /// The default cpu pool has as many threads as the number of logical cores on your machine
+    /// If you call either of these methods without a cpu pool, they run on the configured
+    /// executor (see [`configure_executor`](struct.Hasher.html#method.configure_executor)) or,
+    /// failing that, on the [global thread pool](executor/fn.global_thread_pool.html); so even
+    /// if you never configure [`Hasher`](struct.Hasher.html) with this method you can still use
+    /// the non-blocking hashing methods
     pub fn configure_cpu_pool(&mut self, cpu_pool: CpuPool) -> &mut Hasher<'a> {
         self.config.set_cpu_pool(cpu_pool);
         self
     }
+    /// Allows you to configure [`Hasher`](struct.Hasher.html) with the
+    /// [`BlockingExecutor`](executor/trait.BlockingExecutor.html) that the async methods
+    /// ([`hash_async`](struct.Hasher.html#method.hash_async) and
+    /// [`hash_raw_async`](struct.Hasher.html#method.hash_raw_async)) run on, e.g. a
+    /// [`ThreadPool`](executor/struct.ThreadPool.html) shared with a
+    /// [`Verifier`](struct.Verifier.html), or a
+    /// [`TokioExecutor`](executor/struct.TokioExecutor.html) (with the `tokio` feature).
+    /// If you call the async methods without an executor, they run on the
+    /// [global thread pool](executor/fn.global_thread_pool.html), which is shared by every
+    /// [`Hasher`](struct.Hasher.html) and [`Verifier`](struct.Verifier.html) in the process
+    pub fn configure_executor<E>(&mut self, executor: E) -> &mut Hasher<'a>
+    where
+        E: BlockingExecutor + 'static,
+    {
+        self.config.set_executor(Arc::new(executor));
+        self
+    }
+    /// Configures [`Hasher`](struct.Hasher.html) with the settings of a
+    /// [`HasherConfig`](config/struct.HasherConfig.html), e.g. one returned by
+    /// [`calibrate`](struct.Hasher.html#method.calibrate) or deserialized (with the `serde`
+    /// feature) from a profile written by `argonautica calibrate`. This sets the backend, hash
+    /// length, iterations, lanes, memory size, threads, variant, version, secret key opt-out
+    /// and password and secret key clearing; a random salt is replaced by one of the
+    /// configuration's salt length. The cpu pool, executor, memory allocator, memory budget and
+    /// key id of the [`Hasher`](struct.Hasher.html) are left as they are
+    pub fn configure_from(&mut self, config: &HasherConfig) -> &mut Hasher<'a> {
+        self.config.set_backend(config.backend());
+        self.config.set_hash_len(config.hash_len());
+        self.config.set_iterations(config.iterations());
+        self.config.set_lanes(config.lanes());
+        self.config.set_memory_size(config.memory_size());
+        self.config
+            .set_opt_out_of_secret_key(config.opt_out_of_secret_key());
+        self.config
+            .set_password_clearing(config.password_clearing());
+        self.config
+            .set_secret_key_clearing(config.secret_key_clearing());
+        self.config.set_threads(config.threads());
+        self.config.set_variant(config.variant());
+        self.config.set_version(config.version());
+        if self.salt.is_random() && self.salt.len() as u32 != config.salt_len() {
+            self.with_salt(Salt::random(config.salt_len()));
+        }
+        self
+    }
     /// Allows you to configure [`Hasher`](struct.Hasher.html) to use a custom hash length
     /// (in number of bytes). The default is `32`.
     ///
@@ -153,15 +247,42 @@ impl<'a> Hasher<'a> {
         self
     }
     /// Allows you to configure [`Hasher`](struct.Hasher.html) to use a custom number of
-    /// lanes. The default is the number of physical cores on your machine.
+    /// lanes. The default is the number of cores available to the process (see
+    /// [`default_lanes`](config/fn.default_lanes.html)).
     ///
     /// See [configuration example](index.html#configuration) for a more details on this parameter
     pub fn configure_lanes(&mut self, lanes: u32) -> &mut Hasher<'a> {
         self.config.set_lanes(lanes);
         self
     }
+    /// Allows you to configure [`Hasher`](struct.Hasher.html) with a custom
+    /// [`MemoryAllocator`](allocator/trait.MemoryAllocator.html) for the work area of each hash
+    /// (i.e. the `memory_size` kibibytes of memory that Argon2 fills), for example to lock it
+    /// into RAM or to reuse it across hashes (see
+    /// [`ArenaAllocator`](allocator/struct.ArenaAllocator.html)). It is used by both backends.
+    /// The default
+    /// [`Hasher`](struct.Hasher.html) does not have a memory allocator and allocates the work
+    /// area on the heap
+    pub fn configure_memory_allocator<A>(&mut self, memory_allocator: A) -> &mut Hasher<'a>
+    where
+        A: MemoryAllocator + 'static,
+    {
+        self.config.set_memory_allocator(Arc::new(memory_allocator));
+        self
+    }
+    /// Allows you to attach [`Hasher`](struct.Hasher.html) to a
+    /// [`MemoryBudget`](budget/struct.MemoryBudget.html), which may be shared with other
+    /// [`Hasher`](struct.Hasher.html)s and [`Verifier`](struct.Verifier.html)s. Each hash
+    /// then waits until its memory size fits within the budget (or fails, depending on the
+    /// budget's [`Admission`](budget/enum.Admission.html) policy) before hashing. The default
+    /// [`Hasher`](struct.Hasher.html) does not have a memory budget
+    pub fn configure_memory_budget(&mut self, memory_budget: MemoryBudget) -> &mut Hasher<'a> {
+        self.config.set_memory_budget(memory_budget);
+        self
+    }
     /// Allows you to configure [`Hasher`](struct.Hasher.html) to use a custom memory size
-    /// (in kibibytes). The default is `4096`.
+    /// (in kibibytes). The default is `4096` (see
+    /// [`default_memory_size`](config/fn.default_memory_size.html)).
     ///
     /// See [configuration example](index.html#configuration) for a more details on this parameter
     pub fn configure_memory_size(&mut self, memory_size: u32) -> &mut Hasher<'a> {
@@ -199,7 +320,8 @@ impl<'a> Hasher<'a> {
         self
     }
     /// Allows you to configure [`Hasher`](struct.Hasher.html) to use a custom number of
-    /// threads. The default is the number of physical cores on your machine. If you choose
+    /// threads. The default is the number of cores available to the process (see
+    /// [`default_threads`](config/fn.default_threads.html)). If you choose
     /// a number of threads that is greater than the lanes configuration,
     /// [`Hasher`](struct.Hasher.html) will use the minimum of the two.
     ///
@@ -243,17 +365,94 @@ impl<'a> Hasher<'a> {
         let hash = hash_raw.encode_rust();
         Ok(hash)
     }
+    /// <b><u>The primary method (async version).</u></b>
+    ///
+    /// Same as [`hash`](struct.Hasher.html#method.hash) except the hashing runs on the
+    /// configured [`BlockingExecutor`](executor/trait.BlockingExecutor.html) (see
+    /// [`configure_executor`](struct.Hasher.html#method.configure_executor)) and it returns a
+    /// [`Task`](executor/struct.Task.html), i.e. a
+    /// [`std::future::Future`](https://doc.rust-lang.org/std/future/trait.Future.html) that you
+    /// can `.await`, instead of a [`Result`](https://doc.rust-lang.org/std/result/enum.Result.html)
+    pub fn hash_async(&mut self) -> Task<String> {
+        let mut hasher = self.async_hasher();
+        let executor = hasher.config.executor().unwrap_or_else(default_executor);
+        executor::spawn(&*executor, move || hasher.hash())
+    }
+    /// Hashes each of `passwords` like [`hash_password`](struct.Hasher.html#method.hash_password),
+    /// running several hashes at a time on the configured
+    /// [`BlockingExecutor`](executor/trait.BlockingExecutor.html) (see
+    /// [`configure_executor`](struct.Hasher.html#method.configure_executor)), and returns a
+    /// [`Batch`](executor/struct.Batch.html), i.e. an iterator over the string-encoded hashes
+    /// (or errors) in the same order as `passwords`. `passwords` is consumed lazily, so it can
+    /// stream from e.g. a file or a database query.
+    ///
+    /// For throughput, the `threads` configuration is spread across the batch instead of
+    /// across the lanes of a single hash: up to `threads` hashes run at a time, each on a
+    /// single thread. This does not change the hashes, which only depend on `lanes`. Each
+    /// running hash needs `memory_size` kibibytes, so with large memory sizes, lower `threads`
+    /// or configure a [`MemoryBudget`](budget/struct.MemoryBudget.html)
+    /// (see [`configure_memory_budget`](struct.Hasher.html#method.configure_memory_budget)).
+    /// This [`Hasher`](struct.Hasher.html) is not modified and its password, if any, is ignored
+    pub fn hash_batch<'b, I, P>(&self, passwords: I) -> Batch<'b, String>
+    where
+        I: IntoIterator<Item = P>,
+        I::IntoIter: 'b,
+        P: Into<Password<'b>>,
+    {
+        let mut hasher = self.to_owned();
+        hasher.password = None;
+        let parallelism = hasher.config.threads() as usize;
+        hasher.config.set_threads(1);
+        let executor = hasher.config.executor().unwrap_or_else(default_executor);
+        let hasher = Arc::new(hasher);
+        let jobs = passwords.into_iter().map(move |password| {
+            let hasher = hasher.clone();
+            let password = password.into().to_owned();
+            Box::new(move || hasher.hash_password::<Password>(password)) as BatchJob<String>
+        });
+        executor::spawn_batch(executor, parallelism, jobs)
+    }
     /// <b><u>The primary method (non-blocking version).</u></b>
     ///
     /// Same as [`hash`](struct.Hasher.html#method.hash) except it returns a
     /// [`Future`](https://docs.rs/futures/0.1.21/futures/future/trait.Future.html)
-    /// instead of a [`Result`](https://doc.rust-lang.org/std/result/enum.Result.html)
+    /// instead of a [`Result`](https://doc.rust-lang.org/std/result/enum.Result.html).
+    /// This is a [futures 0.1](https://docs.rs/futures/0.1.21/futures/) future; to use
+    /// `async` / `.await` instead, see [`hash_async`](struct.Hasher.html#method.hash_async)
     pub fn hash_non_blocking(&mut self) -> impl Future<Item = String, Error = Error> {
         self.hash_raw_non_blocking().and_then(|hash_raw| {
             let hash = hash_raw.encode_rust();
             Ok::<_, Error>(hash)
         })
     }
+    /// Hashes `password` with this [`Hasher`](struct.Hasher.html)'s configuration, salt,
+    /// secret key (or keyring) and additional data, returning a string-encoded hash, without
+    /// modifying the [`Hasher`](struct.Hasher.html).
+    ///
+    /// Unlike [`hash`](struct.Hasher.html#method.hash), this method takes `&self` and the
+    /// password as an argument, so a single [`Hasher`](struct.Hasher.html) can be configured
+    /// once and then shared across threads (e.g. in an
+    /// [`Arc`](https://doc.rust-lang.org/std/sync/struct.Arc.html)) to hash many passwords.
+    /// A random [`Salt`](input/struct.Salt.html) produces new random bytes for every call.
+    /// The `password_clearing` configuration applies to `password`; the secret key is never
+    /// cleared, regardless of the `secret_key_clearing` configuration
+    pub fn hash_password<'b, P>(&'b self, password: P) -> Result<String, Error>
+    where
+        P: Into<Password<'b>>,
+    {
+        let hash_raw = self.hash_password_raw(password)?;
+        Ok(hash_raw.encode_rust())
+    }
+    /// Same as [`hash_password`](struct.Hasher.html#method.hash_password) except it returns a
+    /// [`HashRaw`](output/struct.HashRaw.html) instead of a string-encoded hash
+    pub fn hash_password_raw<'b, P>(&'b self, password: P) -> Result<HashRaw, Error>
+    where
+        P: Into<Password<'b>>,
+    {
+        let mut hasher = self.borrowed()?;
+        hasher.password = Some(password.into());
+        hasher.hash_raw()
+    }
     /// Like the [`hash`](struct.Hasher.html#method.hash) method, but instead of producing
     /// an string-encoded hash, it produces a [`HashRaw`](output/struct.HashRaw.html) struct
     /// that contains all the components of the string-encoded version, including the raw
@@ -265,26 +464,47 @@ impl<'a> Hasher<'a> {
         });
         hasher.validate()?;
         hasher.salt.update()?;
-        let hash_raw = match hasher.config.backend() {
+        // Held until hashing is done
+        let _reservation = match hasher.config.memory_budget() {
+            Some(memory_budget) => Some(memory_budget.reserve(hasher.config.memory_size())?),
+            None => None,
+        };
+        let mut hash_raw = match hasher.config.backend() {
+            #[cfg(feature = "backend-c")]
             Backend::C => hasher.hash_raw_c()?,
-            Backend::Rust => return Err(Error::new(ErrorKind::BackendUnsupportedError)),
+            #[cfg(not(feature = "backend-c"))]
+            Backend::C => return Err(Error::new(ErrorKind::BackendUnsupportedError)),
+            Backend::Rust => hasher.hash_raw_rust()?,
         };
+        hash_raw.key_id = hasher.config.key_id().map(|key_id| key_id.to_vec());
         Ok(hash_raw)
     }
+    /// Same as [`hash_raw`](struct.Hasher.html#method.hash_raw) except the hashing runs on the
+    /// configured [`BlockingExecutor`](executor/trait.BlockingExecutor.html) and it returns a
+    /// [`Task`](executor/struct.Task.html) (see
+    /// [`hash_async`](struct.Hasher.html#method.hash_async))
+    pub fn hash_raw_async(&mut self) -> Task<HashRaw> {
+        let mut hasher = self.async_hasher();
+        let executor = hasher.config.executor().unwrap_or_else(default_executor);
+        executor::spawn(&*executor, move || hasher.hash_raw())
+    }
     /// Same as [`hash_raw`](struct.Hasher.html#method.hash) except it returns a
     /// [`Future`](https://docs.rs/futures/0.1.21/futures/future/trait.Future.html)
-    /// instead of a [`Result`](https://doc.rust-lang.org/std/result/enum.Result.html)
+    /// instead of a [`Result`](https://doc.rust-lang.org/std/result/enum.Result.html).
+    /// This is a [futures 0.1](https://docs.rs/futures/0.1.21/futures/) future; to use
+    /// `async` / `.await` instead, see [`hash_raw_async`](struct.Hasher.html#method.hash_raw_async)
     pub fn hash_raw_non_blocking(&mut self) -> impl Future<Item = HashRaw, Error = Error> {
         let hasher = scopeguard::guard(self, |hasher| {
             hasher.clear();
         });
         let mut hasher = hasher.to_owned();
         match hasher.config.cpu_pool() {
-            Some(cpu_pool) => cpu_pool.spawn_fn(move || hasher.hash_raw()),
+            Some(cpu_pool) => Either::A(cpu_pool.spawn_fn(move || hasher.hash_raw())),
             None => {
-                let cpu_pool = default_cpu_pool();
-                hasher.config.set_cpu_pool(cpu_pool.clone());
-                cpu_pool.spawn_fn(move || hasher.hash_raw())
+                let executor = hasher.config.executor().unwrap_or_else(default_executor);
+                Either::B(executor::spawn_non_blocking(&*executor, move || {
+                    hasher.hash_raw()
+                }))
             }
         }
     }
@@ -309,6 +529,7 @@ impl<'a> Hasher<'a> {
         Hasher {
             additional_data: self.additional_data.clone(),
             config: self.config.clone(),
+            keyring: self.keyring.clone(),
             password,
             salt: self.salt.clone(),
             secret_key,
@@ -331,6 +552,23 @@ impl<'a> Hasher<'a> {
         self.additional_data = Some(additional_data.into());
         self
     }
+    /// Allows you to provide [`Hasher`](struct.Hasher.html) with a
+    /// [`Keyring`](input/struct.Keyring.html) instead of a single secret key. Hashing will use
+    /// the keyring's active key and record its id in the `keyid` parameter of the
+    /// string-encoded hash, so that a [`Verifier`](struct.Verifier.html) given the same
+    /// keyring can later tell which key to verify with. If the keyring has no active key,
+    /// hashing will return an [`Error`](struct.Error.html).
+    ///
+    /// [`Hasher`](struct.Hasher.html) keeps a copy of the keyring, so changes made to
+    /// `keyring` afterwards are not seen until you call this method again. This replaces any
+    /// secret key provided with [`with_secret_key`](struct.Hasher.html#method.with_secret_key)
+    pub fn with_keyring(&mut self, keyring: &Keyring) -> &mut Hasher<'a> {
+        let key_id = keyring.active_key_id().map(|key_id| key_id.to_vec());
+        self.config.set_key_id(key_id);
+        self.keyring = Some(keyring.clone());
+        self.secret_key = None;
+        self
+    }
     /// Allows you to provide a [`Hasher`](struct.Hasher.html) with the password you would like
     /// to hash. Hashing requires a password; so you must call this method before calling
     /// [`hash`](struct.Hasher.html#method.hash), [`hash_raw`](struct.Hasher.html#method.hash_raw),
@@ -355,17 +593,22 @@ impl<'a> Hasher<'a> {
         S: Into<Salt>,
     {
         self.salt = salt.into();
+        self.config.set_salt_len(self.salt.len() as u32);
         self
     }
     /// Allows you to provide [`Hasher`](struct.Hasher.html) with a secret key that will be used
     /// to create the hash. The secret key will not be included in the hash output, meaning you
     /// must save it somewhere (ideally outside your code) to use later, as the only way to
     /// verify passwords against the hash later is to know the secret key. This library
-    /// encourages the use of a secret key
+    /// encourages the use of a secret key. This replaces any
+    /// [`Keyring`](input/struct.Keyring.html) provided with
+    /// [`with_keyring`](struct.Hasher.html#method.with_keyring)
     pub fn with_secret_key<SK>(&mut self, secret_key: SK) -> &mut Hasher<'a>
     where
         SK: Into<SecretKey<'a>>,
     {
+        self.config.set_key_id(None);
+        self.keyring = None;
         self.secret_key = Some(secret_key.into());
         self
     }
@@ -380,6 +623,11 @@ impl<'a> Hasher<'a> {
         &self.config
     }
     /// Read-only access to the [`Hasher`](struct.Hasher.html)'s
+    /// [`Keyring`](input/struct.Keyring.html), if any
+    pub fn keyring(&self) -> Option<&Keyring> {
+        self.keyring.as_ref()
+    }
+    /// Read-only access to the [`Hasher`](struct.Hasher.html)'s
     /// [`Password`](input/struct.Password.html), if any
     pub fn password(&self) -> Option<&Password<'a>> {
         self.password.as_ref()
@@ -396,18 +644,23 @@ impl<'a> Hasher<'a> {
 }
 
 impl<'a> Hasher<'a> {
+    /// Returns an owned copy of this [`Hasher`](struct.Hasher.html) to move onto an executor,
+    /// clearing this one as if it had hashed
+    fn async_hasher(&mut self) -> Hasher<'static> {
+        let hasher = scopeguard::guard(self, |hasher| {
+            hasher.clear();
+        });
+        hasher.to_owned()
+    }
     pub(crate) fn clear(&mut self) {
         if self.password.is_some() && self.config.password_clearing() {
             {
                 let password_mut_ref = self.password.as_mut().unwrap();
                 match password_mut_ref.inner {
                     Container::Borrowed(_) => (),
-                    Container::BorrowedMut(ref mut bytes) => {
-                        unsafe { ::std::ptr::write_bytes(bytes.as_mut_ptr(), 0, bytes.len()) };
-                    }
-                    Container::Owned(ref mut bytes) => {
-                        unsafe { ::std::ptr::write_bytes(bytes.as_mut_ptr(), 0, bytes.len()) };
-                    }
+                    Container::BorrowedMut(ref mut bytes) => wipe(bytes),
+                    // Wiped on drop
+                    Container::Owned(_) | Container::Locked(_) => (),
                 }
             }
             self.password = None;
@@ -417,16 +670,46 @@ impl<'a> Hasher<'a> {
                 let secret_key_mut_ref = self.secret_key.as_mut().unwrap();
                 match secret_key_mut_ref.inner {
                     Container::Borrowed(_) => (),
-                    Container::BorrowedMut(ref mut bytes) => {
-                        unsafe { ::std::ptr::write_bytes(bytes.as_mut_ptr(), 0, bytes.len()) };
-                    }
-                    Container::Owned(ref mut bytes) => {
-                        unsafe { ::std::ptr::write_bytes(bytes.as_mut_ptr(), 0, bytes.len()) };
-                    }
+                    Container::BorrowedMut(ref mut bytes) => wipe(bytes),
+                    // Wiped on drop
+                    Container::Owned(_) | Container::Locked(_) => (),
                 }
             }
             self.secret_key = None;
         }
+        if self.keyring.is_some() && self.config.secret_key_clearing() {
+            self.keyring.as_mut().unwrap().clear();
+            self.keyring = None;
+        }
+    }
+    /// Returns a [`Hasher`](struct.Hasher.html) without a password that borrows this one's
+    /// secret key (the keyring's active key, if there is a keyring), so that hashing with it
+    /// leaves this [`Hasher`](struct.Hasher.html) untouched. The secret key is never cleared
+    pub(crate) fn borrowed(&self) -> Result<Hasher<'_>, Error> {
+        if let Some(ref keyring) = self.keyring {
+            validate_keyring(keyring)?;
+        }
+        let mut config = self.config.clone();
+        config.set_secret_key_clearing(false);
+        Ok(Hasher {
+            additional_data: self.additional_data.clone(),
+            config,
+            keyring: None,
+            password: None,
+            salt: self.salt.clone(),
+            secret_key: self.secret_key_bytes().map(SecretKey::from),
+        })
+    }
+    /// The bytes of the secret key to hash with: the active key of the keyring if there is
+    /// one, otherwise the secret key, if any
+    pub(crate) fn secret_key_bytes(&self) -> Option<&[u8]> {
+        match self.keyring {
+            Some(ref keyring) => keyring.active_key().map(|secret_key| secret_key.as_bytes()),
+            None => self
+                .secret_key
+                .as_ref()
+                .map(|secret_key| secret_key.as_bytes()),
+        }
     }
     pub(crate) fn validate(&self) -> Result<(), Error> {
         self.config.validate()?;
@@ -443,6 +726,9 @@ impl<'a> Hasher<'a> {
             None => return Err(Error::new(ErrorKind::PasswordMissingError)),
         }
         self.salt.validate()?;
+        if let Some(ref keyring) = self.keyring {
+            return validate_keyring(keyring);
+        }
         match self.secret_key {
             Some(ref secret_key) => {
                 secret_key.validate()?;
@@ -460,10 +746,26 @@ impl<'a> Hasher<'a> {
     }
 }
 
+fn validate_keyring(keyring: &Keyring) -> Result<(), Error> {
+    keyring.validate()?;
+    if keyring.active_key().is_none() {
+        return Err(
+            Error::new(ErrorKind::SecretKeyMissingError).add_context("Keyring has no active key")
+        );
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use config::{Variant, Version};
+    use Verifier;
+
+    #[cfg(feature = "backend-c")]
+    const BACKENDS: [Backend; 2] = [Backend::C, Backend::Rust];
+    #[cfg(not(feature = "backend-c"))]
+    const BACKENDS: [Backend; 1] = [Backend::Rust];
 
     struct Test {
         variant: Variant,
@@ -473,22 +775,25 @@ mod tests {
 
     impl Test {
         fn run(&self) {
-            let mut hasher = Hasher::default();
-            let raw_hash = hasher
-                .configure_hash_len(32)
-                .configure_iterations(3)
-                .configure_lanes(4)
-                .configure_memory_size(32)
-                .configure_threads(4)
-                .configure_variant(self.variant)
-                .configure_version(self.version)
-                .with_additional_data(vec![4; 12])
-                .with_password(vec![1; 32])
-                .with_salt(vec![2; 16])
-                .with_secret_key(vec![3; 8])
-                .hash_raw()
-                .unwrap();
-            assert_eq!(raw_hash.raw_hash_bytes(), self.expected.as_slice());
+            for backend in &BACKENDS {
+                let mut hasher = Hasher::default();
+                let raw_hash = hasher
+                    .configure_backend(*backend)
+                    .configure_hash_len(32)
+                    .configure_iterations(3)
+                    .configure_lanes(4)
+                    .configure_memory_size(32)
+                    .configure_threads(4)
+                    .configure_variant(self.variant)
+                    .configure_version(self.version)
+                    .with_additional_data(vec![4; 12])
+                    .with_password(vec![1; 32])
+                    .with_salt(vec![2; 16])
+                    .with_secret_key(vec![3; 8])
+                    .hash_raw()
+                    .unwrap();
+                assert_eq!(raw_hash.raw_hash_bytes(), self.expected.as_slice());
+            }
         }
     }
 
@@ -609,12 +914,213 @@ mod tests {
         assert!(hasher.secret_key().is_none());
     }
 
+    #[test]
+    fn test_hasher_configure_from() {
+        let mut hasher1 = Hasher::default();
+        hasher1
+            .configure_backend(Backend::Rust)
+            .configure_hash_len(16)
+            .configure_iterations(2)
+            .configure_lanes(2)
+            .configure_memory_size(64)
+            .configure_password_clearing(true)
+            .configure_threads(1)
+            .configure_variant(Variant::Argon2i)
+            .configure_version(Version::_0x10)
+            .opt_out_of_secret_key(true)
+            .with_salt(Salt::random(16));
+
+        let mut hasher2 = Hasher::default();
+        hasher2.configure_from(hasher1.config());
+        let (config1, config2) = (hasher1.config(), hasher2.config());
+        assert_eq!(config1.backend(), config2.backend());
+        assert_eq!(config1.hash_len(), config2.hash_len());
+        assert_eq!(config1.iterations(), config2.iterations());
+        assert_eq!(config1.lanes(), config2.lanes());
+        assert_eq!(config1.memory_size(), config2.memory_size());
+        assert_eq!(
+            config1.opt_out_of_secret_key(),
+            config2.opt_out_of_secret_key()
+        );
+        assert_eq!(config1.password_clearing(), config2.password_clearing());
+        assert_eq!(config1.salt_len(), config2.salt_len());
+        assert_eq!(config1.secret_key_clearing(), config2.secret_key_clearing());
+        assert_eq!(config1.threads(), config2.threads());
+        assert_eq!(config1.variant(), config2.variant());
+        assert_eq!(config1.version(), config2.version());
+        assert_eq!(hasher2.salt().len(), 16);
+        assert!(hasher2.salt().is_random());
+
+        // A deterministic salt is kept
+        hasher2
+            .with_salt("somesalt")
+            .configure_from(&HasherConfig::default());
+        assert_eq!(hasher2.salt().as_bytes(), b"somesalt");
+        let hash1 = hasher1
+            .with_salt("somesalt")
+            .with_password(String::from("P@ssw0rd"))
+            .hash()
+            .unwrap();
+        let hash2 = hasher2
+            .configure_from(hasher1.config())
+            .with_password(String::from("P@ssw0rd"))
+            .hash()
+            .unwrap();
+        assert_eq!(hash1, hash2);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_hasher_configure_from_deserialized_config() {
+        use serde_json;
+
+        let mut hasher1 = Hasher::default();
+        hasher1
+            .configure_iterations(2)
+            .configure_lanes(1)
+            .configure_memory_size(32)
+            .with_salt("somesalt")
+            .with_secret_key("secret");
+        // E.g. a profile written by `argonautica calibrate --format json`
+        let j = serde_json::to_string_pretty(hasher1.config()).unwrap();
+        let config: HasherConfig = serde_json::from_str(&j).unwrap();
+        let mut hasher2 = Hasher::default();
+        hasher2
+            .configure_from(&config)
+            .with_salt("somesalt")
+            .with_secret_key("secret");
+        assert_eq!(
+            hasher1.with_password("P@ssw0rd").hash().unwrap(),
+            hasher2.with_password("P@ssw0rd").hash().unwrap(),
+        );
+    }
+
     #[test]
     fn test_hasher_fast_but_insecure() {
         let mut hasher = Hasher::fast_but_insecure();
         let _ = hasher.with_password("P@ssw0rd").hash().unwrap();
     }
 
+    #[test]
+    fn test_hasher_hash_async() {
+        use executor::tests::block_on;
+        use executor::ThreadPool;
+
+        let mut hasher = Hasher::default();
+        hasher
+            .configure_executor(ThreadPool::new(1))
+            .configure_iterations(2)
+            .configure_lanes(1)
+            .configure_memory_size(32)
+            .configure_password_clearing(true)
+            .opt_out_of_secret_key(true)
+            .with_password(String::from("P@ssw0rd"))
+            .with_salt("somesalt");
+        let task = hasher.hash_async();
+        // The password is cleared right away, as it has been moved onto the executor
+        assert!(hasher.password().is_none());
+        let hash = block_on(task).unwrap();
+        let hash_raw = block_on(hasher.with_password("P@ssw0rd").hash_raw_async()).unwrap();
+        assert_eq!(hash, hash_raw.to_string());
+
+        // Errors are delivered through the task
+        let err = block_on(Hasher::default().hash_async()).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::PasswordMissingError);
+    }
+
+    #[test]
+    fn test_hasher_hash_batch() {
+        use executor::ThreadPool;
+
+        let mut hasher = Hasher::default();
+        hasher
+            .configure_executor(ThreadPool::new(2))
+            .configure_iterations(2)
+            .configure_lanes(2)
+            .configure_memory_size(32)
+            .configure_password_clearing(true)
+            .configure_threads(3)
+            .with_secret_key("secret");
+        let passwords = ["P@ssw0rd0", "P@ssw0rd1", "", "P@ssw0rd3"];
+        let batch = hasher.hash_batch(passwords.iter().cloned());
+        assert_eq!(batch.parallelism(), 3);
+        let results = batch.collect::<Vec<_>>();
+        assert_eq!(results.len(), 4);
+        assert_eq!(
+            results[2].as_ref().unwrap_err().kind(),
+            ErrorKind::PasswordTooShortError,
+        );
+        // The lanes are kept, even though each hash runs on a single thread
+        assert!(results[0]
+            .as_ref()
+            .unwrap()
+            .starts_with("$argon2id$v=19$m=32,t=2,p=2$"));
+        // The hasher is not modified
+        assert_eq!(hasher.config().threads(), 3);
+        assert!(hasher.secret_key().is_some());
+
+        let mut verifier = Verifier::default();
+        verifier.with_secret_key("secret");
+        for (i, result) in results.iter().enumerate().filter(|&(i, _)| i != 2) {
+            assert!(verifier
+                .verify_password(result.as_ref().unwrap(), passwords[i])
+                .unwrap());
+        }
+    }
+
+    #[test]
+    fn test_hasher_hash_password() {
+        use std::sync::Arc;
+        use std::thread;
+
+        let mut hasher = Hasher::default();
+        hasher
+            .configure_iterations(2)
+            .configure_lanes(1)
+            .configure_memory_size(32)
+            .configure_secret_key_clearing(true)
+            .with_secret_key(vec![3; 8]);
+        let hasher = Arc::new(hasher);
+        let handles = (0..4)
+            .map(|i| {
+                let hasher = hasher.clone();
+                thread::spawn(move || hasher.hash_password(format!("P@ssw0rd{}", i)).unwrap())
+            })
+            .collect::<Vec<_>>();
+        let hashes = handles
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .collect::<Vec<_>>();
+
+        // The salt is renewed for every hash and the secret key is never cleared
+        assert_ne!(hashes[0], hashes[1]);
+        assert!(hasher.secret_key().is_some());
+        assert!(hasher.password().is_none());
+
+        let mut verifier = Verifier::default();
+        verifier
+            .with_hash(&hashes[2])
+            .with_password("P@ssw0rd2")
+            .with_secret_key(vec![3; 8]);
+        assert!(verifier.verify().unwrap());
+
+        // Password clearing applies to the password passed in
+        let mut hasher = Hasher::default();
+        hasher
+            .configure_iterations(2)
+            .configure_lanes(1)
+            .configure_memory_size(32)
+            .configure_password_clearing(true)
+            .opt_out_of_secret_key(true);
+        let mut password = String::from("P@ssw0rd");
+        hasher.hash_password(&mut password).unwrap();
+        assert_eq!(password.as_bytes(), &[0u8; 8]);
+        assert_eq!(
+            hasher.hash_password("P@ssw0rd").unwrap_err().kind(),
+            ErrorKind::PasswordImmutableError,
+        );
+    }
+
     #[cfg(feature = "serde")]
     #[test]
     fn test_hasher_serialization() {
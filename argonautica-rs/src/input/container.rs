@@ -1,8 +1,26 @@
+use std::sync::Arc;
+
+use input::LockedBytes;
+use utils::wipe_raw;
+
 #[derive(Debug, Eq, PartialEq, Hash)]
 pub(crate) enum Container<'a> {
     Borrowed(&'a [u8]),
     BorrowedMut(&'a mut [u8]),
     Owned(Vec<u8>),
+    /// Locked bytes are immutable, so copies share them instead of copying them, which
+    /// cannot fail. They are wiped when the last copy is dropped
+    Locked(Arc<LockedBytes>),
+}
+
+impl<'a> Drop for Container<'a> {
+    /// Owned bytes (including any spare capacity, which may hold leftovers of the bytes) are
+    /// wiped; borrowed bytes belong to the caller and are left alone
+    fn drop(&mut self) {
+        if let Container::Owned(ref mut bytes) = *self {
+            unsafe { wipe_raw(bytes.as_mut_ptr(), bytes.capacity()) };
+        }
+    }
 }
 
 impl<'a> Container<'a> {
@@ -11,6 +29,7 @@ impl<'a> Container<'a> {
             Container::Borrowed(ref bytes) => Container::Owned(bytes.to_vec()),
             Container::BorrowedMut(ref bytes) => Container::Owned(bytes.to_vec()),
             Container::Owned(ref bytes) => Container::Owned(bytes.to_vec()),
+            Container::Locked(ref bytes) => Container::Locked(Arc::clone(bytes)),
         }
     }
 }
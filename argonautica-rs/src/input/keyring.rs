@@ -0,0 +1,210 @@
+use std::collections::BTreeMap;
+
+use base64;
+
+use input::SecretKey;
+use {Error, ErrorKind};
+
+/// Maximum length (in bytes) of a key id, which is limited by the `keyid` parameter of the
+/// PHC string format for Argon2
+const MAX_KEY_ID_LEN: usize = 8;
+
+impl Clone for Keyring {
+    fn clone(&self) -> Keyring {
+        Keyring {
+            active_key_id: self.active_key_id.clone(),
+            keys: self
+                .keys
+                .iter()
+                .map(|(key_id, secret_key)| (key_id.clone(), secret_key.to_owned()))
+                .collect(),
+        }
+    }
+}
+
+/// A set of [`SecretKey`](struct.SecretKey.html)s, each identified by a short key id, one of
+/// which is the active key. Use it to rotate secret keys (a.k.a. peppers) without invalidating
+/// existing hashes.
+///
+/// A [`Hasher`](../struct.Hasher.html) configured with a keyring (see
+/// [`with_keyring`](../struct.Hasher.html#method.with_keyring)) hashes with the active key and
+/// records its id in the `keyid` parameter of the string-encoded hash. A
+/// [`Verifier`](../struct.Verifier.html) configured with a keyring (see
+/// [`with_keyring`](../struct.Verifier.html#method.with_keyring)) then picks the key whose id
+/// is recorded in the hash it verifies against. To rotate keys, add a new key, make it the
+/// active key, and keep the old keys around until the hashes created with them have been
+/// upgraded (see [`Verifier::verify_and_upgrade`](../struct.Verifier.html#method.verify_and_upgrade)).
+///
+/// Key ids must be between 1 and 8 bytes long. The keyring stores copies of the secret keys
+/// it is given
+#[derive(Debug, Default)]
+pub struct Keyring {
+    active_key_id: Option<Vec<u8>>,
+    keys: BTreeMap<Vec<u8>, SecretKey<'static>>,
+}
+
+impl Keyring {
+    /// Creates a new, empty [`Keyring`](struct.Keyring.html)
+    pub fn new() -> Keyring {
+        Keyring::default()
+    }
+    /// Adds a copy of `secret_key` to the [`Keyring`](struct.Keyring.html) under `key_id`,
+    /// replacing the key previously stored under that id, if any. This does not change which
+    /// key is active
+    pub fn add_key<'b, K, SK>(&mut self, key_id: K, secret_key: SK) -> &mut Keyring
+    where
+        K: AsRef<[u8]>,
+        SK: Into<SecretKey<'b>>,
+    {
+        let secret_key = secret_key.into().to_owned();
+        self.keys.insert(key_id.as_ref().to_vec(), secret_key);
+        self
+    }
+    /// Removes the key stored under `key_id` from the [`Keyring`](struct.Keyring.html),
+    /// returning it if there was one. If it was the active key, the
+    /// [`Keyring`](struct.Keyring.html) no longer has an active key
+    pub fn remove_key<K>(&mut self, key_id: K) -> Option<SecretKey<'static>>
+    where
+        K: AsRef<[u8]>,
+    {
+        let key_id = key_id.as_ref();
+        if self.active_key_id.as_ref().map(|id| &id[..]) == Some(key_id) {
+            self.active_key_id = None;
+        }
+        self.keys.remove(key_id)
+    }
+    /// Makes the key stored under `key_id` the active key, i.e. the key new hashes are
+    /// created with. If there is no key with this id, hashing with the
+    /// [`Keyring`](struct.Keyring.html) will return an [`Error`](../struct.Error.html)
+    pub fn set_active_key<K>(&mut self, key_id: K) -> &mut Keyring
+    where
+        K: AsRef<[u8]>,
+    {
+        self.active_key_id = Some(key_id.as_ref().to_vec());
+        self
+    }
+    /// Read-only access to the active key, if any
+    pub fn active_key(&self) -> Option<&SecretKey<'static>> {
+        self.active_key_id
+            .as_ref()
+            .and_then(|key_id| self.keys.get(key_id))
+    }
+    /// Read-only access to the id of the active key, if any
+    pub fn active_key_id(&self) -> Option<&[u8]> {
+        self.active_key_id.as_ref().map(|key_id| &key_id[..])
+    }
+    /// Read-only access to the key stored under `key_id`, if any
+    pub fn get<K>(&self, key_id: K) -> Option<&SecretKey<'static>>
+    where
+        K: AsRef<[u8]>,
+    {
+        self.keys.get(key_id.as_ref())
+    }
+    /// Returns `true` if the [`Keyring`](struct.Keyring.html) holds no keys
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+    /// Returns the ids of all the keys in the [`Keyring`](struct.Keyring.html), in ascending
+    /// order
+    pub fn key_ids(&self) -> Vec<&[u8]> {
+        self.keys.keys().map(|key_id| &key_id[..]).collect()
+    }
+    /// The number of keys in the [`Keyring`](struct.Keyring.html)
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+}
+
+impl Keyring {
+    pub(crate) fn clear(&mut self) {
+        // The keys' owned bytes are wiped on drop
+        self.keys.clear();
+        self.active_key_id = None;
+    }
+    pub(crate) fn validate(&self) -> Result<(), Error> {
+        for (key_id, secret_key) in &self.keys {
+            validate_key_id(key_id)?;
+            secret_key.validate()?;
+        }
+        if let Some(ref key_id) = self.active_key_id {
+            if !self.keys.contains_key(key_id) {
+                return Err(Error::new(ErrorKind::KeyIdUnknownError)
+                    .add_context(format!("Active key id: {}", encode_key_id(key_id))));
+            }
+        }
+        Ok(())
+    }
+}
+
+pub(crate) fn encode_key_id(key_id: &[u8]) -> String {
+    base64::encode_config(key_id, base64::STANDARD_NO_PAD)
+}
+
+fn validate_key_id(key_id: &[u8]) -> Result<(), Error> {
+    if key_id.is_empty() || key_id.len() > MAX_KEY_ID_LEN {
+        return Err(Error::new(ErrorKind::KeyIdInvalidError)
+            .add_context(format!("Length: {}", key_id.len())));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keyring() {
+        let mut keyring = Keyring::new();
+        assert!(keyring.is_empty());
+        keyring
+            .add_key("2018", "old secret")
+            .add_key("2019", "new secret")
+            .set_active_key("2019");
+        assert_eq!(keyring.len(), 2);
+        assert_eq!(keyring.key_ids(), vec![&b"2018"[..], &b"2019"[..]]);
+        assert_eq!(keyring.active_key_id(), Some(&b"2019"[..]));
+        assert_eq!(keyring.active_key().unwrap().as_bytes(), b"new secret");
+        assert_eq!(keyring.get("2018").unwrap().as_bytes(), b"old secret");
+        assert!(keyring.get("2020").is_none());
+        assert!(keyring.validate().is_ok());
+
+        // Keys are copied, so the keyring owns them
+        assert!(keyring.get("2018").unwrap().is_mutable());
+
+        let removed = keyring.remove_key("2019").unwrap();
+        assert_eq!(removed.as_bytes(), b"new secret");
+        assert!(keyring.active_key_id().is_none());
+        assert!(keyring.active_key().is_none());
+        assert_eq!(keyring.len(), 1);
+    }
+
+    #[test]
+    fn test_keyring_validate() {
+        let mut keyring = Keyring::new();
+        keyring.add_key("key", "secret").set_active_key("other");
+        let err = keyring.validate().unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::KeyIdUnknownError);
+
+        let mut keyring = Keyring::new();
+        keyring.add_key("", "secret");
+        let err = keyring.validate().unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::KeyIdInvalidError);
+
+        let mut keyring = Keyring::new();
+        keyring.add_key("123456789", "secret");
+        let err = keyring.validate().unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::KeyIdInvalidError);
+    }
+
+    #[test]
+    fn test_send() {
+        fn assert_send<T: Send>() {}
+        assert_send::<Keyring>();
+    }
+
+    #[test]
+    fn test_sync() {
+        fn assert_sync<T: Sync>() {}
+        assert_sync::<Keyring>();
+    }
+}
@@ -0,0 +1,181 @@
+use std::alloc::Layout;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::ops::Deref;
+use std::ptr::{self, NonNull};
+use std::slice;
+
+use allocator::{lock, page_size, unlock, MemoryAllocator, SystemAllocator};
+use utils::wipe_raw;
+use {Error, ErrorKind};
+
+/// Bytes stored in memory that is locked into RAM (so that it is never written to swap) and,
+/// on Linux, excluded from core dumps. The memory is allocated in whole pages, because locks
+/// are not counted: unlocking a page that is shared with other locked bytes would unlock
+/// those too. The bytes are immutable and are wiped before the memory is unlocked and freed
+pub(crate) struct LockedBytes {
+    layout: Option<Layout>,
+    len: usize,
+    ptr: NonNull<u8>,
+}
+
+// Safe: the bytes are never mutated after construction
+unsafe impl Send for LockedBytes {}
+unsafe impl Sync for LockedBytes {}
+
+impl LockedBytes {
+    /// Copies `bytes` into newly allocated, locked memory. Returns an
+    /// [`Error`](../struct.Error.html) of kind
+    /// [`MemoryLockError`](../enum.ErrorKind.html#variant.MemoryLockError) if the memory cannot
+    /// be locked, typically because `RLIMIT_MEMLOCK` would be exceeded
+    pub(crate) fn new(bytes: &[u8]) -> Result<LockedBytes, Error> {
+        if bytes.is_empty() {
+            return Ok(LockedBytes {
+                layout: None,
+                len: 0,
+                ptr: NonNull::dangling(),
+            });
+        }
+        let page_size = page_size();
+        // The page size is a power of two
+        let size = (bytes.len() + page_size - 1) & !(page_size - 1);
+        let layout = Layout::from_size_align(size, page_size).map_err(|_| {
+            Error::new(ErrorKind::MemoryAllocationError).add_context(format!("Size: {}", size))
+        })?;
+        let ptr = SystemAllocator.allocate(layout)?;
+        unsafe {
+            if let Err(e) = lock(ptr.as_ptr(), size) {
+                SystemAllocator.deallocate(ptr, layout);
+                return Err(e);
+            }
+            ptr::copy_nonoverlapping(bytes.as_ptr(), ptr.as_ptr(), bytes.len());
+        }
+        Ok(LockedBytes {
+            layout: Some(layout),
+            len: bytes.len(),
+            ptr,
+        })
+    }
+}
+
+impl Deref for LockedBytes {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        unsafe { slice::from_raw_parts(self.ptr.as_ptr(), self.len) }
+    }
+}
+
+impl Drop for LockedBytes {
+    fn drop(&mut self) {
+        if let Some(layout) = self.layout {
+            unsafe {
+                wipe_raw(self.ptr.as_ptr(), layout.size());
+                unlock(self.ptr.as_ptr(), layout.size());
+                SystemAllocator.deallocate(self.ptr, layout);
+            }
+        }
+    }
+}
+
+impl fmt::Debug for LockedBytes {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "LockedBytes {{ len: {} }}", self.len)
+    }
+}
+
+impl Eq for LockedBytes {}
+
+impl Hash for LockedBytes {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self[..].hash(state);
+    }
+}
+
+impl PartialEq for LockedBytes {
+    fn eq(&self, other: &LockedBytes) -> bool {
+        self[..] == other[..]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use input::{Password, SecretKey};
+    use Hasher;
+
+    #[test]
+    fn test_locked_bytes() {
+        for len in &[0, 1, 32, page_size(), page_size() + 1] {
+            let bytes = vec![0xABu8; *len];
+            let locked = match LockedBytes::new(&bytes) {
+                Ok(locked) => locked,
+                Err(e) => {
+                    // Locking is not permitted in this environment
+                    assert_eq!(e.kind(), ErrorKind::MemoryLockError);
+                    continue;
+                }
+            };
+            assert_eq!(&locked[..], &bytes[..]);
+            if let Some(layout) = locked.layout {
+                assert_eq!(locked.ptr.as_ptr() as usize % page_size(), 0);
+                assert_eq!(layout.size() % page_size(), 0);
+            }
+        }
+    }
+
+    #[test]
+    fn test_locked_password_and_secret_key() {
+        let (password, secret_key) = match (
+            Password::locked(b"P@ssw0rd"),
+            SecretKey::locked(b"somesecret"),
+        ) {
+            (Ok(password), Ok(secret_key)) => (password, secret_key),
+            (Err(e), _) | (_, Err(e)) => {
+                assert_eq!(e.kind(), ErrorKind::MemoryLockError);
+                return;
+            }
+        };
+        assert!(password.is_locked());
+        assert!(password.is_mutable());
+        assert_eq!(password.as_bytes(), b"P@ssw0rd");
+        assert!(password.to_owned().is_locked());
+        assert!(secret_key.is_locked());
+        assert!(secret_key.to_owned().is_locked());
+        assert!(!Password::from("P@ssw0rd").is_locked());
+
+        let mut hasher = Hasher::default();
+        hasher
+            .configure_iterations(2)
+            .configure_lanes(1)
+            .configure_memory_size(32)
+            .with_salt("somesalt");
+        let expected = hasher
+            .with_password("P@ssw0rd")
+            .with_secret_key("somesecret")
+            .hash()
+            .unwrap();
+        let hash = hasher
+            .configure_password_clearing(true)
+            .configure_secret_key_clearing(true)
+            .with_password(password)
+            .with_secret_key(secret_key)
+            .hash()
+            .unwrap();
+        assert_eq!(hash, expected);
+        assert!(hasher.password().is_none());
+        assert!(hasher.secret_key().is_none());
+    }
+
+    #[test]
+    fn test_send() {
+        fn assert_send<T: Send>() {}
+        assert_send::<LockedBytes>();
+    }
+
+    #[test]
+    fn test_sync() {
+        fn assert_sync<T: Sync>() {}
+        assert_sync::<LockedBytes>();
+    }
+}
@@ -14,14 +14,22 @@
 //! constructors as well, e.g. [`Salt::random(...)`](struct.Salt.html#method.random), which
 //! produces a [`Salt`](struct.Salt.html) that will create new crytographically-secure,
 //! random bytes after each hash.
+//!
+//! A [`Keyring`](struct.Keyring.html) holds several identified secret keys, so that secret
+//! keys can be rotated without invalidating existing hashes.
 mod additional_data;
 mod container;
+mod keyring;
+mod locked;
 mod password;
 mod salt;
 mod secret_key;
 
 pub use self::additional_data::AdditionalData;
 pub(crate) use self::container::Container;
+pub(crate) use self::keyring::encode_key_id;
+pub use self::keyring::Keyring;
+pub(crate) use self::locked::LockedBytes;
 pub use self::password::Password;
 pub use self::salt::Salt;
 pub use self::secret_key::SecretKey;
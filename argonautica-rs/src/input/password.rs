@@ -1,6 +1,7 @@
 use std::fmt;
+use std::sync::Arc;
 
-use input::Container;
+use input::{Container, LockedBytes};
 use {Error, ErrorKind};
 
 impl<'a> From<&'a str> for Password<'a> {
@@ -91,6 +92,7 @@ impl<'a> From<&'a Password<'a>> for Password<'a> {
             Container::Borrowed(ref bytes) => &**bytes,
             Container::BorrowedMut(ref bytes) => &**bytes,
             Container::Owned(ref bytes) => bytes,
+            Container::Locked(ref bytes) => bytes,
         };
         Password {
             inner: Container::Borrowed(bytes),
@@ -110,6 +112,9 @@ impl<'a> From<&'a mut Password<'a>> for Password<'a> {
             Container::Owned(ref mut bytes) => Password {
                 inner: Container::BorrowedMut(&mut *bytes),
             },
+            Container::Locked(ref bytes) => Password {
+                inner: Container::Locked(Arc::clone(bytes)),
+            },
         }
     }
 }
@@ -120,19 +125,51 @@ impl<'a> fmt::Debug for Password<'a> {
     }
 }
 
-/// Type-safe struct representing the raw bytes of a password
+/// Type-safe struct representing the raw bytes of a password.
+///
+/// If the [`Password`](struct.Password.html) owns its bytes (i.e. it was constructed from an owned
+/// value such as a `String` or a `Vec<u8>`, or with
+/// [`to_owned`](struct.Password.html#method.to_owned)), they are wiped when it is dropped.
+/// [`Password::locked`](struct.Password.html#method.locked) additionally keeps them out of
+/// swap and core dumps
 #[derive(Eq, PartialEq, Hash)]
 pub struct Password<'a> {
     pub(crate) inner: Container<'a>,
 }
 
 impl<'a> Password<'a> {
+    /// Creates a new <u>locked</u> [`Password`](struct.Password.html) by copying `bytes` into
+    /// memory that is locked into RAM (with `mlock`), so that it is never written to swap, and, on
+    /// Linux, excluded from core dumps. Wipe `bytes` yourself once you no longer need them.
+    ///
+    /// Returns an [`Error`](../struct.Error.html) of kind
+    /// [`MemoryLockError`](../enum.ErrorKind.html#variant.MemoryLockError) if the memory cannot be
+    /// locked, typically because the process's `RLIMIT_MEMLOCK` limit (see `ulimit -l`) would be
+    /// exceeded, or because the platform does not support it (only Unix platforms do). Locked
+    /// memory is allocated in whole pages, so every locked [`Password`](struct.Password.html)
+    /// counts as at least one page (typically 4 KiB) against that limit
+    pub fn locked(bytes: &[u8]) -> Result<Password<'static>, Error> {
+        Ok(Password {
+            inner: Container::Locked(Arc::new(LockedBytes::new(bytes)?)),
+        })
+    }
     /// Read-only access to the underlying byte buffer
     pub fn as_bytes(&self) -> &[u8] {
         match self.inner {
             Container::Borrowed(ref bytes) => bytes,
             Container::BorrowedMut(ref bytes) => bytes,
             Container::Owned(ref bytes) => bytes,
+            Container::Locked(ref bytes) => bytes,
+        }
+    }
+    /// Indicates whether the underlying byte buffer is locked into RAM or not, i.e. whether
+    /// the [`Password`](struct.Password.html) was constructed with
+    /// [`locked`](struct.Password.html#method.locked) (or is a copy of such a
+    /// [`Password`](struct.Password.html))
+    pub fn is_locked(&self) -> bool {
+        match self.inner {
+            Container::Locked(_) => true,
+            Container::Borrowed(_) | Container::BorrowedMut(_) | Container::Owned(_) => false,
         }
     }
     /// Indicates whether the underlying byte buffer is mutable or not. The underlying byte
@@ -142,6 +179,9 @@ impl<'a> Password<'a> {
     /// [`Password`](struct.Password.html) was constructed from an immutable reference
     /// (such as a `&str` or a `&[u8]`). The [`Password`](struct.Password.html) must be mutable
     /// in order to hash or verify with the `password_clearing` configuration set to `true`
+    ///
+    /// A locked [`Password`](struct.Password.html) counts as mutable: clearing it drops it, and its
+    /// bytes are wiped once the last copy of it is dropped
     pub fn is_mutable(&self) -> bool {
         match self.inner {
             Container::Borrowed(_) => false,
@@ -152,9 +192,11 @@ impl<'a> Password<'a> {
     pub fn len(&self) -> usize {
         self.as_bytes().len()
     }
-    /// Clones the underlying byte buffer and returns a new
-    /// [`Password`](struct.Password.html) with a `static` lifetime. Use this method if you
-    /// would like to move a [`Password`](struct.Password.html) to another thread
+    /// Clones the underlying byte buffer and returns a new [`Password`](struct.Password.html) with
+    /// a `static` lifetime. Use this method if you would like to move a
+    /// [`Password`](struct.Password.html) to another thread. The copy of a locked
+    /// [`Password`](struct.Password.html) is locked too: rather than being cloned, the locked bytes
+    /// are shared, and they are wiped once the last copy is dropped
     pub fn to_owned(&self) -> Password<'static> {
         Password {
             inner: self.inner.to_owned(),
@@ -1,8 +1,9 @@
 use std::fmt;
+use std::sync::Arc;
 
 use base64;
 
-use input::Container;
+use input::{Container, LockedBytes};
 use {Error, ErrorKind};
 
 impl<'a> From<&'a str> for SecretKey<'a> {
@@ -93,6 +94,7 @@ impl<'a> From<&'a SecretKey<'a>> for SecretKey<'a> {
             Container::Borrowed(ref bytes) => &**bytes,
             Container::BorrowedMut(ref bytes) => &**bytes,
             Container::Owned(ref bytes) => bytes,
+            Container::Locked(ref bytes) => bytes,
         };
         SecretKey {
             inner: Container::Borrowed(bytes),
@@ -112,6 +114,9 @@ impl<'a> From<&'a mut SecretKey<'a>> for SecretKey<'a> {
             Container::Owned(ref mut bytes) => SecretKey {
                 inner: Container::BorrowedMut(&mut *bytes),
             },
+            Container::Locked(ref bytes) => SecretKey {
+                inner: Container::Locked(Arc::clone(bytes)),
+            },
         }
     }
 }
@@ -122,7 +127,13 @@ impl<'a> fmt::Debug for SecretKey<'a> {
     }
 }
 
-/// Type-safe struct representing the raw bytes of a secret key
+/// Type-safe struct representing the raw bytes of a secret key.
+///
+/// If the [`SecretKey`](struct.SecretKey.html) owns its bytes (i.e. it was constructed from an
+/// owned value such as a `String` or a `Vec<u8>`, or with
+/// [`to_owned`](struct.SecretKey.html#method.to_owned)), they are wiped when it is dropped.
+/// [`SecretKey::locked`](struct.SecretKey.html#method.locked) additionally keeps them out of
+/// swap and core dumps
 #[derive(Eq, PartialEq, Hash)]
 pub struct SecretKey<'a> {
     pub(crate) inner: Container<'a>,
@@ -160,12 +171,38 @@ impl<'a> SecretKey<'a> {
             inner: Container::Owned(bytes),
         })
     }
+    /// Creates a new <u>locked</u> [`SecretKey`](struct.SecretKey.html) by copying `bytes` into
+    /// memory that is locked into RAM (with `mlock`), so that it is never written to swap, and, on
+    /// Linux, excluded from core dumps. Wipe `bytes` yourself once you no longer need them.
+    ///
+    /// Returns an [`Error`](../struct.Error.html) of kind
+    /// [`MemoryLockError`](../enum.ErrorKind.html#variant.MemoryLockError) if the memory cannot be
+    /// locked, typically because the process's `RLIMIT_MEMLOCK` limit (see `ulimit -l`) would be
+    /// exceeded, or because the platform does not support it (only Unix platforms do). Locked
+    /// memory is allocated in whole pages, so every locked [`SecretKey`](struct.SecretKey.html)
+    /// counts as at least one page (typically 4 KiB) against that limit
+    pub fn locked(bytes: &[u8]) -> Result<SecretKey<'static>, Error> {
+        Ok(SecretKey {
+            inner: Container::Locked(Arc::new(LockedBytes::new(bytes)?)),
+        })
+    }
     /// Read-only access to the underlying byte buffer
     pub fn as_bytes(&self) -> &[u8] {
         match self.inner {
             Container::Borrowed(ref bytes) => bytes,
             Container::BorrowedMut(ref bytes) => bytes,
             Container::Owned(ref bytes) => bytes,
+            Container::Locked(ref bytes) => bytes,
+        }
+    }
+    /// Indicates whether the underlying byte buffer is locked into RAM or not, i.e. whether
+    /// the [`SecretKey`](struct.SecretKey.html) was constructed with
+    /// [`locked`](struct.SecretKey.html#method.locked) (or is a copy of such a
+    /// [`SecretKey`](struct.SecretKey.html))
+    pub fn is_locked(&self) -> bool {
+        match self.inner {
+            Container::Locked(_) => true,
+            Container::Borrowed(_) | Container::BorrowedMut(_) | Container::Owned(_) => false,
         }
     }
     /// Indicates whether the underlying byte buffer is mutable or not. The underlying byte
@@ -175,6 +212,9 @@ impl<'a> SecretKey<'a> {
     /// [`SecretKey`](struct.SecretKey.html) was constructed from an immutable reference
     /// (such as a `&str` or a `&[u8]`). The [`SecretKey`](struct.SecretKey.html) must be mutable
     /// in order to hash or verify with the `secret_key_clearing` configuration set to `true`
+    ///
+    /// A locked [`SecretKey`](struct.SecretKey.html) counts as mutable: clearing it drops it, and
+    /// its bytes are wiped once the last copy of it is dropped
     pub fn is_mutable(&self) -> bool {
         match self.inner {
             Container::Borrowed(_) => false,
@@ -185,9 +225,11 @@ impl<'a> SecretKey<'a> {
     pub fn len(&self) -> usize {
         self.as_bytes().len()
     }
-    /// Clones the underlying byte buffer and returns a new
-    /// [`SecretKey`](struct.SecretKey.html) with a `static` lifetime. Use this method if you
-    /// would like to move a [`SecretKey`](struct.SecretKey.html) to another thread
+    /// Clones the underlying byte buffer and returns a new [`SecretKey`](struct.SecretKey.html)
+    /// with a `static` lifetime. Use this method if you would like to move a
+    /// [`SecretKey`](struct.SecretKey.html) to another thread. The copy of a locked
+    /// [`SecretKey`](struct.SecretKey.html) is locked too: rather than being cloned, the locked
+    /// bytes are shared, and they are wiped once the last copy is dropped
     pub fn to_owned(&self) -> SecretKey<'static> {
         SecretKey {
             inner: self.inner.to_owned(),
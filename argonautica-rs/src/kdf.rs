@@ -0,0 +1,483 @@
+//! Argon2 as a general-purpose key derivation function (see
+//! [`KdfParams`](struct.KdfParams.html)).
+//!
+//! [`Hasher`](../struct.Hasher.html) is built for storing passwords: it creates a new salt
+//! for every hash and produces hashes of a fixed, modest length. This module instead derives
+//! key material of any length from a passphrase and a salt that you keep, e.g. to encrypt a
+//! file with a key derived from a passphrase. [`KdfParams`](struct.KdfParams.html) holds
+//! everything needed to derive the same key material again and can be stored alongside the
+//! encrypted data, either as a string (see
+//! [`KdfParams::encode`](struct.KdfParams.html#method.encode)) or, with the `serde` feature,
+//! in any format serde supports.
+//!
+//! ```
+//! extern crate argonautica;
+//!
+//! use argonautica::kdf::KdfParams;
+//!
+//! fn main() {
+//!     let mut params = KdfParams::random().unwrap();
+//!     params.configure_iterations(2).configure_memory_size(1024);
+//!     let subkeys = params
+//!         .derive_subkeys("P@ssw0rd", &[("encryption", 32), ("mac", 32)])
+//!         .unwrap();
+//!     assert_eq!(subkeys.get("encryption").unwrap().len(), 32);
+//!
+//!     // Later, e.g. when decrypting, derive the same subkeys from the stored parameters
+//!     let params = params.encode().parse::<KdfParams>().unwrap();
+//!     let subkeys2 = params
+//!         .derive_subkeys("P@ssw0rd", &[("encryption", 32), ("mac", 32)])
+//!         .unwrap();
+//!     assert_eq!(subkeys.get("mac"), subkeys2.get("mac"));
+//! }
+//! ```
+use std::cmp;
+use std::fmt;
+use std::str::FromStr;
+
+use base64;
+use blake2_rfc::blake2b::Blake2b;
+
+use backend::decode_params_rust;
+use config::defaults::{
+    default_lanes, default_memory_size, DEFAULT_ITERATIONS, DEFAULT_SALT_LEN, DEFAULT_VARIANT,
+    DEFAULT_VERSION,
+};
+use config::{Variant, Version};
+use input::Password;
+use utils::{generate_random_bytes, wipe};
+use {Error, ErrorKind, Hasher};
+
+/// The length (in bytes) of the key material that subkeys are expanded from, which is also the
+/// output length of BLAKE2b
+const SUBKEY_SEED_LEN: u32 = 64;
+
+/// The parameters of a key derivation: the Argon2 variant, version, iterations, lanes and
+/// memory size, plus the salt.
+///
+/// Deriving key material from the same passphrase with the same parameters always yields the
+/// same bytes; so, unlike with password hashes, the salt is not regenerated. Keep the
+/// parameters (they are not secret) to derive the key material again later
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct KdfParams {
+    iterations: u32,
+    lanes: u32,
+    memory_size: u32,
+    salt: Vec<u8>,
+    variant: Variant,
+    version: Version,
+}
+
+impl KdfParams {
+    /// Creates new [`KdfParams`](struct.KdfParams.html) with the given salt (which must be at
+    /// least 8 bytes long) and the same defaults as [`Hasher`](../struct.Hasher.html):
+    /// * `iterations`: `192`
+    /// * `lanes`: the number of cores available to the process
+    /// * `memory_size`: derived from the memory available to the process (see
+    ///   [`default_memory_size`](../config/fn.default_memory_size.html))
+    /// * `variant`: `Variant::Argon2id`
+    /// * `version`: `Version::_0x13`
+    pub fn new<S>(salt: S) -> KdfParams
+    where
+        S: AsRef<[u8]>,
+    {
+        KdfParams {
+            iterations: DEFAULT_ITERATIONS,
+            lanes: default_lanes(),
+            memory_size: default_memory_size(),
+            salt: salt.as_ref().to_vec(),
+            variant: DEFAULT_VARIANT,
+            version: DEFAULT_VERSION,
+        }
+    }
+    /// Same as [`new`](struct.KdfParams.html#method.new), with a new, cryptographically-secure,
+    /// random salt of `32` bytes
+    pub fn random() -> Result<KdfParams, Error> {
+        Ok(KdfParams::new(generate_random_bytes(DEFAULT_SALT_LEN)?))
+    }
+    /// Allows you to configure the number of iterations
+    pub fn configure_iterations(&mut self, iterations: u32) -> &mut KdfParams {
+        self.iterations = iterations;
+        self
+    }
+    /// Allows you to configure the number of lanes
+    pub fn configure_lanes(&mut self, lanes: u32) -> &mut KdfParams {
+        self.lanes = lanes;
+        self
+    }
+    /// Allows you to configure the memory size (in kibibytes)
+    pub fn configure_memory_size(&mut self, memory_size: u32) -> &mut KdfParams {
+        self.memory_size = memory_size;
+        self
+    }
+    /// Allows you to configure the Argon2 variant
+    pub fn configure_variant(&mut self, variant: Variant) -> &mut KdfParams {
+        self.variant = variant;
+        self
+    }
+    /// Allows you to configure the Argon2 version
+    pub fn configure_version(&mut self, version: Version) -> &mut KdfParams {
+        self.version = version;
+        self
+    }
+    /// Derives `len` bytes (at least `4`) of key material from `passphrase`
+    pub fn derive<'a, P>(&self, passphrase: P, len: u32) -> Result<KeyMaterial, Error>
+    where
+        P: Into<Password<'a>>,
+    {
+        let mut hasher = Hasher::default();
+        hasher
+            .configure_hash_len(len)
+            .configure_iterations(self.iterations)
+            .configure_lanes(self.lanes)
+            .configure_memory_size(self.memory_size)
+            .configure_variant(self.variant)
+            .configure_version(self.version)
+            .opt_out_of_secret_key(true)
+            .with_password(passphrase)
+            .with_salt(&self.salt[..]);
+        let hash_raw = hasher.hash_raw()?;
+        Ok(KeyMaterial(hash_raw.raw_hash_bytes().to_vec()))
+    }
+    /// Derives labelled subkeys from `passphrase`, e.g. `&[("encryption", 32), ("mac", 32)]`
+    /// for a 32-byte encryption key and a 32-byte MAC key. Argon2 runs once, to derive 64 bytes
+    /// of key material; each subkey is then expanded from it with BLAKE2b keyed with the key
+    /// material, over the label and length of the subkey. So subkeys with different labels are
+    /// independent of each other, and a subkey only depends on the passphrase, the parameters
+    /// and its own label and length, not on which other subkeys are derived with it. Labels
+    /// must be unique and lengths must be greater than `0`, or else an
+    /// [`Error`](../struct.Error.html) of kind
+    /// [`KdfSubkeysInvalidError`](../enum.ErrorKind.html#variant.KdfSubkeysInvalidError) is
+    /// returned
+    pub fn derive_subkeys<'a, P>(
+        &self,
+        passphrase: P,
+        subkeys: &[(&str, u32)],
+    ) -> Result<Subkeys, Error>
+    where
+        P: Into<Password<'a>>,
+    {
+        for (i, &(label, subkey_len)) in subkeys.iter().enumerate() {
+            if subkey_len == 0 {
+                return Err(Error::new(ErrorKind::KdfSubkeysInvalidError)
+                    .add_context(format!("Subkey {:?} has a length of 0", label)));
+            }
+            if subkeys[..i].iter().any(|&(other, _)| other == label) {
+                return Err(Error::new(ErrorKind::KdfSubkeysInvalidError)
+                    .add_context(format!("Duplicate subkey label: {:?}", label)));
+            }
+        }
+        let seed = self.derive(passphrase, SUBKEY_SEED_LEN)?;
+        let subkeys = subkeys
+            .iter()
+            .map(|&(label, len)| (label.to_string(), expand_subkey(&seed, label, len)))
+            .collect();
+        Ok(Subkeys(subkeys))
+    }
+    /// Encodes the parameters as a string in the
+    /// [PHC string format](https://github.com/P-H-C/phc-string-format/blob/master/phc-sf-spec.md)
+    /// without a hash, e.g. `$argon2id$v=19$m=4096,t=192,p=4$c29tZXNhbHQ`. Parse it back with
+    /// `str::parse`
+    pub fn encode(&self) -> String {
+        format!(
+            "${}$v={}$m={},t={},p={}${}",
+            self.variant.as_str(),
+            self.version.as_str(),
+            self.memory_size,
+            self.iterations,
+            self.lanes,
+            base64::encode_config(&self.salt, base64::STANDARD_NO_PAD),
+        )
+    }
+    #[allow(missing_docs)]
+    pub fn iterations(&self) -> u32 {
+        self.iterations
+    }
+    #[allow(missing_docs)]
+    pub fn lanes(&self) -> u32 {
+        self.lanes
+    }
+    #[allow(missing_docs)]
+    pub fn memory_size(&self) -> u32 {
+        self.memory_size
+    }
+    #[allow(missing_docs)]
+    pub fn salt(&self) -> &[u8] {
+        &self.salt
+    }
+    #[allow(missing_docs)]
+    pub fn variant(&self) -> Variant {
+        self.variant
+    }
+    #[allow(missing_docs)]
+    pub fn version(&self) -> Version {
+        self.version
+    }
+}
+
+impl fmt::Display for KdfParams {
+    /// Same as [`encode`](struct.KdfParams.html#method.encode)
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.encode())
+    }
+}
+
+impl FromStr for KdfParams {
+    type Err = Error;
+
+    /// Parses parameters encoded with [`encode`](struct.KdfParams.html#method.encode)
+    fn from_str(s: &str) -> Result<KdfParams, Error> {
+        let params = decode_params_rust(s)?;
+        if params.key_id().is_some() || params.data().is_some() {
+            return Err(Error::new(ErrorKind::HashDecodeError)
+                .add_context(format!("Unexpected keyid or data parameter. Params: {}", s)));
+        }
+        Ok(KdfParams {
+            iterations: params.iterations(),
+            lanes: params.lanes(),
+            memory_size: params.memory_size(),
+            salt: params.raw_salt_bytes().to_vec(),
+            variant: params.variant(),
+            version: params.version(),
+        })
+    }
+}
+
+/// Key material derived with [`KdfParams`](struct.KdfParams.html). The bytes are wiped when
+/// it is dropped
+#[derive(Clone, Eq, PartialEq, Hash)]
+pub struct KeyMaterial(Vec<u8>);
+
+impl KeyMaterial {
+    /// Read-only access to the key material
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+    /// Returns `true` if the key material is empty
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+    /// The length of the key material (in number of bytes)
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+}
+
+impl AsRef<[u8]> for KeyMaterial {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl fmt::Debug for KeyMaterial {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "****")
+    }
+}
+
+impl Drop for KeyMaterial {
+    fn drop(&mut self) {
+        wipe(&mut self.0);
+    }
+}
+
+/// Expands `seed` into a subkey of `len` bytes bound to `label`: the concatenation of
+/// `BLAKE2b(key = seed, len || label length || label || counter)` for `counter` = `0`, `1`, ...
+/// (all integers little-endian), truncated to `len` bytes
+fn expand_subkey(seed: &KeyMaterial, label: &str, len: u32) -> KeyMaterial {
+    // Allocated in full up front, so that no unwiped copies are left behind by reallocations
+    let mut subkey = Vec::with_capacity(len as usize);
+    let mut counter = 0u32;
+    while subkey.len() < len as usize {
+        let mut state = Blake2b::with_key(SUBKEY_SEED_LEN as usize, seed.as_bytes());
+        state.update(&len.to_le_bytes());
+        state.update(&(label.len() as u64).to_le_bytes());
+        state.update(label.as_bytes());
+        state.update(&counter.to_le_bytes());
+        let block = state.finalize();
+        let n = cmp::min(block.len(), len as usize - subkey.len());
+        subkey.extend_from_slice(&block.as_bytes()[..n]);
+        counter += 1;
+    }
+    KeyMaterial(subkey)
+}
+
+/// Labelled subkeys derived with
+/// [`KdfParams::derive_subkeys`](struct.KdfParams.html#method.derive_subkeys), in the order
+/// they were requested
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct Subkeys(Vec<(String, KeyMaterial)>);
+
+impl Subkeys {
+    /// Returns the subkey with the given label, if any
+    pub fn get<S>(&self, label: S) -> Option<&[u8]>
+    where
+        S: AsRef<str>,
+    {
+        self.0
+            .iter()
+            .find(|(other, _)| other == label.as_ref())
+            .map(|(_, subkey)| subkey.as_bytes())
+    }
+    /// Returns `true` if there are no subkeys
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+    /// Returns the labels of the subkeys, in the order they were requested
+    pub fn labels(&self) -> Vec<&str> {
+        self.0.iter().map(|(label, _)| &label[..]).collect()
+    }
+    /// The number of subkeys
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params() -> KdfParams {
+        let mut params = KdfParams::new("somesalt");
+        params
+            .configure_iterations(2)
+            .configure_lanes(2)
+            .configure_memory_size(64);
+        params
+    }
+
+    #[test]
+    fn test_derive() {
+        let params = params();
+        for &len in &[4, 32, 64, 65, 1000] {
+            let key_material = params.derive("P@ssw0rd", len).unwrap();
+            assert_eq!(key_material.len(), len as usize);
+            assert_eq!(key_material, params.derive("P@ssw0rd", len).unwrap());
+            assert_ne!(key_material, params.derive("P@ssw0rd2", len).unwrap());
+        }
+
+        // Same as a hash of the same length with the same parameters
+        let mut hasher = Hasher::default();
+        let hash_raw = hasher
+            .configure_hash_len(100)
+            .configure_iterations(2)
+            .configure_lanes(2)
+            .configure_memory_size(64)
+            .opt_out_of_secret_key(true)
+            .with_password("P@ssw0rd")
+            .with_salt("somesalt")
+            .hash_raw()
+            .unwrap();
+        let key_material = params.derive("P@ssw0rd", 100).unwrap();
+        assert_eq!(key_material.as_bytes(), hash_raw.raw_hash_bytes());
+
+        let err = params.derive("P@ssw0rd", 3).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::HashLenTooShortError);
+        let err = KdfParams::new("salt").derive("P@ssw0rd", 32).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::SaltTooShortError);
+    }
+
+    #[test]
+    fn test_derive_subkeys() {
+        let params = params();
+        let subkeys = params
+            .derive_subkeys("P@ssw0rd", &[("encryption", 32), ("mac", 16)])
+            .unwrap();
+        assert_eq!(subkeys.len(), 2);
+        assert_eq!(subkeys.labels(), vec!["encryption", "mac"]);
+        assert_eq!(subkeys.get("encryption").unwrap().len(), 32);
+        assert_eq!(subkeys.get("mac").unwrap().len(), 16);
+        assert!(subkeys.get("other").is_none());
+
+        // Each subkey is bound to its label, not to its position among the subkeys
+        let key_material = params.derive("P@ssw0rd", 48).unwrap();
+        assert_ne!(
+            subkeys.get("encryption").unwrap(),
+            &key_material.as_bytes()[..32]
+        );
+        assert_ne!(subkeys.get("mac").unwrap(), &key_material.as_bytes()[32..]);
+        let swapped = params
+            .derive_subkeys("P@ssw0rd", &[("mac", 16), ("encryption", 32), ("other", 8)])
+            .unwrap();
+        assert_eq!(swapped.get("encryption"), subkeys.get("encryption"));
+        assert_eq!(swapped.get("mac"), subkeys.get("mac"));
+        let relabelled = params
+            .derive_subkeys("P@ssw0rd", &[("encryption2", 32), ("mac", 16)])
+            .unwrap();
+        assert_ne!(relabelled.get("encryption2"), subkeys.get("encryption"));
+
+        // A subkey also depends on its length, and longer subkeys are expanded block by block
+        for &len in &[64, 65, 200] {
+            let longer = params
+                .derive_subkeys("P@ssw0rd", &[("encryption", len)])
+                .unwrap();
+            let longer = longer.get("encryption").unwrap();
+            assert_eq!(longer.len(), len as usize);
+            assert_ne!(&longer[..32], subkeys.get("encryption").unwrap());
+        }
+        assert_ne!(
+            params
+                .derive_subkeys("P@ssw0rd2", &[("mac", 16)])
+                .unwrap()
+                .get("mac"),
+            subkeys.get("mac")
+        );
+
+        let invalid: &[&[(&str, u32)]] = &[
+            &[("encryption", 32), ("encryption", 32)],
+            &[("encryption", 32), ("mac", 0)],
+        ];
+        for subkeys in invalid {
+            let err = params.derive_subkeys("P@ssw0rd", subkeys).unwrap_err();
+            assert_eq!(err.kind(), ErrorKind::KdfSubkeysInvalidError);
+        }
+    }
+
+    #[test]
+    fn test_encode() {
+        let params = params();
+        let encoded = params.encode();
+        assert_eq!(&encoded, "$argon2id$v=19$m=64,t=2,p=2$c29tZXNhbHQ");
+        assert_eq!(params.to_string(), encoded);
+        assert_eq!(encoded.parse::<KdfParams>().unwrap(), params);
+
+        let random = KdfParams::random().unwrap();
+        assert_eq!(random.salt().len(), DEFAULT_SALT_LEN as usize);
+        assert_eq!(random.encode().parse::<KdfParams>().unwrap(), random);
+
+        for s in &[
+            "$argon2id$v=19$m=64,t=2,p=2$c29tZXNhbHQ$aGFzaA",
+            "$argon2id$v=19$m=64,t=2,p=2,keyid=AQID$c29tZXNhbHQ",
+            "$argon2id$v=19$m=64,t=2$c29tZXNhbHQ",
+        ] {
+            let err = s.parse::<KdfParams>().unwrap_err();
+            assert_eq!(err.kind(), ErrorKind::HashDecodeError);
+        }
+    }
+
+    #[test]
+    fn test_send() {
+        fn assert_send<T: Send>() {}
+        assert_send::<KdfParams>();
+        assert_send::<KeyMaterial>();
+        assert_send::<Subkeys>();
+    }
+
+    #[test]
+    fn test_sync() {
+        fn assert_sync<T: Sync>() {}
+        assert_sync::<KdfParams>();
+        assert_sync::<KeyMaterial>();
+        assert_sync::<Subkeys>();
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        let params = params();
+        let json = ::serde_json::to_string(&params).unwrap();
+        assert_eq!(::serde_json::from_str::<KdfParams>(&json).unwrap(), params);
+    }
+}
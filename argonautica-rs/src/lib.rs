@@ -85,6 +85,14 @@
 //!     assert!(is_valid);
 //! }
 //! ```
+//! The parameters of a hash (memory size, iterations, etc.) are read from the hash itself, so
+//! [`Verifier`](struct.Verifier.html) checks them against a
+//! [`VerifierPolicy`](config/struct.VerifierPolicy.html) and refuses to verify against hashes
+//! that would take an unreasonable amount of memory or time. If you create hashes with more
+//! memory or iterations than the default policy allows, configure your
+//! [`Verifier`](struct.Verifier.html) with a custom policy (see
+//! [`configure_policy`](struct.Verifier.html#method.configure_policy)).
+//!
 //! # Alternatives
 //!
 //! If <b>argonautica</b> isn't your cup of tea, other Rust crates that will do Argon2 hashing for you
@@ -116,12 +124,12 @@
 //!     let mut hasher = Hasher::default();
 //!     hasher
 //!         .configure_backend(Backend::C) // Default is `Backend::C`
-//!         // 👆 argonautica was designed to support multiple backends (meaning multiple
-//!         // implementations of the underlying Argon2 algorithm). Currently only the C backend
-//!         // is supported, which uses the canonical Argon2 library written in C to actually
-//!         // do the work. In the future hopefully a Rust backend will also be supported, but,
-//!         // for the moment, you must use `Backend::C`, which is the default. Using
-//!         // `Backend::Rust` will result in an error (again, for the moment).
+//!         // 👆 argonautica supports multiple backends (meaning multiple implementations of
+//!         // the underlying Argon2 algorithm). The C backend uses the canonical Argon2 library
+//!         // written in C to actually do the work; the Rust backend is a pure Rust
+//!         // implementation. Both produce exactly the same hashes. `Backend::C` is the default
+//!         // unless you build argonautica without the `backend-c` feature, in which case only
+//!         // `Backend::Rust` is available (and is the default).
 //!         .configure_cpu_pool(CpuPool::new(2))
 //!         // 👆 There are two non-blocking methods on `Hasher` that perform computation on
 //!         // a separate thread and return a `Future` instead of a `Result` (`hash_non_blocking`
@@ -130,8 +138,8 @@
 //!         // methods `hash` and `hash_raw` do not use a 'CpuPool'; so if you are using only
 //!         // these blocking methods you can ignore this configuration entirely. If, however,
 //!         // you are using the non-blocking methods and would like to provide your own `CpuPool`
-//!         // instead of using the default, which is a lazily created `CpuPool` with the number
-//!         // of threads equal to the number of logical cores on your machine, you can
+//!         // instead of using the default, which is a lazily created thread pool shared by the
+//!         // whole process (see `executor::configure_global_thread_pool`), you can
 //!         // configure your `Hasher` with a custom `CpuPool` using this method. This
 //!         // might be useful if, for example, you are writing code in an environment which
 //!         // makes heavy use of futures, the code you are writing uses both a `Hasher` and
@@ -153,13 +161,15 @@
 //!         // size parameter) in order to increase the time it takes to hash to the maximum you
 //!         // can reasonably allow for your use-case (e.g. to probably about 300-500 milliseconds
 //!         // for the use-case of hashing user passwords for a website)
-//!         .configure_lanes(2) // Default is number of logical cores on your machine
+//!         .configure_lanes(2) // Default is number of cores available to your program
 //!         // 👆 Argon2 can break up its work into one or more "lanes" during some parts of
 //!         // the hashing algorithm. If you configure it with multiple lanes and you also
 //!         // use multiple threads (see below) the hashing algorithm will performed its
 //!         // work in parallel in some parts, potentially speeding up the time it takes to
 //!         // produce a hash without diminishing the security of the result. By default,
-//!         // the number of lanes is set to the number of logical cores on your machine
+//!         // the number of lanes is set to the number of cores available to your program, i.e.
+//!         // the number of logical cores on your machine or, in a container, the CPU quota
+//!         // of the container if that is lower (see `config::environment`)
 //!         .configure_memory_size(4096) // Default is `4096`
 //!         // 👆 Argon2 has a notion of "memory size" or "memory cost" (in kibibytes). All else
 //!         // equal and generally speaking, the greater the memory size, the longer it takes to
@@ -190,13 +200,13 @@
 //!         // you will be required to provide `Hasher` with a mutable secret key (e.g.
 //!         // a `String`, a `Vec<u8>`, a `&mut str`, or a `&mut [u8]` instead of a `&str`
 //!         // or a `&[u8]`)
-//!         .configure_threads(2) // Default is number of logical cores on your machine
+//!         .configure_threads(2) // Default is number of cores available to your program
 //!         // 👆 If you have configured `Hasher` to use more than one lane (see above), you
 //!         // can get the hashing algorithm to run in parallel during some parts of the
 //!         // computation by setting the number of threads to be greater than one as well,
 //!         // potentially speeding up the time it takes to produce a hash without diminishing
 //!         // the security of the result. By default, the number of threads is set to the number
-//!         // of logical cores on your machine. If you set the number of threads to a number
+//!         // of cores available to your program. If you set the number of threads to a number
 //!         // greater than the number of lanes, `Hasher` will automatically reduce the number
 //!         // of threads to the number of lanes
 //!         .configure_variant(Variant::Argon2id) // Default is `Variant::Argon2id`
@@ -240,25 +250,50 @@
 //! <b>argonautica</b> should be relatively straightforward to include in your Rust project:
 //! * Place `extern crate argonautica;` in your code (typically in either `lib.rs` or `main.rs`)
 //! * In the `[dependencies]` section of your `Cargo.toml`, place ...
-//!     * ... if you're building for your own machine ...
-//!         * `argonautica = { version = "0.2", features = ["simd"] }`, or
-//!         * `argonautica = { version = "0.2", features = ["serde", "simd"] }`
-//!     * ... if you're building for a different machine ...
-//!         * `argonautica = "0.2"`, or
-//!         * `argonautica = { version = "0.2", features = ["serde"] }`
-//!
-//! That said, <b>argonautica</b> uses [cc](https://github.com/alexcrichton/cc-rs) and
+//!     * `argonautica = "0.2"`, or
+//!     * `argonautica = { version = "0.2", features = ["serde"] }`, or
+//!     * `argonautica = { version = "0.2", features = ["tokio"] }` (to run the async methods
+//!       on the blocking thread pool of a [tokio](https://tokio.rs) runtime), or
+//!     * `argonautica = { version = "0.2", features = ["envelope"] }` (to seal payloads with
+//!       a passphrase using the [`envelope`](envelope/index.html) module), or
+//!     * ... if you don't have (or don't want) a C toolchain ...
+//!         * `argonautica = { version = "0.2", default-features = false }`
+//!
+//! There is no need to build for a specific machine: both backends include SSE2 and AVX2 versions
+//! of the Argon2 block compression function alongside a portable one and pick between them at
+//! runtime based on the CPU they are running on, so a single build can safely be deployed to
+//! machines with different instruction sets. (The `simd` feature from earlier versions is no
+//! longer needed and has no effect.)
+//!
+//! That said, by default <b>argonautica</b> uses [cc](https://github.com/alexcrichton/cc-rs) and
 //! [bindgen](https://github.com/rust-lang-nursery/rust-bindgen) to compile the canonical
 //! [C implemenation](https://github.com/P-H-C/phc-winner-argon2) of Argon2 into a
-//! static archive during the build process. This means you need a C compiler on your
-//! machine in order to build <b>argonautica</b>. More specifically, you need:
+//! static archive during the build process (the `backend-c` feature). This means you need a
+//! C compiler on your machine in order to build <b>argonautica</b> with its default features.
+//! If you turn off default features, only the pure Rust backend is built and none of the
+//! following is required. Otherwise, you need:
 //! * [LLVM/Clang](https://llvm.org/) (version 3.9 or higher)
 //!     * Mac OS: `brew install llvm`, which requires [Homebrew](https://brew.sh/)
 //!     * Debian-based linux: `apt-get install clang llvm-dev libclang-dev`
 //!     * Arch linux: `pacman -S clang`
 //!     * Windows: Download a pre-built binary [here](http://releases.llvm.org/download.html)
 //!
-//! <b>argonautica</b> runs on stable Rust version 1.32.0 or greater.
+//! <b>argonautica</b> runs on stable Rust version 1.57.0 or greater, which the Rust backend needs
+//! for `Vec::try_reserve_exact`.
+//!
+//! There is also an `argonautica` command-line tool, which hashes (`hash`) and verifies (`verify`)
+//! passwords, prints the parameters of hashes (`inspect`) and generates secret keys (`gen-key`).
+//! Install it with `cargo install argonautica --features cli` and run `argonautica help` for
+//! details. `verify` exits with status `0` if the password matches, `1` if it does not and `2` on
+//! errors. `argonautica calibrate --target 400ms --max-memory 256MiB` tunes the parameters to the
+//! machine it runs on and prints them as a TOML (or JSON) profile, which deserializes (with the
+//! `serde` feature) into a [`HasherConfig`](config/struct.HasherConfig.html) that you can pass to
+//! [`Hasher::configure_from`](struct.Hasher.html#method.configure_from).
+//!
+//! `argonautica audit users.csv --csv-column hash --profile profile.toml --format json` reports the
+//! variants and parameters of stored hashes (one per line, or in a CSV column) and flags the ones
+//! that do not parse, exceed the `--max-*` limits or are weaker than the profile (exiting with
+//! status `1` if any are flagged); the [`audit`](audit/index.html) module does the same from Rust.
 //!
 //! # License
 //!
@@ -280,17 +315,18 @@
 extern crate base64;
 #[macro_use]
 extern crate bitflags;
-#[cfg(feature = "development")]
 extern crate blake2_rfc;
+#[cfg(feature = "envelope")]
+extern crate chacha20poly1305;
 #[macro_use]
 extern crate failure;
 extern crate futures;
 extern crate futures_cpupool;
+#[macro_use]
+extern crate lazy_static;
 extern crate libc;
 #[macro_use]
 extern crate log;
-#[macro_use]
-extern crate nom;
 extern crate num_cpus;
 extern crate rand;
 extern crate scopeguard;
@@ -299,10 +335,19 @@ extern crate scopeguard;
 extern crate serde;
 #[cfg(all(test, feature = "serde"))]
 extern crate serde_json;
+extern crate subtle;
+#[cfg(feature = "tokio")]
+extern crate tokio;
 
+pub mod allocator;
+pub mod audit;
 mod backend;
+pub mod budget;
+#[cfg(feature = "envelope")]
+pub mod envelope;
 mod error;
 mod error_kind;
+#[cfg(feature = "backend-c")]
 mod ffi;
 mod hasher;
 mod verifier;
@@ -310,8 +355,10 @@ mod verifier;
 pub mod config;
 pub use error::Error;
 pub use error_kind::ErrorKind;
+pub mod executor;
 pub use hasher::Hasher;
 pub mod input;
+pub mod kdf;
 pub mod output;
 pub mod utils;
 pub use verifier::Verifier;
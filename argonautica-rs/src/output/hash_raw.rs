@@ -1,9 +1,41 @@
+use std::hash::{Hash, Hasher};
 use std::str::FromStr;
 
 use backend::decode_rust;
-use config::{Variant, Version};
+use config::{HasherConfig, Variant, Version};
+use output::RehashReasons;
+use utils::{constant_time_eq, wipe};
 use Error;
 
+impl Drop for HashRaw {
+    /// The raw hash bytes are wiped, since (like the password) they can be used to check
+    /// password guesses
+    fn drop(&mut self) {
+        wipe(&mut self.raw_hash_bytes);
+    }
+}
+
+impl Hash for HashRaw {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.data.hash(state);
+        self.iterations.hash(state);
+        self.key_id.hash(state);
+        self.lanes.hash(state);
+        self.memory_size.hash(state);
+        self.raw_hash_bytes.hash(state);
+        self.raw_salt_bytes.hash(state);
+        self.variant.hash(state);
+        self.version.hash(state);
+    }
+}
+
+impl PartialEq for HashRaw {
+    /// Same as [`ct_eq`](struct.HashRaw.html#method.ct_eq)
+    fn eq(&self, other: &HashRaw) -> bool {
+        self.ct_eq(other)
+    }
+}
+
 impl FromStr for HashRaw {
     ///
     type Err = Error;
@@ -28,11 +60,17 @@ impl FromStr for HashRaw {
 ///   `let hash_raw = hash_str.parse::<HashRaw>()?;`, or
 /// * Obtaining a `HashRaw` directly by calling [`hash_raw`](../struct.Hasher.html#method.hash_raw)
 ///   on a [`Hasher`](../struct.Hasher.html) (or its non-blocking equivalent)
-#[derive(Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+///
+/// Comparing two instances of [`HashRaw`](struct.HashRaw.html) with `==` is equivalent to
+/// calling [`ct_eq`](struct.HashRaw.html#method.ct_eq), i.e. the raw hash bytes are compared
+/// in constant time.
+#[derive(Clone, Debug, Eq, Ord, PartialOrd)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
 pub struct HashRaw {
+    pub(crate) data: Option<Vec<u8>>,
     pub(crate) iterations: u32,
+    pub(crate) key_id: Option<Vec<u8>>,
     pub(crate) lanes: u32,
     pub(crate) memory_size: u32,
     pub(crate) raw_hash_bytes: Vec<u8>,
@@ -42,14 +80,45 @@ pub struct HashRaw {
 }
 
 impl HashRaw {
+    /// Returns `true` if `self` and `other` are the same hash. The raw hash bytes and raw salt
+    /// bytes are compared in constant time, i.e. the comparison does not return early at the
+    /// first byte that differs (nor when the two hashes have different lengths), so the time it
+    /// takes reveals nothing about how much of a candidate hash is correct. The remaining fields
+    /// (iterations, lanes, key id, etc.) are public parameters and are compared normally
+    pub fn ct_eq(&self, other: &HashRaw) -> bool {
+        let hash_eq = constant_time_eq(&self.raw_hash_bytes, &other.raw_hash_bytes);
+        let salt_eq = constant_time_eq(&self.raw_salt_bytes, &other.raw_salt_bytes);
+        let params_eq = self.data == other.data
+            && self.iterations == other.iterations
+            && self.key_id == other.key_id
+            && self.lanes == other.lanes
+            && self.memory_size == other.memory_size
+            && self.variant == other.variant
+            && self.version == other.version;
+        hash_eq & salt_eq & params_eq
+    }
     /// Converts the [`HashRaw`](struct.HashRaw.html) to a string-encoded hash
     pub fn to_string(&self) -> String {
         self.encode_rust()
     }
+    /// The optional associated data stored in the hash (the `data` parameter of the
+    /// [PHC string format](https://github.com/P-H-C/phc-string-format/blob/master/phc-sf-spec.md)).
+    /// When verifying a hash that carries data, [`Verifier`](../struct.Verifier.html) uses it
+    /// as the [`AdditionalData`](../input/struct.AdditionalData.html) unless you provide
+    /// additional data yourself
+    pub fn data(&self) -> Option<&[u8]> {
+        self.data.as_ref().map(|data| &data[..])
+    }
     /// Obtain the iterations configuration that was used to produce this hash
     pub fn iterations(&self) -> u32 {
         self.iterations
     }
+    /// The optional key id stored in the hash (the `keyid` parameter of the
+    /// [PHC string format](https://github.com/P-H-C/phc-string-format/blob/master/phc-sf-spec.md)),
+    /// which identifies the secret key the hash was created with
+    pub fn key_id(&self) -> Option<&[u8]> {
+        self.key_id.as_ref().map(|key_id| &key_id[..])
+    }
     /// Obtain the lanes configuration that was used to produce this hash
     pub fn lanes(&self) -> u32 {
         self.lanes
@@ -58,6 +127,40 @@ impl HashRaw {
     pub fn memory_size(&self) -> u32 {
         self.memory_size
     }
+    /// Checks this hash against `config` (typically the configuration of the
+    /// [`Hasher`](../struct.Hasher.html) you currently use to create new hashes), returning
+    /// the reasons, if any, why the hash is weaker than what `config` would produce. If the
+    /// returned [`RehashReasons`](struct.RehashReasons.html) is empty, the hash is fine as is;
+    /// otherwise you may want to hash the password again with the current configuration the
+    /// next time you have it (e.g. right after successfully verifying it at login)
+    pub fn needs_rehash(&self, config: &HasherConfig) -> RehashReasons {
+        let mut reasons = RehashReasons::empty();
+        if self.variant != config.variant() {
+            reasons |= RehashReasons::VARIANT;
+        }
+        if self.version < config.version() {
+            reasons |= RehashReasons::VERSION;
+        }
+        if self.memory_size < config.memory_size() {
+            reasons |= RehashReasons::MEMORY_SIZE;
+        }
+        if self.iterations < config.iterations() {
+            reasons |= RehashReasons::ITERATIONS;
+        }
+        if self.lanes != config.lanes() {
+            reasons |= RehashReasons::LANES;
+        }
+        if (self.raw_hash_bytes.len() as u32) < config.hash_len() {
+            reasons |= RehashReasons::HASH_LEN;
+        }
+        if (self.raw_salt_bytes.len() as u32) < config.salt_len() {
+            reasons |= RehashReasons::SALT_LEN;
+        }
+        if config.key_id().is_some() && self.key_id() != config.key_id() {
+            reasons |= RehashReasons::KEY_ID;
+        }
+        reasons
+    }
     /// Read-only access to the raw hash bytes
     pub fn raw_hash_bytes(&self) -> &[u8] {
         &self.raw_hash_bytes
@@ -80,6 +183,110 @@ impl HashRaw {
 mod tests {
     use super::*;
 
+    fn hash_raw(raw_hash_bytes: &[u8]) -> HashRaw {
+        HashRaw {
+            data: None,
+            iterations: 192,
+            key_id: None,
+            lanes: 2,
+            memory_size: 4096,
+            raw_hash_bytes: raw_hash_bytes.to_vec(),
+            raw_salt_bytes: b"somesalt".to_vec(),
+            variant: Variant::Argon2id,
+            version: Version::_0x13,
+        }
+    }
+
+    #[test]
+    fn test_needs_rehash() {
+        use Hasher;
+
+        let mut hasher = Hasher::default();
+        hasher
+            .configure_hash_len(8)
+            .configure_iterations(192)
+            .configure_lanes(2)
+            .configure_memory_size(4096)
+            .configure_variant(Variant::Argon2id)
+            .configure_version(Version::_0x13)
+            .with_salt("somesalt");
+        let hash_raw = hash_raw(&[1, 2, 3, 4, 5, 6, 7, 8]);
+        assert!(hash_raw.needs_rehash(hasher.config()).is_empty());
+
+        // Weaker settings in the hash than in the config are reported...
+        hasher
+            .configure_iterations(256)
+            .configure_memory_size(8192)
+            .with_salt(&[0u8; 16][..]);
+        assert_eq!(
+            hash_raw.needs_rehash(hasher.config()),
+            RehashReasons::ITERATIONS | RehashReasons::MEMORY_SIZE | RehashReasons::SALT_LEN,
+        );
+
+        // ... stronger settings are not...
+        hasher
+            .configure_hash_len(4)
+            .configure_iterations(1)
+            .configure_memory_size(1024)
+            .with_salt("somesalt");
+        assert!(hash_raw.needs_rehash(hasher.config()).is_empty());
+
+        // ... and a different variant, an older version, or different lanes always are
+        hasher
+            .configure_hash_len(16)
+            .configure_lanes(4)
+            .configure_variant(Variant::Argon2i);
+        let mut old_hash_raw = hash_raw.clone();
+        old_hash_raw.version = Version::_0x10;
+        assert_eq!(
+            old_hash_raw.needs_rehash(hasher.config()),
+            RehashReasons::VARIANT
+                | RehashReasons::VERSION
+                | RehashReasons::LANES
+                | RehashReasons::HASH_LEN,
+        );
+    }
+
+    #[test]
+    fn test_ct_eq() {
+        let a = hash_raw(&[1, 2, 3, 4, 5, 6, 7, 8]);
+        assert!(a.ct_eq(&hash_raw(&[1, 2, 3, 4, 5, 6, 7, 8])));
+        assert!(!a.ct_eq(&hash_raw(&[0, 2, 3, 4, 5, 6, 7, 8])));
+        assert!(!a.ct_eq(&hash_raw(&[1, 2, 3, 4, 5, 6, 7, 9])));
+        let mut b = a.clone();
+        b.iterations += 1;
+        assert!(!a.ct_eq(&b));
+        assert_eq!(a, a.clone());
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    fn test_ct_eq_length_mismatch() {
+        let a = hash_raw(&[1, 2, 3, 4, 5, 6, 7, 8]);
+        // A prefix of the correct hash must not compare equal...
+        let prefix = hash_raw(&[1, 2, 3, 4]);
+        assert!(!a.ct_eq(&prefix));
+        assert!(!prefix.ct_eq(&a));
+        // ... nor may the correct hash followed by trailing bytes ...
+        let extended = hash_raw(&[1, 2, 3, 4, 5, 6, 7, 8, 0]);
+        assert!(!a.ct_eq(&extended));
+        assert!(!extended.ct_eq(&a));
+        // ... nor an empty hash
+        let empty = hash_raw(&[]);
+        assert!(!a.ct_eq(&empty));
+        assert!(!empty.ct_eq(&a));
+        assert!(empty.ct_eq(&hash_raw(&[])));
+    }
+
+    #[test]
+    fn test_constant_time_eq_padding_cannot_match() {
+        // Missing bytes of `actual` are compared as the complement of the corresponding byte of
+        // `expected`, so padding can never accidentally make a shorter input look correct
+        assert!(!constant_time_eq(&[0, 0, 0], &[0, 0]));
+        assert!(!constant_time_eq(&[0xff, 0xff], &[]));
+        assert!(constant_time_eq(&[], &[]));
+    }
+
     #[test]
     fn test_send() {
         fn assert_send<T: Send>() {}
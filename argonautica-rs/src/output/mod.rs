@@ -1,4 +1,8 @@
 //! Struct representing raw hash output.
 mod hash_raw;
+mod rehash_reasons;
+mod verification;
 
 pub use self::hash_raw::HashRaw;
+pub use self::rehash_reasons::RehashReasons;
+pub use self::verification::Verification;
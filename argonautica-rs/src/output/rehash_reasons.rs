@@ -0,0 +1,67 @@
+bitflags! {
+    /// The set of reasons, if any, why a hash no longer meets the current
+    /// [`HasherConfig`](../config/struct.HasherConfig.html) and should be replaced by a new hash
+    /// of the same password (e.g. the next time the user logs in successfully). Returned by
+    /// [`HashRaw::needs_rehash`](struct.HashRaw.html#method.needs_rehash) and
+    /// [`Verifier::needs_rehash`](../struct.Verifier.html#method.needs_rehash).
+    ///
+    /// An empty set (see `is_empty`) means the hash is at least as strong as the configuration
+    /// it was checked against. Otherwise, check for individual reasons with `contains`, e.g.
+    /// `reasons.contains(RehashReasons::MEMORY_SIZE)`
+    #[derive(Default)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    pub struct RehashReasons: u32 {
+        /// The hash was created with a different [`Variant`](../config/enum.Variant.html)
+        const VARIANT = 0b0000_0001;
+        /// The hash was created with an older [`Version`](../config/enum.Version.html)
+        const VERSION = 0b0000_0010;
+        /// The hash was created with a smaller memory size
+        const MEMORY_SIZE = 0b0000_0100;
+        /// The hash was created with fewer iterations
+        const ITERATIONS = 0b0000_1000;
+        /// The hash was created with a different number of lanes. More lanes is not
+        /// necessarily stronger, so any difference is reported
+        const LANES = 0b0001_0000;
+        /// The hash is shorter
+        const HASH_LEN = 0b0010_0000;
+        /// The hash was created with a shorter salt
+        const SALT_LEN = 0b0100_0000;
+        /// The hash was created with a secret key other than the active key of the
+        /// [`Keyring`](../input/struct.Keyring.html) the configuration was created with (or
+        /// without a key id at all)
+        const KEY_ID = 0b1000_0000;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_send() {
+        fn assert_send<T: Send>() {}
+        assert_send::<RehashReasons>();
+    }
+
+    #[test]
+    fn test_sync() {
+        fn assert_sync<T: Sync>() {}
+        assert_sync::<RehashReasons>();
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serialize() {
+        use serde;
+        fn assert_serialize<T: serde::Serialize>() {}
+        assert_serialize::<RehashReasons>();
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_deserialize() {
+        use serde;
+        fn assert_deserialize<'de, T: serde::Deserialize<'de>>() {}
+        assert_deserialize::<RehashReasons>();
+    }
+}
@@ -0,0 +1,72 @@
+use output::RehashReasons;
+
+/// The outcome of [`Verifier::verify_and_upgrade`](../struct.Verifier.html#method.verify_and_upgrade)
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub enum Verification {
+    /// The password does not match the hash
+    Invalid,
+    /// The password matches the hash, and the hash is at least as strong as the current
+    /// configuration; there is nothing to do
+    Valid,
+    /// The password matches the hash, but the hash was weaker than the current configuration
+    /// (for the reasons given). `hash` is a fresh string-encoded hash of the same password
+    /// created with the current configuration, which should replace the stored hash
+    ValidWithRehash {
+        /// The new string-encoded hash
+        hash: String,
+        /// Why the stored hash needed to be replaced
+        reasons: RehashReasons,
+    },
+}
+
+impl Verification {
+    /// Returns `true` if the password matched the hash, whether or not it was rehashed
+    pub fn is_valid(&self) -> bool {
+        match *self {
+            Verification::Invalid => false,
+            Verification::Valid | Verification::ValidWithRehash { .. } => true,
+        }
+    }
+    /// Returns the replacement hash, if the password matched and the hash needed to be upgraded
+    pub fn new_hash(&self) -> Option<&str> {
+        match *self {
+            Verification::ValidWithRehash { ref hash, .. } => Some(hash),
+            Verification::Invalid | Verification::Valid => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_send() {
+        fn assert_send<T: Send>() {}
+        assert_send::<Verification>();
+    }
+
+    #[test]
+    fn test_sync() {
+        fn assert_sync<T: Sync>() {}
+        assert_sync::<Verification>();
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serialize() {
+        use serde;
+        fn assert_serialize<T: serde::Serialize>() {}
+        assert_serialize::<Verification>();
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_deserialize() {
+        use serde;
+        fn assert_deserialize<'de, T: serde::Deserialize<'de>>() {}
+        assert_deserialize::<Verification>();
+    }
+}
@@ -1,8 +1,12 @@
 //! Utility functions for generating random bytes, which can be useful for generating
 //! [`SecretKey`](input/struct.SecretKey.html)s, for example.
+use std::mem;
+use std::ptr;
+
 use base64;
 use rand::rngs::OsRng;
 use rand::RngCore;
+use subtle::{Choice, ConstantTimeEq};
 
 use {Error, ErrorKind};
 
@@ -46,3 +50,56 @@ pub fn generate_random_base64_encoded_string_config(
     let output = base64::encode_config(&bytes, config);
     Ok(output)
 }
+
+/// Compares `expected` against `actual` in constant time. The running time depends only on the
+/// length of `expected` (which is typically public, e.g. the configured hash length); it does not
+/// depend on where (or whether) the inputs differ, nor on the length of `actual`.
+pub(crate) fn constant_time_eq(expected: &[u8], actual: &[u8]) -> bool {
+    let len_eq = (expected.len() as u64).ct_eq(&(actual.len() as u64));
+    let mut bytes_eq = Choice::from(1);
+    for (i, byte) in expected.iter().enumerate() {
+        let other = actual.get(i).cloned().unwrap_or(!*byte);
+        bytes_eq &= byte.ct_eq(&other);
+    }
+    (len_eq & bytes_eq).into()
+}
+
+/// Overwrites `bytes` with zeros using volatile writes, which the compiler cannot elide even
+/// if `bytes` is never read again (e.g. because it is about to be dropped)
+pub(crate) fn wipe(bytes: &mut [u8]) {
+    unsafe { wipe_raw(bytes.as_mut_ptr(), bytes.len()) }
+}
+
+/// Same as [`wipe`](fn.wipe.html), for `len` bytes at `ptr`, which must be valid for writes
+pub(crate) unsafe fn wipe_raw(ptr: *mut u8, len: usize) {
+    let mut i = 0;
+    if ptr as usize & (mem::align_of::<u64>() - 1) == 0 {
+        while i + mem::size_of::<u64>() <= len {
+            ptr::write_volatile(ptr.add(i) as *mut u64, 0);
+            i += mem::size_of::<u64>();
+        }
+    }
+    while i < len {
+        ptr::write_volatile(ptr.add(i), 0);
+        i += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wipe() {
+        for len in &[0, 1, 7, 8, 9, 64, 1027] {
+            let mut bytes = vec![0xABu8; len + 1];
+            // Unaligned as well as aligned buffers
+            for offset in 0..2 {
+                wipe(&mut bytes[offset..offset + len]);
+                assert!(bytes[offset..offset + len].iter().all(|&byte| byte == 0));
+                bytes.iter_mut().for_each(|byte| *byte = 0xAB);
+            }
+            assert_eq!(bytes[*len], 0xAB);
+        }
+    }
+}
@@ -1,12 +1,26 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use futures::future::Either;
 use futures::Future;
 use futures_cpupool::CpuPool;
 
+use allocator::MemoryAllocator;
 use backend::decode_rust;
-use config::{default_cpu_pool, Backend, VerifierConfig};
-use input::{AdditionalData, Password, SecretKey};
-use output::HashRaw;
+use budget::MemoryBudget;
+use config::{
+    default_executor, Backend, HasherConfig, Variant, VerifierConfig, VerifierPolicy, Version,
+};
+use executor::{self, Batch, BatchJob, BlockingExecutor, Task};
+use input::{encode_key_id, AdditionalData, Keyring, Password, SecretKey};
+use output::{HashRaw, RehashReasons, Verification};
+use utils::generate_random_bytes;
 use {Error, ErrorKind, Hasher};
 
+lazy_static! {
+    static ref DUMMY_HASHES: Mutex<HashMap<DummyParams, HashRaw>> = Mutex::new(HashMap::new());
+}
+
 impl Default for Hash {
     fn default() -> Hash {
         Hash::None
@@ -28,6 +42,8 @@ impl<'a> Default for Verifier<'a> {
         Verifier {
             hash: Hash::default(),
             hasher: Hasher::default(),
+            keyring: None,
+            policy: VerifierPolicy::default(),
         }
     }
 }
@@ -39,26 +55,44 @@ impl<'a> Default for Verifier<'a> {
 pub struct Verifier<'a> {
     hash: Hash,
     hasher: Hasher<'a>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing, skip_deserializing))]
+    keyring: Option<Keyring>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    policy: VerifierPolicy,
 }
 
 impl<'a> Verifier<'a> {
     /// Creates a new [`Verifier`](struct.Verifier.html) with the following configuration:
-    /// * `backend`: [`Backend::C`](config/enum.Backend.html#variant.C)
-    /// * `cpu_pool`: A [`CpuPool`](https://docs.rs/futures-cpupool/0.1.8/futures_cpupool/struct.CpuPool.html) ...
-    ///     * with threads equal to the number of logical cores on your machine
-    ///     * that is lazily created, i.e. created only if / when you call the method that
-    ///       needs it ([`verify_non_blocking`](struct.Verifier.html#method.verify_non_blocking))
+    /// * `backend`: [`Backend::C`](config/enum.Backend.html#variant.C) (or
+    ///   [`Backend::Rust`](config/enum.Backend.html#variant.Rust) without the `backend-c` feature)
+    /// * `cpu_pool`: `None`, i.e. the non-blocking and async methods run on the
+    ///   [global thread pool](executor/fn.global_thread_pool.html), which ...
+    ///     * has threads equal to the number of cores available to the process (see
+    ///       [`configure_global_thread_pool`](executor/fn.configure_global_thread_pool.html))
+    ///     * is lazily created, i.e. created only if / when you call the methods that need it
+    ///     * is shared by every [`Hasher`](struct.Hasher.html) and
+    ///       [`Verifier`](struct.Verifier.html) in the process
+    /// * `memory_allocator`: `None`, i.e. the work area of each verification is allocated on
+    ///   the heap
+    /// * `memory_budget`: `None`
     /// * `password_clearing`: `false`
+    /// * `policy`: The default [`VerifierPolicy`](config/struct.VerifierPolicy.html) (see
+    ///   [`VerifierPolicy::new`](config/struct.VerifierPolicy.html#method.new))
     /// * `secret_key_clearing`: `false`
-    /// * `threads`: The number of logical cores on your machine
+    /// * `threads`: The number of cores available to the process (see
+    ///   [`default_threads`](config/fn.default_threads.html))
+    ///
+    /// The decoy hash of [`verify_dummy`](struct.Verifier.html#method.verify_dummy) has the
+    /// parameters of [`Hasher::new`](struct.Hasher.html#method.new) unless configured otherwise
+    /// (see [`configure_from`](struct.Verifier.html#method.configure_from))
     pub fn new() -> Verifier<'a> {
         Verifier::default()
     }
     /// Allows you to configure [`Verifier`](struct.Verifier.html) with a custom backend. The
-    /// default backend is [`Backend::C`](config/enum.Backend.html#variant.C), <i>which is
-    /// currently the only backend supported. A Rust backend is planned, but is not currently
-    /// available. If you configure a [`Verifier`](struct.Verifier.html) with
-    /// [`Backend::Rust`](config/enum.Backend.html#variant.Rust) it will error</i>
+    /// default backend is [`Backend::C`](config/enum.Backend.html#variant.C) (or
+    /// [`Backend::Rust`](config/enum.Backend.html#variant.Rust) if argonautica was built
+    /// without the `backend-c` feature). Both backends produce identical hashes; so a hash
+    /// created with one backend can be verified with the other
     pub fn configure_backend(&mut self, backend: Backend) -> &mut Verifier<'a> {
         self.hasher.config.set_backend(backend);
         self
@@ -68,14 +102,66 @@ impl<'a> Verifier<'a> {
     /// The default [`Verifier`](struct.Verifier.html) does not have a cpu pool, which is
     /// only needed for the [`verify_non_blocking`](struct.Verifier.html#method.verify_non_blocking)
     /// method. If you call [`verify_non_blocking`](struct.Verifier.html#method.verify_non_blocking)
-    /// without a cpu pool, a default cpu pool will be created for you on the fly; so even
-    /// if you never configure [`Verifier`](struct.Verifier.html) with this method you can still
-    /// use the [`verify_non_blocking`](struct.Verifier.html#method.verify_non_blocking) method.
-    /// The default cpu pool has as many threads as the number of logical cores on your machine
+    /// without a cpu pool, it runs on the configured executor (see
+    /// [`configure_executor`](struct.Verifier.html#method.configure_executor)) or, failing that,
+    /// on the [global thread pool](executor/fn.global_thread_pool.html); so even if you never
+    /// configure [`Verifier`](struct.Verifier.html) with this method you can still use the
+    /// [`verify_non_blocking`](struct.Verifier.html#method.verify_non_blocking) method
     pub fn configure_cpu_pool(&mut self, cpu_pool: CpuPool) -> &mut Verifier<'a> {
         self.hasher.config.set_cpu_pool(cpu_pool);
         self
     }
+    /// Allows you to configure [`Verifier`](struct.Verifier.html) with the
+    /// [`BlockingExecutor`](executor/trait.BlockingExecutor.html) that
+    /// [`verify_async`](struct.Verifier.html#method.verify_async) runs on. If you call
+    /// [`verify_async`](struct.Verifier.html#method.verify_async) without an executor, it runs
+    /// on the [global thread pool](executor/fn.global_thread_pool.html), which is shared by every
+    /// [`Hasher`](struct.Hasher.html) and [`Verifier`](struct.Verifier.html) in the process
+    pub fn configure_executor<E>(&mut self, executor: E) -> &mut Verifier<'a>
+    where
+        E: BlockingExecutor + 'static,
+    {
+        self.hasher.config.set_executor(Arc::new(executor));
+        self
+    }
+    /// Configures the [`Verifier`](struct.Verifier.html) from `config` (typically the
+    /// configuration of the [`Hasher`](struct.Hasher.html) you currently use to create new
+    /// hashes): its backend, password clearing, secret key clearing and threads, as well as the
+    /// hash parameters (hash length, iterations, lanes, memory size, salt length, variant and
+    /// version) of the decoy hash that
+    /// [`verify_dummy`](struct.Verifier.html#method.verify_dummy) verifies against. The
+    /// parameters of the hashes passed to [`verify`](struct.Verifier.html#method.verify) and
+    /// the like are always read from the hashes themselves
+    pub fn configure_from(&mut self, config: &HasherConfig) -> &mut Verifier<'a> {
+        self.hasher.configure_from(config);
+        self
+    }
+    /// Allows you to configure [`Verifier`](struct.Verifier.html) with a custom
+    /// [`MemoryAllocator`](allocator/trait.MemoryAllocator.html) for the work area of each
+    /// verification (see
+    /// [`Hasher::configure_memory_allocator`](struct.Hasher.html#method.configure_memory_allocator)).
+    /// The default [`Verifier`](struct.Verifier.html) does not have a memory allocator and
+    /// allocates the work area on the heap
+    pub fn configure_memory_allocator<A>(&mut self, memory_allocator: A) -> &mut Verifier<'a>
+    where
+        A: MemoryAllocator + 'static,
+    {
+        self.hasher
+            .config
+            .set_memory_allocator(Arc::new(memory_allocator));
+        self
+    }
+    /// Allows you to attach [`Verifier`](struct.Verifier.html) to a
+    /// [`MemoryBudget`](budget/struct.MemoryBudget.html), which may be shared with other
+    /// [`Hasher`](struct.Hasher.html)s and [`Verifier`](struct.Verifier.html)s. Each
+    /// verification then waits until the memory size of the hash fits within the budget (or
+    /// fails, depending on the budget's [`Admission`](budget/enum.Admission.html) policy)
+    /// before hashing. The default [`Verifier`](struct.Verifier.html) does not have a memory
+    /// budget
+    pub fn configure_memory_budget(&mut self, memory_budget: MemoryBudget) -> &mut Verifier<'a> {
+        self.hasher.config.set_memory_budget(memory_budget);
+        self
+    }
     /// Allows you to configure [`Verifier`](struct.Verifier.html) to erase the password bytes
     /// after each call to [`verify`](struct.Verifier.html#method.verify)
     /// or its non-blocking equivalent. The default is to <b>not</b> clear out the password
@@ -88,6 +174,16 @@ impl<'a> Verifier<'a> {
         self.hasher.config.set_password_clearing(boolean);
         self
     }
+    /// Allows you to configure [`Verifier`](struct.Verifier.html) with a custom
+    /// [`VerifierPolicy`](config/struct.VerifierPolicy.html), i.e. with the limits on the
+    /// parameters (memory size, iterations, lanes, hash length and salt length) of the hashes
+    /// it verifies against. Hashes that exceed these limits are rejected with an
+    /// [`Error`](struct.Error.html) before any hashing is done. The default policy is
+    /// [`VerifierPolicy::default`](config/struct.VerifierPolicy.html#method.new)
+    pub fn configure_policy(&mut self, policy: VerifierPolicy) -> &mut Verifier<'a> {
+        self.policy = policy;
+        self
+    }
     /// Allows you to configure [`Verifier`](struct.Verifier.html) to erase the secret key bytes
     /// after each call to [`verify`](struct.Verifier.html#method.verify)
     /// or its non-blocking equivalent. The default is to <b>not</b> clear out the secret key
@@ -101,7 +197,8 @@ impl<'a> Verifier<'a> {
         self
     }
     /// Allows you to configure [`Verifier`](struct.Verifier.html) to use a custom number of
-    /// threads. The default is the number of physical cores on your machine. If you choose
+    /// threads. The default is the number of cores available to the process (see
+    /// [`default_threads`](config/fn.default_threads.html)). If you choose
     /// a number of threads that is greater than the lanes configuration of your hash,
     /// [`Verifier`](struct.Verifier.html) will use the minimum of the two.
     pub fn configure_threads(&mut self, threads: u32) -> &mut Verifier<'a> {
@@ -115,6 +212,8 @@ impl<'a> Verifier<'a> {
         Verifier {
             hash: self.hash.clone(),
             hasher: self.hasher.to_owned(),
+            keyring: self.keyring.clone(),
+            policy: self.policy,
         }
     }
     /// <b><u>The primary method (blocking version)</u></b>
@@ -129,65 +228,196 @@ impl<'a> Verifier<'a> {
     /// call this method to verify that the password matches the hash or
     /// [`HashRaw`](output/struct.HashRaw.html)
     pub fn verify(&mut self) -> Result<bool, Error> {
-        match self.hash {
-            Hash::Encoded(ref s) => {
-                let hash_raw = decode_rust(s)?;
-                self.hasher
-                    .config
-                    .set_hash_len(hash_raw.raw_hash_bytes().len() as u32);
-                self.hasher.config.set_iterations(hash_raw.iterations());
-                self.hasher.config.set_lanes(hash_raw.lanes());
-                self.hasher.config.set_memory_size(hash_raw.memory_size());
-                self.hasher.config.set_opt_out_of_secret_key(true);
-                self.hasher.config.set_variant(hash_raw.variant());
-                self.hasher.config.set_version(hash_raw.version());
-                self.hasher.salt = hash_raw.raw_salt_bytes().into();
-                let hash_raw2 = self.hasher.hash_raw()?;
-                let is_valid = if hash_raw.raw_hash_bytes() == hash_raw2.raw_hash_bytes() {
-                    true
-                } else {
-                    false
-                };
-                Ok(is_valid)
-            }
-            Hash::Raw(ref hash_raw) => {
-                self.hasher
-                    .config
-                    .set_hash_len(hash_raw.raw_hash_bytes().len() as u32);
-                self.hasher.config.set_iterations(hash_raw.iterations());
-                self.hasher.config.set_lanes(hash_raw.lanes());
-                self.hasher.config.set_memory_size(hash_raw.memory_size());
-                self.hasher.config.set_opt_out_of_secret_key(true);
-                self.hasher.config.set_variant(hash_raw.variant());
-                self.hasher.config.set_version(hash_raw.version());
-                self.hasher.salt = hash_raw.raw_salt_bytes().into();
-                let hash_raw2 = self.hasher.hash_raw()?;
-                let is_valid = if hash_raw.raw_hash_bytes() == hash_raw2.raw_hash_bytes() {
-                    true
-                } else {
-                    false
-                };
-                Ok(is_valid)
-            }
+        let hash_raw = match self.hash {
+            Hash::Encoded(ref s) => decode_rust(s)?,
+            Hash::Raw(ref hash_raw) => hash_raw.clone(),
             Hash::None => return Err(Error::new(ErrorKind::HashMissingError)),
-        }
+        };
+        verify_hash_raw(
+            &mut self.hasher,
+            &hash_raw,
+            self.keyring.as_ref(),
+            &self.policy,
+        )
+    }
+    /// Verifies the password against the hash like [`verify`](struct.Verifier.html#method.verify)
+    /// and, if the password is valid but the hash is weaker than `hasher`'s configuration (see
+    /// [`needs_rehash`](struct.Verifier.html#method.needs_rehash)), also hashes the password
+    /// again with `hasher`, so that the stored hash can be upgraded without a separate step.
+    ///
+    /// `hasher` should be the [`Hasher`](struct.Hasher.html) you currently use to create new
+    /// hashes (including its secret key, if any); it is not modified. This
+    /// [`Verifier`](struct.Verifier.html)'s password clearing and secret key clearing
+    /// configuration is honored once both verifying and rehashing are done
+    pub fn verify_and_upgrade(&mut self, hasher: &Hasher) -> Result<Verification, Error> {
+        let reasons = self.needs_rehash(hasher.config())?;
+        let password_clearing = self.hasher.config.password_clearing();
+        // Keep the password around until it has been rehashed
+        self.hasher.config.set_password_clearing(false);
+        let is_valid = self.verify();
+        self.hasher.config.set_password_clearing(password_clearing);
+        let result = match is_valid {
+            Ok(false) => Ok(Verification::Invalid),
+            Ok(true) if reasons.is_empty() => Ok(Verification::Valid),
+            Ok(true) => {
+                let mut upgrade_hasher = hasher.to_owned();
+                upgrade_hasher.password = self.hasher.password.as_ref().map(|p| p.to_owned());
+                upgrade_hasher
+                    .hash()
+                    .map(|hash| Verification::ValidWithRehash { hash, reasons })
+            }
+            Err(e) => Err(e),
+        };
+        self.hasher.clear();
+        result
+    }
+    /// <b><u>The primary method (async version)</u></b>
+    ///
+    /// Same as [`verify`](struct.Verifier.html#method.verify) except the verification runs on
+    /// the configured [`BlockingExecutor`](executor/trait.BlockingExecutor.html) (see
+    /// [`configure_executor`](struct.Verifier.html#method.configure_executor)) and it returns a
+    /// [`Task`](executor/struct.Task.html), i.e. a
+    /// [`std::future::Future`](https://doc.rust-lang.org/std/future/trait.Future.html) that you
+    /// can `.await`, instead of a [`Result`](https://doc.rust-lang.org/std/result/enum.Result.html)
+    pub fn verify_async(&mut self) -> Task<bool> {
+        let mut verifier = self.to_owned();
+        let executor = verifier
+            .hasher
+            .config
+            .executor()
+            .unwrap_or_else(default_executor);
+        executor::spawn(&*executor, move || verifier.verify())
+    }
+    /// Verifies each `(hash, password)` pair of `pairs` like
+    /// [`verify_password`](struct.Verifier.html#method.verify_password), running several
+    /// verifications at a time on the configured
+    /// [`BlockingExecutor`](executor/trait.BlockingExecutor.html) (see
+    /// [`configure_executor`](struct.Verifier.html#method.configure_executor)), and returns a
+    /// [`Batch`](executor/struct.Batch.html), i.e. an iterator over the results in the same
+    /// order as `pairs`. `pairs` is consumed lazily, so it can stream from e.g. a file or a
+    /// database query.
+    ///
+    /// As with [`Hasher::hash_batch`](struct.Hasher.html#method.hash_batch), up to `threads`
+    /// verifications run at a time, each on a single thread. This
+    /// [`Verifier`](struct.Verifier.html) is not modified and its hash and password, if any,
+    /// are ignored
+    pub fn verify_batch<'b, I, H, P>(&self, pairs: I) -> Batch<'b, bool>
+    where
+        I: IntoIterator<Item = (H, P)>,
+        I::IntoIter: 'b,
+        H: AsRef<str>,
+        P: Into<Password<'b>>,
+    {
+        let mut verifier = self.to_owned();
+        verifier.hash = Hash::None;
+        verifier.hasher.password = None;
+        let parallelism = verifier.hasher.config.threads() as usize;
+        verifier.hasher.config.set_threads(1);
+        let executor = verifier
+            .hasher
+            .config
+            .executor()
+            .unwrap_or_else(default_executor);
+        let verifier = Arc::new(verifier);
+        let jobs = pairs.into_iter().map(move |(hash, password)| {
+            let verifier = verifier.clone();
+            let hash = hash.as_ref().to_string();
+            let password = password.into().to_owned();
+            Box::new(move || verifier.verify_password::<_, Password>(hash, password))
+                as BatchJob<bool>
+        });
+        executor::spawn_batch(executor, parallelism, jobs)
+    }
+    /// Verifies the [`Verifier`](struct.Verifier.html)'s password against a decoy hash and
+    /// returns `false`, e.g. when the user trying to log in does not exist. This takes as long
+    /// as verifying a real hash with the same parameters, so that the response time of a login
+    /// does not reveal whether the account exists (see
+    /// [`verify_or_dummy`](struct.Verifier.html#method.verify_or_dummy)).
+    ///
+    /// The decoy hash has random hash bytes and salt, and the hash parameters the
+    /// [`Verifier`](struct.Verifier.html) was configured with (see
+    /// [`configure_from`](struct.Verifier.html#method.configure_from)), which should match those
+    /// of your stored hashes. It is created once per set of parameters and then cached for the
+    /// lifetime of the process. Like [`verify`](struct.Verifier.html#method.verify), this
+    /// method hashes the password with the secret key and additional data, if any, clears
+    /// them according to the configuration, and returns an error if there is no password
+    pub fn verify_dummy(&mut self) -> Result<bool, Error> {
+        let hash_raw = dummy_hash_raw(&self.hasher.config)?;
+        verify_hash_raw(
+            &mut self.hasher,
+            &hash_raw,
+            self.keyring.as_ref(),
+            &self.policy,
+        )?;
+        Ok(false)
     }
     /// <b><u>The primary method (non-blocking version)</u></b>
     ///
     /// Same as [`verify`](struct.Verifier.html#method.verify) except it returns a
     /// [`Future`](https://docs.rs/futures/0.1.21/futures/future/trait.Future.html)
-    /// instead of a [`Result`](https://doc.rust-lang.org/std/result/enum.Result.html)
+    /// instead of a [`Result`](https://doc.rust-lang.org/std/result/enum.Result.html).
+    /// This is a [futures 0.1](https://docs.rs/futures/0.1.21/futures/) future; to use
+    /// `async` / `.await` instead, see [`verify_async`](struct.Verifier.html#method.verify_async)
     pub fn verify_non_blocking(&mut self) -> impl Future<Item = bool, Error = Error> {
         let mut verifier = self.to_owned();
         match verifier.hasher.config.cpu_pool() {
-            Some(cpu_pool) => cpu_pool.spawn_fn(move || verifier.verify()),
+            Some(cpu_pool) => Either::A(cpu_pool.spawn_fn(move || verifier.verify())),
             None => {
-                let cpu_pool = default_cpu_pool();
-                verifier.hasher.config.set_cpu_pool(cpu_pool.clone());
-                cpu_pool.spawn_fn(move || verifier.verify())
+                let executor = verifier
+                    .hasher
+                    .config
+                    .executor()
+                    .unwrap_or_else(default_executor);
+                Either::B(executor::spawn_non_blocking(&*executor, move || {
+                    verifier.verify()
+                }))
             }
         }
     }
+    /// Verifies the [`Verifier`](struct.Verifier.html)'s password against `hash` like
+    /// [`with_hash`](struct.Verifier.html#method.with_hash) followed by
+    /// [`verify`](struct.Verifier.html#method.verify) if there is a hash, and against a decoy
+    /// hash with [`verify_dummy`](struct.Verifier.html#method.verify_dummy) (returning `false`)
+    /// if there is none. Pass the stored hash of the user trying to log in, or `None` if there
+    /// is no such user, so that both cases take the same time
+    pub fn verify_or_dummy(&mut self, hash: Option<&str>) -> Result<bool, Error> {
+        match hash {
+            Some(hash) => self.with_hash(hash).verify(),
+            None => self.verify_dummy(),
+        }
+    }
+    /// Verifies `password` against the string-encoded `hash` using this
+    /// [`Verifier`](struct.Verifier.html)'s configuration, secret key (or keyring) and
+    /// additional data, without modifying the [`Verifier`](struct.Verifier.html) (any hash or
+    /// password it was provided with is ignored).
+    ///
+    /// Unlike [`verify`](struct.Verifier.html#method.verify), this method takes `&self` and
+    /// the hash and password as arguments, so a single [`Verifier`](struct.Verifier.html) can
+    /// be configured once and then shared across threads (e.g. in an
+    /// [`Arc`](https://doc.rust-lang.org/std/sync/struct.Arc.html)) to verify many passwords.
+    /// The `password_clearing` configuration applies to `password`; the secret key is never
+    /// cleared, regardless of the `secret_key_clearing` configuration
+    pub fn verify_password<'b, H, P>(&'b self, hash: H, password: P) -> Result<bool, Error>
+    where
+        H: AsRef<str>,
+        P: Into<Password<'b>>,
+    {
+        let hash_raw = decode_rust(hash.as_ref())?;
+        let mut hasher = self.hasher.borrowed()?;
+        hasher.password = Some(password.into());
+        verify_hash_raw(&mut hasher, &hash_raw, self.keyring.as_ref(), &self.policy)
+    }
+    /// Checks the [`Verifier`](struct.Verifier.html)'s hash against `config` (typically the
+    /// configuration of the [`Hasher`](struct.Hasher.html) you currently use to create new
+    /// hashes). See [`HashRaw::needs_rehash`](output/struct.HashRaw.html#method.needs_rehash).
+    /// Errors if the [`Verifier`](struct.Verifier.html) has no hash or if its string-encoded
+    /// hash cannot be parsed
+    pub fn needs_rehash(&self, config: &HasherConfig) -> Result<RehashReasons, Error> {
+        match self.hash_raw()? {
+            Some(hash_raw) => Ok(hash_raw.needs_rehash(config)),
+            None => Err(Error::new(ErrorKind::HashMissingError)),
+        }
+    }
     /// Allows you to provide [`Verifier`](struct.Verifier.html) with the additional data
     /// that was originally used to create the hash. Normally hashes are not created with
     /// additional data; so you are not likely to need this method
@@ -219,6 +449,21 @@ impl<'a> Verifier<'a> {
         self.hash = Hash::Raw(hash_raw.clone());
         self
     }
+    /// Allows you to provide [`Verifier`](struct.Verifier.html) with the
+    /// [`Keyring`](input/struct.Keyring.html) holding the secret keys hashes were created with.
+    /// When verifying against a hash that records a key id (i.e. a hash created by a
+    /// [`Hasher`](struct.Hasher.html) configured with a keyring), the key with that id is used
+    /// instead of the secret key provided with
+    /// [`with_secret_key`](struct.Verifier.html#method.with_secret_key), and verifying returns
+    /// an [`Error`](struct.Error.html) if the keyring has no such key. Hashes without a key id
+    /// are verified with the secret key as usual.
+    ///
+    /// [`Verifier`](struct.Verifier.html) keeps a copy of the keyring. The keyring is not
+    /// affected by the `secret_key_clearing` configuration
+    pub fn with_keyring(&mut self, keyring: &Keyring) -> &mut Verifier<'a> {
+        self.keyring = Some(keyring.clone());
+        self
+    }
     /// Allows you to provide [`Verifier`](struct.Verifier.html) with the password
     /// to verify against
     pub fn with_password<P>(&mut self, password: P) -> &mut Verifier<'a>
@@ -245,13 +490,7 @@ impl<'a> Verifier<'a> {
     /// Read-only access to the [`Verifier`](struct.Verifier.html)'s
     /// [`VerifierConfig`](config/struct.VerifierConfig.html)
     pub fn config(&self) -> VerifierConfig {
-        VerifierConfig::new(
-            /* backend */ self.hasher.config.backend(),
-            /* cpu_pool */ self.hasher.config.cpu_pool(),
-            /* password_clearing */ self.hasher.config.password_clearing(),
-            /* secret_key_clearing */ self.hasher.config.secret_key_clearing(),
-            /* threads */ self.hasher.config.threads(),
-        )
+        VerifierConfig::new(&self.hasher.config, self.policy)
     }
     /// Returns the [`Verifier`](struct.Verifier.html)'s string-encoded hash, if any
     pub fn hash(&self) -> Option<String> {
@@ -271,6 +510,11 @@ impl<'a> Verifier<'a> {
         }
     }
     /// Read-only access to the [`Verifier`](struct.Verifier.html)'s
+    /// [`Keyring`](input/struct.Keyring.html), if any
+    pub fn keyring(&self) -> Option<&Keyring> {
+        self.keyring.as_ref()
+    }
+    /// Read-only access to the [`Verifier`](struct.Verifier.html)'s
     /// [`Password`](input/struct.Password.html), if any
     pub fn password(&self) -> Option<&Password<'a>> {
         self.hasher.password()
@@ -282,9 +526,508 @@ impl<'a> Verifier<'a> {
     }
 }
 
+/// The parameters of a decoy hash
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+struct DummyParams {
+    hash_len: u32,
+    iterations: u32,
+    lanes: u32,
+    memory_size: u32,
+    salt_len: u32,
+    variant: Variant,
+    version: Version,
+}
+
+/// Returns the decoy hash with the parameters of `config`, creating it if needed
+fn dummy_hash_raw(config: &HasherConfig) -> Result<HashRaw, Error> {
+    let params = DummyParams {
+        hash_len: config.hash_len(),
+        iterations: config.iterations(),
+        lanes: config.lanes(),
+        memory_size: config.memory_size(),
+        salt_len: config.salt_len(),
+        variant: config.variant(),
+        version: config.version(),
+    };
+    let mut dummy_hashes = DUMMY_HASHES.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(hash_raw) = dummy_hashes.get(&params) {
+        return Ok(hash_raw.clone());
+    }
+    let hash_raw = HashRaw {
+        data: None,
+        iterations: params.iterations,
+        key_id: None,
+        lanes: params.lanes,
+        memory_size: params.memory_size,
+        raw_hash_bytes: generate_random_bytes(params.hash_len)?,
+        raw_salt_bytes: generate_random_bytes(params.salt_len)?,
+        variant: params.variant,
+        version: params.version,
+    };
+    dummy_hashes.insert(params, hash_raw.clone());
+    Ok(hash_raw)
+}
+
+/// Hashes the password of `hasher` with the parameters, salt and data of `hash_raw` and
+/// compares the result with `hash_raw` in constant time. The key with the hash's key id is
+/// picked from `keyring`, if any. Hashes that exceed `policy` are rejected up front
+fn verify_hash_raw(
+    hasher: &mut Hasher,
+    hash_raw: &HashRaw,
+    keyring: Option<&Keyring>,
+    policy: &VerifierPolicy,
+) -> Result<bool, Error> {
+    if let Err(e) = policy.check(hash_raw) {
+        hasher.clear();
+        return Err(e);
+    }
+    // The keyring's key with the hash's key id is used instead of the secret key
+    let keyring_secret_key = match (keyring, hash_raw.key_id()) {
+        (Some(keyring), Some(key_id)) => match keyring.get(key_id) {
+            Some(secret_key) => Some(secret_key.to_owned()),
+            None => {
+                hasher.clear();
+                return Err(Error::new(ErrorKind::KeyIdUnknownError)
+                    .add_context(format!("Key id: {}", encode_key_id(key_id))));
+            }
+        },
+        _ => None,
+    };
+    let secret_key = keyring_secret_key.map(|secret_key| hasher.secret_key.replace(secret_key));
+    // The parameters of the hash only apply to this hash; the configured ones are restored
+    // afterwards, e.g. for the decoy hash of `verify_dummy`
+    let config = hasher.config.clone();
+    hasher
+        .config
+        .set_hash_len(hash_raw.raw_hash_bytes().len() as u32);
+    hasher.config.set_iterations(hash_raw.iterations());
+    hasher.config.set_lanes(hash_raw.lanes());
+    hasher.config.set_memory_size(hash_raw.memory_size());
+    hasher.config.set_opt_out_of_secret_key(true);
+    hasher.config.set_variant(hash_raw.variant());
+    hasher.config.set_version(hash_raw.version());
+    hasher.salt = hash_raw.raw_salt_bytes().into();
+    // Associated data stored in the hash is used unless additional data was provided
+    let additional_data = hasher.additional_data.clone();
+    if let (None, Some(data)) = (&additional_data, hash_raw.data()) {
+        hasher.additional_data = Some(data.into());
+    }
+    let hash_raw2 = hasher.hash_raw();
+    hasher.additional_data = additional_data;
+    hasher.config = config;
+    if let Some(secret_key) = secret_key {
+        hasher.secret_key = secret_key;
+        hasher.clear();
+    }
+    let mut hash_raw2 = hash_raw2?;
+    // The key id and data are carried by the stored hash, not produced by hashing
+    hash_raw2.key_id = hash_raw.key_id.clone();
+    hash_raw2.data = hash_raw.data.clone();
+    Ok(hash_raw.ct_eq(&hash_raw2))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use input::Salt;
+
+    #[test]
+    fn test_verify_with_data_and_key_id() {
+        let mut hasher = Hasher::default();
+        hasher
+            .configure_iterations(2)
+            .configure_lanes(1)
+            .configure_memory_size(32)
+            .opt_out_of_secret_key(true)
+            .with_additional_data("ad")
+            .with_password("P@ssw0rd");
+        let mut hash_raw = hasher.hash_raw().unwrap();
+        hash_raw.data = Some(b"ad".to_vec());
+        hash_raw.key_id = Some(b"key".to_vec());
+        let hash = hash_raw.to_string();
+        assert!(hash.contains(",keyid=a2V5,data=YWQ$"));
+
+        // The data in the hash is used as additional data...
+        let mut verifier = Verifier::default();
+        verifier.with_hash(&hash).with_password("P@ssw0rd");
+        assert!(verifier.verify().unwrap());
+        assert!(verifier.additional_data().is_none());
+
+        // ... unless additional data is provided explicitly
+        verifier.with_additional_data("other");
+        assert!(!verifier.verify().unwrap());
+    }
+
+    #[test]
+    fn test_verify_and_upgrade() {
+        let mut hasher = Hasher::default();
+        hasher
+            .configure_iterations(2)
+            .configure_lanes(1)
+            .configure_memory_size(32)
+            .opt_out_of_secret_key(true)
+            .with_password("P@ssw0rd");
+        let hash = hasher.hash().unwrap();
+
+        // Invalid
+        let mut verifier = Verifier::default();
+        verifier.with_hash(&hash).with_password("wrong");
+        let verification = verifier.verify_and_upgrade(&hasher).unwrap();
+        assert_eq!(verification, Verification::Invalid);
+        assert!(!verification.is_valid());
+
+        // Valid and current
+        verifier.with_password("P@ssw0rd");
+        let verification = verifier.verify_and_upgrade(&hasher).unwrap();
+        assert_eq!(verification, Verification::Valid);
+        assert!(verification.new_hash().is_none());
+
+        // Valid with a replacement hash
+        let mut new_hasher = Hasher::default();
+        new_hasher
+            .configure_iterations(3)
+            .configure_lanes(1)
+            .configure_memory_size(64)
+            .opt_out_of_secret_key(true);
+        let mut verifier = Verifier::default();
+        verifier
+            .configure_password_clearing(true)
+            .with_hash(&hash)
+            .with_password(String::from("P@ssw0rd"));
+        let verification = verifier.verify_and_upgrade(&new_hasher).unwrap();
+        assert!(verification.is_valid());
+        match verification {
+            Verification::ValidWithRehash {
+                ref hash,
+                ref reasons,
+            } => {
+                assert_eq!(
+                    *reasons,
+                    RehashReasons::ITERATIONS | RehashReasons::MEMORY_SIZE
+                );
+                let hash_raw = hash.parse::<HashRaw>().unwrap();
+                assert_eq!(hash_raw.iterations(), 3);
+                assert_eq!(hash_raw.memory_size(), 64);
+            }
+            _ => panic!("expected a replacement hash, got {:?}", verification),
+        }
+        // The verifier's password clearing configuration is still honored...
+        assert!(verifier.password().is_none());
+        // ... and the replacement hash verifies
+        let mut verifier = Verifier::default();
+        verifier
+            .with_hash(verification.new_hash().unwrap())
+            .with_password("P@ssw0rd");
+        assert_eq!(
+            verifier.verify_and_upgrade(&new_hasher).unwrap(),
+            Verification::Valid
+        );
+        assert!(new_hasher.password().is_none());
+    }
+
+    #[test]
+    fn test_verify_async() {
+        use executor::tests::block_on;
+        use executor::ThreadPool;
+
+        let thread_pool = ThreadPool::new(2);
+        let mut hasher = Hasher::default();
+        hasher
+            .configure_executor(thread_pool.clone())
+            .configure_iterations(2)
+            .configure_lanes(1)
+            .configure_memory_size(32)
+            .opt_out_of_secret_key(true)
+            .with_password("P@ssw0rd");
+        let hash = block_on(hasher.hash_async()).unwrap();
+
+        let mut verifier = Verifier::default();
+        verifier
+            .configure_executor(thread_pool)
+            .with_hash(&hash)
+            .with_password("P@ssw0rd");
+        assert!(verifier.config().executor().is_some());
+        assert!(block_on(verifier.verify_async()).unwrap());
+        verifier.with_password("wrong");
+        assert!(!block_on(verifier.verify_async()).unwrap());
+    }
+
+    #[test]
+    fn test_verify_batch() {
+        let mut hasher = Hasher::default();
+        hasher
+            .configure_iterations(2)
+            .configure_lanes(1)
+            .configure_memory_size(32)
+            .opt_out_of_secret_key(true);
+        let hashes = hasher
+            .hash_batch(vec!["P@ssw0rd0", "P@ssw0rd1", "P@ssw0rd2"])
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        let mut verifier = Verifier::default();
+        verifier
+            .configure_policy(*VerifierPolicy::default().configure_max_iterations(2))
+            .configure_threads(2)
+            .with_password("ignored");
+        let pairs = vec![
+            (hashes[0].as_str(), "P@ssw0rd0"),
+            (hashes[1].as_str(), "wrong"),
+            ("$argon2id$", "P@ssw0rd"),
+            (hashes[2].as_str(), "P@ssw0rd2"),
+            (
+                "$argon2id$v=19$m=32,t=3,p=1$c29tZXNhbHQ$aGFzaGhhc2hoYXNoaGFzaA",
+                "P@ssw0rd",
+            ),
+        ];
+        let results = verifier.verify_batch(pairs).collect::<Vec<_>>();
+        assert_eq!(results.len(), 5);
+        assert!(results[0].as_ref().unwrap());
+        assert!(!results[1].as_ref().unwrap());
+        assert_eq!(
+            results[2].as_ref().unwrap_err().kind(),
+            ErrorKind::HashDecodeError,
+        );
+        assert!(results[3].as_ref().unwrap());
+        assert_eq!(
+            results[4].as_ref().unwrap_err().kind(),
+            ErrorKind::VerifierPolicyError,
+        );
+        assert!(verifier.password().is_some());
+    }
+
+    #[test]
+    fn test_verify_dummy() {
+        let mut hasher = Hasher::default();
+        hasher
+            .configure_iterations(2)
+            .configure_lanes(2)
+            .configure_memory_size(64)
+            .with_salt(Salt::random(16))
+            .with_secret_key("secret");
+        let hash = hasher.with_password("P@ssw0rd").hash().unwrap();
+
+        let mut verifier = Verifier::default();
+        verifier
+            .configure_from(hasher.config())
+            .configure_password_clearing(true)
+            .with_secret_key("secret");
+        verifier.with_password(String::from("P@ssw0rd"));
+        assert!(!verifier.verify_dummy().unwrap());
+        assert!(verifier.password().is_none());
+        assert_eq!(
+            verifier.verify_dummy().unwrap_err().kind(),
+            ErrorKind::PasswordMissingError,
+        );
+        verifier.with_password(String::from("P@ssw0rd"));
+        assert!(verifier.verify_or_dummy(Some(&hash)).unwrap());
+        verifier.with_password(String::from("P@ssw0rd"));
+        assert!(!verifier.verify_or_dummy(None).unwrap());
+
+        // The decoy hash has the configured parameters and is cached
+        let decoy = dummy_hash_raw(hasher.config()).unwrap();
+        assert_eq!(decoy, dummy_hash_raw(hasher.config()).unwrap());
+        let hash_raw = hash.parse::<HashRaw>().unwrap();
+        assert_eq!(decoy.iterations(), hash_raw.iterations());
+        assert_eq!(decoy.lanes(), hash_raw.lanes());
+        assert_eq!(decoy.memory_size(), hash_raw.memory_size());
+        assert_eq!(
+            decoy.raw_hash_bytes().len(),
+            hash_raw.raw_hash_bytes().len()
+        );
+        assert_eq!(decoy.raw_salt_bytes().len(), 16);
+        assert_ne!(decoy.raw_salt_bytes(), hash_raw.raw_salt_bytes());
+
+        // Verifying a hash with other parameters does not change those of the decoy hash
+        let mut weak_hasher = Hasher::default();
+        weak_hasher
+            .configure_hash_len(16)
+            .configure_iterations(1)
+            .configure_lanes(1)
+            .configure_memory_size(32)
+            .configure_variant(Variant::Argon2i)
+            .configure_version(Version::_0x10)
+            .with_secret_key("secret");
+        let weak_hash = weak_hasher.with_password("P@ssw0rd").hash().unwrap();
+        verifier.with_password(String::from("P@ssw0rd"));
+        assert!(verifier.with_hash(&weak_hash).verify().unwrap());
+        assert_eq!(dummy_hash_raw(&verifier.hasher.config).unwrap(), decoy);
+        verifier.with_password(String::from("P@ssw0rd"));
+        assert!(!verifier.verify_dummy().unwrap());
+    }
+
+    #[test]
+    fn test_verify_password() {
+        let mut keyring = Keyring::new();
+        keyring.add_key("k1", "secret").set_active_key("k1");
+        let mut hasher = Hasher::default();
+        hasher
+            .configure_iterations(2)
+            .configure_lanes(1)
+            .configure_memory_size(32)
+            .with_keyring(&keyring);
+        let hash = hasher.hash_password("P@ssw0rd").unwrap();
+
+        let mut verifier = Verifier::default();
+        verifier
+            .configure_secret_key_clearing(true)
+            .with_keyring(&keyring);
+        assert!(verifier.verify_password(&hash, "P@ssw0rd").unwrap());
+        assert!(!verifier.verify_password(&hash, "wrong").unwrap());
+        assert!(verifier.keyring().is_some());
+        assert!(verifier.hash().is_none());
+        assert!(verifier.password().is_none());
+        assert_eq!(
+            verifier
+                .verify_password("$argon2id$", "P@ssw0rd")
+                .unwrap_err()
+                .kind(),
+            ErrorKind::HashDecodeError,
+        );
+    }
+
+    #[test]
+    fn test_verify_policy() {
+        let mut hasher = Hasher::default();
+        hasher
+            .configure_iterations(2)
+            .configure_lanes(1)
+            .configure_memory_size(32)
+            .opt_out_of_secret_key(true);
+        let hash = hasher.hash_password("P@ssw0rd").unwrap();
+
+        let mut verifier = Verifier::default();
+        verifier.with_hash(&hash).with_password("P@ssw0rd");
+        assert_eq!(verifier.config().policy(), VerifierPolicy::default());
+        assert!(verifier.verify().unwrap());
+
+        let mut policy = VerifierPolicy::new();
+        policy.configure_max_memory_size(16);
+        verifier.configure_policy(policy);
+        assert_eq!(
+            verifier.verify().unwrap_err().kind(),
+            ErrorKind::VerifierPolicyError,
+        );
+        assert_eq!(
+            verifier
+                .verify_password(&hash, "P@ssw0rd")
+                .unwrap_err()
+                .kind(),
+            ErrorKind::VerifierPolicyError,
+        );
+
+        // A tampered with hash asking for 4 TiB of memory is rejected without hashing
+        let hash = hash.replace("m=32", &format!("m={}", u32::MAX));
+        let mut verifier = Verifier::default();
+        verifier.with_hash(&hash).with_password("P@ssw0rd");
+        assert_eq!(
+            verifier.verify().unwrap_err().kind(),
+            ErrorKind::VerifierPolicyError,
+        );
+    }
+
+    #[test]
+    fn test_needs_rehash() {
+        let mut hasher = Hasher::default();
+        hasher
+            .configure_iterations(2)
+            .configure_lanes(1)
+            .configure_memory_size(32)
+            .opt_out_of_secret_key(true)
+            .with_password("P@ssw0rd");
+        let hash = hasher.hash().unwrap();
+
+        let mut verifier = Verifier::default();
+        assert_eq!(
+            verifier.needs_rehash(hasher.config()).unwrap_err().kind(),
+            ErrorKind::HashMissingError,
+        );
+        verifier.with_hash(&hash);
+        assert!(verifier.needs_rehash(hasher.config()).unwrap().is_empty());
+        hasher.configure_iterations(3);
+        assert_eq!(
+            verifier.needs_rehash(hasher.config()).unwrap(),
+            RehashReasons::ITERATIONS,
+        );
+    }
+
+    #[test]
+    fn test_keyring_rotation() {
+        let mut keyring = Keyring::new();
+        keyring.add_key("k1", "old secret").set_active_key("k1");
+        let mut hasher = Hasher::default();
+        hasher
+            .configure_iterations(2)
+            .configure_lanes(1)
+            .configure_memory_size(32)
+            .with_keyring(&keyring)
+            .with_password("P@ssw0rd");
+        let hash = hasher.hash().unwrap();
+        assert!(hash.contains(",keyid=azE$"));
+
+        // The key is picked from the keyring by the hash's key id
+        keyring.add_key("k2", "new secret").set_active_key("k2");
+        let mut verifier = Verifier::default();
+        verifier
+            .with_hash(&hash)
+            .with_keyring(&keyring)
+            .with_password("P@ssw0rd")
+            .with_secret_key("unrelated");
+        assert!(verifier.verify().unwrap());
+        assert_eq!(verifier.secret_key().unwrap().as_bytes(), b"unrelated");
+
+        // Hashes under a retired key are upgraded to the active key
+        hasher.with_keyring(&keyring);
+        assert_eq!(
+            verifier.needs_rehash(hasher.config()).unwrap(),
+            RehashReasons::KEY_ID,
+        );
+        let verification = verifier.verify_and_upgrade(&hasher).unwrap();
+        let new_hash = verification.new_hash().unwrap().to_string();
+        assert!(new_hash.contains(",keyid=azI$"));
+        let mut verifier = Verifier::default();
+        verifier
+            .with_hash(&new_hash)
+            .with_keyring(&keyring)
+            .with_password("P@ssw0rd");
+        assert_eq!(
+            verifier.verify_and_upgrade(&hasher).unwrap(),
+            Verification::Valid
+        );
+
+        // A key id missing from the keyring is an error
+        keyring.remove_key("k1");
+        let mut verifier = Verifier::default();
+        verifier
+            .with_hash(&hash)
+            .with_keyring(&keyring)
+            .with_password("P@ssw0rd");
+        assert_eq!(
+            verifier.verify().unwrap_err().kind(),
+            ErrorKind::KeyIdUnknownError,
+        );
+
+        // The password and secret key are cleared on that error too
+        verifier
+            .configure_password_clearing(true)
+            .configure_secret_key_clearing(true)
+            .with_password(String::from("P@ssw0rd"))
+            .with_secret_key(String::from("secret"));
+        assert_eq!(
+            verifier.verify().unwrap_err().kind(),
+            ErrorKind::KeyIdUnknownError,
+        );
+        assert!(verifier.password().is_none());
+        assert!(verifier.secret_key().is_none());
+
+        // Hashing requires an active key
+        keyring.remove_key("k2");
+        hasher.with_keyring(&keyring);
+        assert_eq!(
+            hasher.hash().unwrap_err().kind(),
+            ErrorKind::SecretKeyMissingError,
+        );
+    }
 
     #[cfg(feature = "serde")]
     #[test]
@@ -0,0 +1,321 @@
+//! Tests of the `argonautica` command-line tool, run as a separate process
+#![cfg(feature = "cli")]
+
+#[macro_use]
+extern crate serde_json;
+
+use std::env;
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{self, Command, Output, Stdio};
+
+use serde_json::Value;
+
+const EXIT_SUCCESS: i32 = 0;
+const EXIT_INVALID: i32 = 1;
+const EXIT_ERROR: i32 = 2;
+
+/// Small parameters, so that hashing is fast
+const PARAMS: &[&str] = &[
+    "--backend",
+    "rust",
+    "--iterations",
+    "2",
+    "--lanes",
+    "1",
+    "--memory-size",
+    "32",
+];
+
+fn command(args: &[&str]) -> Command {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_argonautica"));
+    command.args(args);
+    command
+}
+
+/// Runs the tool with `args`, writing `stdin` to its standard input
+fn run(args: &[&str], stdin: &str) -> Output {
+    run_command(&mut command(args), stdin)
+}
+
+fn run_command(command: &mut Command, stdin: &str) -> Output {
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    // The tool may exit without reading its standard input, e.g. on invalid arguments
+    let _ = child.stdin.take().unwrap().write_all(stdin.as_bytes());
+    child.wait_with_output().unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8(output.stdout.clone()).unwrap()
+}
+
+fn json(output: &Output) -> Value {
+    serde_json::from_str(&stdout(output)).unwrap()
+}
+
+/// A file in the temporary directory, removed when dropped
+struct TempFile(PathBuf);
+
+impl TempFile {
+    fn new(name: &str, contents: &str) -> TempFile {
+        let path = env::temp_dir().join(format!("argonautica-cli-{}-{}", process::id(), name));
+        fs::write(&path, contents).unwrap();
+        TempFile(path)
+    }
+    fn path(&self) -> &str {
+        self.0.to_str().unwrap()
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+    }
+}
+
+fn gen_key() -> String {
+    let output = run(&["gen-key"], "");
+    assert_eq!(output.status.code(), Some(EXIT_SUCCESS));
+    stdout(&output)
+}
+
+fn hash(password: &str, secret_key_file: &TempFile) -> String {
+    let mut args = vec![
+        "hash",
+        "--format",
+        "json",
+        "--password-stdin",
+        "--secret-key-file",
+        secret_key_file.path(),
+    ];
+    args.extend_from_slice(PARAMS);
+    let output = run(&args, &format!("{}\n", password));
+    assert_eq!(output.status.code(), Some(EXIT_SUCCESS));
+    json(&output)["hash"].as_str().unwrap().to_string()
+}
+
+fn verify(hash: &str, password: &str, secret_key_file: &TempFile) -> Output {
+    let args = [
+        "verify",
+        "--backend",
+        "rust",
+        "--password-stdin",
+        "--secret-key-file",
+        secret_key_file.path(),
+        hash,
+    ];
+    run(&args, &format!("{}\n", password))
+}
+
+#[test]
+fn test_gen_key() {
+    let output = run(&["gen-key", "--format", "json", "--len", "16"], "");
+    assert_eq!(output.status.code(), Some(EXIT_SUCCESS));
+    let secret_key = json(&output)["secret_key"].as_str().unwrap().to_string();
+    // 16 bytes, base64-encoded with padding
+    assert_eq!(secret_key.len(), 24);
+
+    let output = run(&["gen-key", "--len", "0"], "");
+    assert_eq!(output.status.code(), Some(EXIT_ERROR));
+    assert!(stdout(&output).is_empty());
+}
+
+#[test]
+fn test_hash_and_verify() {
+    let secret_key_file = TempFile::new("secret_key", &gen_key());
+    let hash = hash("P@ssw0rd", &secret_key_file);
+    assert!(hash.starts_with("$argon2id$v=19$m=32,t=2,p=1$"));
+
+    let output = verify(&hash, "P@ssw0rd", &secret_key_file);
+    assert_eq!(output.status.code(), Some(EXIT_SUCCESS));
+    assert_eq!(stdout(&output), "valid\n");
+
+    let output = verify(&hash, "P@ssw0rd2", &secret_key_file);
+    assert_eq!(output.status.code(), Some(EXIT_INVALID));
+    assert_eq!(stdout(&output), "invalid\n");
+
+    // A different secret key does not verify either
+    let other_secret_key_file = TempFile::new("other_secret_key", &gen_key());
+    let output = verify(&hash, "P@ssw0rd", &other_secret_key_file);
+    assert_eq!(output.status.code(), Some(EXIT_INVALID));
+
+    // The secret key can also be read from an environment variable
+    let args = [
+        "verify",
+        "--format",
+        "json",
+        "--password-stdin",
+        "--secret-key-env",
+        "SECRET_KEY",
+        &hash,
+    ];
+    let secret_key = fs::read_to_string(secret_key_file.path()).unwrap();
+    let output = run_command(command(&args).env("SECRET_KEY", secret_key), "P@ssw0rd\n");
+    assert_eq!(output.status.code(), Some(EXIT_SUCCESS));
+    assert_eq!(json(&output), json!({ "valid": true }));
+}
+
+#[test]
+fn test_errors() {
+    let secret_key_file = TempFile::new("errors_secret_key", &gen_key());
+
+    // An unparseable hash
+    let output = verify("$argon2id$v=19$invalid", "P@ssw0rd", &secret_key_file);
+    assert_eq!(output.status.code(), Some(EXIT_ERROR));
+    assert!(stdout(&output).is_empty());
+
+    // A missing secret key file
+    let mut args = vec![
+        "hash",
+        "--password-stdin",
+        "--secret-key-file",
+        "/nonexistent/secret_key",
+    ];
+    args.extend_from_slice(PARAMS);
+    let output = run(&args, "P@ssw0rd\n");
+    assert_eq!(output.status.code(), Some(EXIT_ERROR));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("/nonexistent/secret_key"));
+
+    // Invalid arguments
+    let output = run(&["hash", "--iterations", "many"], "");
+    assert_eq!(output.status.code(), Some(EXIT_ERROR));
+    let output = run(&[], "");
+    assert_eq!(output.status.code(), Some(EXIT_ERROR));
+}
+
+#[test]
+fn test_inspect() {
+    let secret_key_file = TempFile::new("inspect_secret_key", &gen_key());
+    let hash = hash("P@ssw0rd", &secret_key_file);
+    let output = run(&["inspect", "--format", "json", &hash], "");
+    assert_eq!(output.status.code(), Some(EXIT_SUCCESS));
+    let inspected = json(&output);
+    assert_eq!(inspected["variant"], "argon2id");
+    assert_eq!(inspected["version"], "19");
+    assert_eq!(inspected["memory_size"], 32);
+    assert_eq!(inspected["iterations"], 2);
+    assert_eq!(inspected["lanes"], 1);
+    assert_eq!(inspected["salt_len"], 32);
+    assert_eq!(inspected["hash_len"], 32);
+    assert_eq!(inspected["key_id"], Value::Null);
+    assert_eq!(inspected["data"], Value::Null);
+
+    // The hash can also be read from standard input
+    let output = run(
+        &["inspect", "--format", "json", "-"],
+        &format!("{}\n", hash),
+    );
+    assert_eq!(output.status.code(), Some(EXIT_SUCCESS));
+    assert_eq!(json(&output), inspected);
+
+    let output = run(&["inspect", "not a hash"], "");
+    assert_eq!(output.status.code(), Some(EXIT_ERROR));
+}
+
+#[test]
+fn test_calibrate() {
+    let calibrate = [
+        "calibrate",
+        "--backend",
+        "rust",
+        "--lanes",
+        "1",
+        "--target",
+        "5ms",
+        "--max-memory",
+        "64KiB",
+    ];
+    let profile_file = TempFile::new("profile", "");
+    let args = [
+        "--samples",
+        "2",
+        "--format",
+        "json",
+        "--output",
+        profile_file.path(),
+    ];
+    let output = run(&[&calibrate[..], &args].concat(), "");
+    assert_eq!(output.status.code(), Some(EXIT_SUCCESS));
+    assert!(stdout(&output).is_empty());
+    let profile = fs::read_to_string(profile_file.path()).unwrap();
+    let profile: Value = serde_json::from_str(&profile).unwrap();
+    assert_eq!(profile["backend"], "rust");
+    assert_eq!(profile["lanes"], 1);
+    assert!(profile["memorySize"].as_u64().unwrap() <= 64);
+    assert!(profile["iterations"].as_u64().unwrap() >= 1);
+
+    // The profile is written to standard output by default, in TOML
+    let output = run(&[&calibrate[..], &["--samples", "2"]].concat(), "");
+    assert_eq!(output.status.code(), Some(EXIT_SUCCESS));
+    assert!(stdout(&output).contains("memorySize = "));
+
+    // At least 2 samples are needed
+    let output = run(&[&calibrate[..], &["--samples", "1"]].concat(), "");
+    assert_eq!(output.status.code(), Some(EXIT_ERROR));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("At least 2 samples"));
+
+    let output = run(&["calibrate", "--target", "fast"], "");
+    assert_eq!(output.status.code(), Some(EXIT_ERROR));
+}
+
+#[test]
+fn test_audit() {
+    let secret_key_file = TempFile::new("audit_secret_key", &gen_key());
+    let hash = hash("P@ssw0rd", &secret_key_file);
+    let output = run(
+        &["audit", "--format", "json"],
+        &format!("{}\n  {}\n", hash, hash),
+    );
+    assert_eq!(output.status.code(), Some(EXIT_SUCCESS));
+    let report = json(&output);
+    assert_eq!(report["total"], 2);
+    assert_eq!(report["invalid"], 0);
+    assert_eq!(report["variants"], json!({ "argon2id": 2 }));
+    assert_eq!(report["findings"], json!([]));
+
+    // Flagged hashes are listed by line, without their contents
+    let dump = TempFile::new(
+        "audit_dump",
+        &format!("user,hash\nalice,\"{}\"\nbob,hunter2\n", hash),
+    );
+    let output = run(&["audit", "--csv-column", "hash", dump.path()], "");
+    assert_eq!(output.status.code(), Some(EXIT_INVALID));
+    let report = stdout(&output);
+    assert!(report.contains("total: 2\ninvalid: 1\n"));
+    assert!(report.contains("line 3: invalid: "));
+    assert!(!report.contains("hunter2"));
+
+    // Hashes weaker than a profile are flagged too
+    let profile = json!({
+        "backend": "rust",
+        "hashLen": 32,
+        "iterations": 3,
+        "keyId": null,
+        "lanes": 1,
+        "memorySize": 32,
+        "optOutOfSecretKey": false,
+        "passwordClearing": false,
+        "saltLen": 32,
+        "secretKeyClearing": false,
+        "threads": 1,
+        "variant": "argon2id",
+        "version": "_0x13",
+    });
+    let profile = TempFile::new("audit_profile.json", &profile.to_string());
+    let args = ["audit", "--format", "json", "--profile", profile.path()];
+    let output = run(&args, &hash);
+    assert_eq!(output.status.code(), Some(EXIT_INVALID));
+    assert_eq!(
+        json(&output)["findings"][0]["reasons"],
+        json!(["iterations"])
+    );
+
+    let output = run(&["audit", "/nonexistent/hashes"], "");
+    assert_eq!(output.status.code(), Some(EXIT_ERROR));
+}